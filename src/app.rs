@@ -30,6 +30,33 @@ pub fn build_app() -> Command {
                 .help("Check your system for potential problems to run tasks")
                 .required(false),
         )
+        .arg(
+            Arg::new("fix")
+                .long("fix")
+                .action(ArgAction::SetTrue)
+                .requires("doctor")
+                .help("Fix problems found by --doctor, such as installing missing tools")
+                .required(false),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .num_args(1)
+                .value_parser(["text", "json"])
+                .requires("doctor")
+                .conflicts_with("fix")
+                .help("Output format of --doctor: text or json")
+                .required(false),
+        )
+        .arg(
+            Arg::new("yes")
+                .long("yes")
+                .short('y')
+                .action(ArgAction::SetTrue)
+                .requires("fix")
+                .help("Apply fixes without confirmation")
+                .required(false),
+        )
         .arg(
             Arg::new("no-dotenv")
                 .long("no-dotenv")
@@ -37,11 +64,101 @@ pub fn build_app() -> Command {
                 .help("Disable to load .env file")
                 .required(false),
         )
+        .arg(
+            Arg::new("env")
+                .long("env")
+                .num_args(1)
+                .conflicts_with("no-dotenv")
+                .help("Load .env, .env.<name> and .env.<name>.local for tasks, NODE_ENV as default")
+                .required(false),
+        )
+        .arg(
+            Arg::new("env-file")
+                .long("env-file")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .conflicts_with("no-dotenv")
+                .help("Load the dotenv file on top of .env files, and it can be repeated")
+                .required(false),
+        )
+        .arg(
+            Arg::new("env-override")
+                .long("env-override")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no-dotenv")
+                .help("Override the variables of the environment with the ones of dotenv files")
+                .required(false),
+        )
+        .arg(
+            Arg::new("env-show")
+                .long("env-show")
+                .action(ArgAction::SetTrue)
+                .help("Print the variables from dotenv files with their source files, secrets masked")
+                .required(false),
+        )
+        .arg(
+            Arg::new("nix")
+                .long("nix")
+                .action(ArgAction::SetTrue)
+                .help("Load the environment of direnv(.envrc), devenv or Nix flake before running tasks, TK_NIX=1 as default")
+                .required(false),
+        )
+        .arg(
+            Arg::new("timestamps")
+                .long("timestamps")
+                .action(ArgAction::SetTrue)
+                .help("Prefix each output line of tasks with a timestamp")
+                .required(false),
+        )
+        .arg(
+            Arg::new("prefix")
+                .long("prefix")
+                .action(ArgAction::SetTrue)
+                .help("Prefix each output line of tasks with the task name")
+                .required(false),
+        )
+        .arg(
+            Arg::new("report")
+                .long("report")
+                .num_args(1)
+                .help("Write a report of executed tasks for CI: junit=report.xml or tap=report.tap")
+                .required(false),
+        )
+        .arg(
+            Arg::new("log-dir")
+                .long("log-dir")
+                .num_args(1)
+                .help("Write the output of each task to a log file under the directory, TK_LOG_DIR as default")
+                .required(false),
+        )
+        .arg(
+            Arg::new("log-retention")
+                .long("log-retention")
+                .num_args(1)
+                .help("Log files to keep: latest N logs per task(10), or logs of the last N days(7d)")
+                .required(false),
+        )
+        .arg(
+            Arg::new("logs")
+                .long("logs")
+                .num_args(1)
+                .help("Print the latest log of the task")
+                .required(false),
+        )
+        .arg(
+            Arg::new("follow")
+                .long("follow")
+                .short('f')
+                .action(ArgAction::SetTrue)
+                .requires("logs")
+                .help("Keep printing the log as it grows, used with --logs")
+                .required(false),
+        )
         .arg(
             Arg::new("init")
                 .long("init")
                 .num_args(1)
-                .value_parser(["shell", "make", "jbang", "just","vscode","pipenv","deno","argc","nur"])
+                .value_parser(["shell", "make", "jbang", "just","vscode","pipenv","deno","argc","nur","mise"])
                 .help("Create a new task file by runner name")
                 .required(false),
         )
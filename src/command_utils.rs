@@ -1,9 +1,10 @@
+use crate::common::capture;
+use crate::common::process::{spawn_and_wait, TaskProcess};
 use crate::errors::KeeperError;
 use colored::Colorize;
 use error_stack::{IntoReport, Report, ResultExt};
 use std::collections::HashMap;
-use std::io;
-use std::io::{Read, Write};
+use std::io::Write;
 use std::process::{Command, ExitStatus, Output, Stdio};
 use which::which;
 
@@ -11,22 +12,17 @@ pub struct CommandOutput {
     pub status: ExitStatus,
     pub stdout: Option<String>,
     pub stderr: Option<String>,
+    /// stdout and stderr in the order they were written, only available when output is captured
+    pub merged: Option<String>,
 }
 
 impl CommandOutput {
-    pub fn from(output: Output) -> Self {
+    pub fn from_status(status: ExitStatus) -> Self {
         CommandOutput {
-            status: output.status,
-            stdout: if output.stdout.len() == 0 {
-                None
-            } else {
-                String::from_utf8(output.stdout).ok()
-            },
-            stderr: if output.stderr.len() == 0 {
-                None
-            } else {
-                String::from_utf8(output.stderr).ok()
-            },
+            status,
+            stdout: None,
+            stderr: None,
+            merged: None,
         }
     }
 }
@@ -86,6 +82,16 @@ pub fn run_command_line_from_stdin(
     command_line: &str,
     input: &str,
     verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    run_command_line_from_stdin_with_env_vars(command_line, input, &HashMap::new(), verbose)
+}
+
+/// run the command line with the input from stdin, and the variables are for the command only
+pub fn run_command_line_from_stdin_with_env_vars(
+    command_line: &str,
+    input: &str,
+    env_vars: &HashMap<String, String>,
+    verbose: bool,
 ) -> Result<CommandOutput, Report<KeeperError>> {
     let command_and_args = split_command_line(command_line).unwrap();
     let command_name = &command_and_args[0];
@@ -98,24 +104,24 @@ pub fn run_command_line_from_stdin(
         println!("[tk] command line:  {:?}", command_line);
     }
     if is_command_available(&command_name) {
-        let mut child = Command::new(command_name)
-            .args(&args)
-            .envs(std::env::vars())
+        let mut command = Command::new(command_name);
+        command.args(&args).envs(std::env::vars()).envs(env_vars);
+        if capture::is_capture_required() {
+            return intercept_output_with_input(&mut command, Some(input));
+        }
+        command
             .stdin(Stdio::piped())
             .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .spawn()
+            .stderr(Stdio::inherit());
+        let mut task_process = TaskProcess::spawn(&mut command)
             .change_context(KeeperError::FailedToRunTasks(format!("{:?}", command_name)))?;
-        child
-            .stdin
-            .as_mut()
-            .ok_or("Child process stdin has not been captured!")
-            .unwrap()
-            .write_all(input.as_bytes())
-            .unwrap();
-        child
-            .wait_with_output()
-            .map(CommandOutput::from)
+        // close stdin after writing, so the child sees EOF
+        if let Some(mut stdin) = task_process.child_mut().stdin.take() {
+            stdin.write_all(input.as_bytes()).unwrap();
+        }
+        task_process
+            .wait()
+            .map(CommandOutput::from_status)
             .change_context(KeeperError::FailedToRunTasks(format!("{:?}", command_name)))
     } else {
         println!(
@@ -154,21 +160,30 @@ pub fn run_command_with_env_vars(
     if verbose {
         println!("[tk] command line:  {:?}", command);
     }
-    if std::env::var("TK_TASK_NAME").is_ok() {
+    if capture::is_capture_required() {
         return intercept_output(&mut command);
     }
     command
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .output()
-        .map(CommandOutput::from)
+        .stderr(Stdio::inherit());
+    spawn_and_wait(&mut command)
+        .map(CommandOutput::from_status)
         .change_context(KeeperError::FailedToRunTasks(format!("{:?}", command)))
 }
 
 pub fn run_command_by_shell(
     command_line: &str,
     verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    run_command_by_shell_in_dir(command_line, &None, verbose)
+}
+
+/// run the command line by the shell in the working directory, such as scripts of workspace members
+pub fn run_command_by_shell_in_dir(
+    command_line: &str,
+    working_dir: &Option<String>,
+    verbose: bool,
 ) -> Result<CommandOutput, Report<KeeperError>> {
     let mut command = if cfg!(target_os = "windows") {
         Command::new("cmd")
@@ -181,74 +196,43 @@ pub fn run_command_by_shell(
     } else {
         command.arg("-c").arg(command_line)
     };
+    if let Some(current_dir) = working_dir {
+        command.current_dir(current_dir);
+    }
     if verbose {
         println!("[tk] command line:  {:?}", command);
     }
+    command.envs(std::env::vars());
+    if capture::is_capture_required() {
+        return intercept_output(&mut command);
+    }
     command
-        .envs(std::env::vars())
         .stdin(Stdio::inherit())
         .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .output()
-        .map(CommandOutput::from)
+        .stderr(Stdio::inherit());
+    spawn_and_wait(&mut command)
+        .map(CommandOutput::from_status)
         .change_context(KeeperError::FailedToRunTasks(format!("{:?}", command)))
 }
 
 pub fn intercept_output(command: &mut Command) -> Result<CommandOutput, Report<KeeperError>> {
-    let mut child = command
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .change_context(KeeperError::FailedToRunTasks(format!("{:?}", command)))?;
-    // Create threads to handle both streams
-    let mut stdout = child.stdout.take().unwrap();
-    let mut stderr = child.stderr.take().unwrap();
-
-    let stdout_thread = std::thread::spawn(move || {
-        let mut stdout_bytes = Vec::new();
-        let mut buffer = [0; 32];
-        while let Ok(n) = stdout.read(&mut buffer) {
-            if n == 0 {
-                break;
-            }
-            // Print to console
-            let content = &buffer[..n];
-            io::stdout().write_all(content).unwrap();
-            // Collect output
-            stdout_bytes.extend_from_slice(content);
-        }
-        String::from_utf8_lossy(&stdout_bytes).to_string()
-    });
-
-    let stderr_thread = std::thread::spawn(move || {
-        let mut stderr_bytes = Vec::new();
-        let mut buffer = [0; 32];
-        while let Ok(n) = stderr.read(&mut buffer) {
-            if n == 0 {
-                break;
-            }
-            // Print to console
-            let content = &buffer[..n];
-            io::stderr().write_all(content).unwrap();
-            // You can also process the error output here
-            stderr_bytes.extend_from_slice(content);
-        }
-        String::from_utf8_lossy(&stderr_bytes).to_string()
-    });
-
-    let output = stdout_thread.join().unwrap();
-    let error = stderr_thread.join().unwrap();
+    intercept_output_with_input(command, None)
+}
 
-    let status = child.wait().unwrap();
+/// Run the command through the capture layer: output is displayed live, dispatched to the sinks
+/// of the current settings, and collected for notifications.
+pub fn intercept_output_with_input(
+    command: &mut Command,
+    input: Option<&str>,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    let output = capture::capture(command, input, capture::default_sinks())
+        .change_context(KeeperError::FailedToRunTasks(format!("{:?}", command)))?;
+    let non_empty = |text: String| if text.is_empty() { None } else { Some(text) };
     Ok(CommandOutput {
-        status,
-        stdout: if output.is_empty() {
-            None
-        } else {
-            Some(output)
-        },
-        stderr: if error.is_empty() { None } else { Some(error) },
+        status: output.status,
+        stdout: non_empty(output.stdout),
+        stderr: non_empty(output.stderr),
+        merged: non_empty(output.merged),
     })
 }
 
@@ -0,0 +1,441 @@
+//! Capture the output of a task while it is still displayed live.
+//!
+//! stdout and stderr are read with large buffers on two threads and framed into lines,
+//! so that multi-byte UTF-8 characters are never split. Frames are dispatched on one thread
+//! in the order they were read, which keeps stdout and stderr merged in true order for
+//! sinks like log files. Incomplete lines(prompts, progress bars) are dispatched as soon as
+//! they are read. Only the last `OUTPUT_TAIL_SIZE` bytes of each stream are kept in memory for
+//! reports and notifications, and the full output goes to the log file with `--log-dir`.
+use crate::common::logs::{create_log_sink, LogRetention, DEFAULT_LOGS_KEPT};
+use crate::common::process::TaskProcess;
+use crate::common::report;
+use chrono::{DateTime, Local};
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::sync::RwLock;
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+const TIMESTAMP_FORMAT: &str = "%H:%M:%S%.3f";
+/// bytes of output kept for reports and notifications
+const OUTPUT_TAIL_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// A piece of output: a complete line with its line break, or an incomplete line
+pub struct OutputChunk<'a> {
+    pub stream: Stream,
+    pub text: &'a str,
+    /// the chunk starts a new line on its stream
+    pub line_start: bool,
+    pub timestamp: DateTime<Local>,
+}
+
+pub trait OutputSink {
+    fn write(&mut self, chunk: &OutputChunk) -> io::Result<()>;
+
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Settings of the capture layer, set once from command line options
+#[derive(Debug, Clone, Default)]
+pub struct CaptureSettings {
+    /// prefix each output line with a timestamp
+    pub timestamps: bool,
+    /// prefix each output line with the name of the task
+    pub prefix: bool,
+    /// tee the output of each task into a log file under this directory
+    pub log_dir: Option<PathBuf>,
+    pub log_retention: LogRetention,
+    /// runner or manager of the task running now
+    pub runner: Option<String>,
+    /// name of the task running now
+    pub task_name: Option<String>,
+}
+
+static SETTINGS: RwLock<CaptureSettings> = RwLock::new(CaptureSettings {
+    timestamps: false,
+    prefix: false,
+    log_dir: None,
+    log_retention: LogRetention::Count(DEFAULT_LOGS_KEPT),
+    runner: None,
+    task_name: None,
+});
+
+pub fn settings() -> CaptureSettings {
+    SETTINGS.read().unwrap().clone()
+}
+
+pub fn update_settings<F: FnOnce(&mut CaptureSettings)>(update: F) {
+    update(&mut SETTINGS.write().unwrap());
+}
+
+/// Output must be captured for notifications, log files and reports, or to decorate the lines displayed
+pub fn is_capture_required() -> bool {
+    let settings = settings();
+    settings.timestamps
+        || settings.prefix
+        || settings.log_dir.is_some()
+        || report::is_enabled()
+        || std::env::var("TK_TASK_NAME").is_ok()
+        || std::env::var("TK_TASK_ID").is_ok()
+}
+
+/// Sinks for the current settings: the terminal, and the log file of the task with a log directory
+pub fn default_sinks() -> Vec<Box<dyn OutputSink>> {
+    let settings = settings();
+    let prefix = if settings.prefix {
+        settings.task_name.clone()
+    } else {
+        None
+    };
+    let mut sinks: Vec<Box<dyn OutputSink>> =
+        vec![Box::new(TerminalSink::new(prefix, settings.timestamps))];
+    if let Some(log_dir) = &settings.log_dir {
+        let runner = settings.runner.as_deref().unwrap_or("tk");
+        let task_name = settings.task_name.as_deref().unwrap_or("command");
+        match create_log_sink(log_dir, runner, task_name, settings.log_retention) {
+            Ok(file_sink) => sinks.push(Box::new(file_sink)),
+            Err(err) => eprintln!("[tk] failed to create log file in {}: {}", log_dir.display(), err),
+        }
+    }
+    sinks
+}
+
+fn line_decoration(prefix: &Option<String>, timestamps: bool, chunk: &OutputChunk) -> String {
+    let mut decoration = String::new();
+    if !chunk.line_start {
+        return decoration;
+    }
+    if timestamps {
+        decoration.push_str(&chunk.timestamp.format(TIMESTAMP_FORMAT).to_string());
+        decoration.push(' ');
+    }
+    if let Some(prefix) = prefix {
+        decoration.push_str(&format!("[{}] ", prefix));
+    }
+    decoration
+}
+
+/// Display output on the terminal, stdout and stderr kept apart
+pub struct TerminalSink {
+    prefix: Option<String>,
+    timestamps: bool,
+}
+
+impl TerminalSink {
+    pub fn new(prefix: Option<String>, timestamps: bool) -> Self {
+        TerminalSink { prefix, timestamps }
+    }
+}
+
+impl OutputSink for TerminalSink {
+    fn write(&mut self, chunk: &OutputChunk) -> io::Result<()> {
+        let decoration = line_decoration(&self.prefix, self.timestamps, chunk);
+        match chunk.stream {
+            Stream::Stdout => {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(decoration.as_bytes())?;
+                stdout.write_all(chunk.text.as_bytes())?;
+                stdout.flush()
+            }
+            Stream::Stderr => {
+                let mut stderr = io::stderr().lock();
+                stderr.write_all(decoration.as_bytes())?;
+                stderr.write_all(chunk.text.as_bytes())?;
+                stderr.flush()
+            }
+        }
+    }
+}
+
+/// Write stdout and stderr merged into one file
+pub struct FileSink {
+    writer: BufWriter<File>,
+    timestamps: bool,
+}
+
+impl FileSink {
+    /// create a new file, and fail if the file exists
+    pub fn create(path: &Path, timestamps: bool) -> io::Result<Self> {
+        File::create_new(path).map(|file| FileSink {
+            writer: BufWriter::new(file),
+            timestamps,
+        })
+    }
+}
+
+impl OutputSink for FileSink {
+    fn write(&mut self, chunk: &OutputChunk) -> io::Result<()> {
+        let decoration = line_decoration(&None, self.timestamps, chunk);
+        self.writer.write_all(decoration.as_bytes())?;
+        self.writer.write_all(chunk.text.as_bytes())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// the last lines of the output, kept under `OUTPUT_TAIL_SIZE` bytes
+pub struct CapturedOutput {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+    /// stdout and stderr in the order they were written
+    pub merged: String,
+}
+
+/// Keep the tail of a text within `limit` bytes. The text is trimmed once it doubles the limit,
+/// so appending stays cheap.
+struct TailBuffer {
+    text: String,
+    limit: usize,
+    /// the text starts with the rest of a line cut by trimming
+    partial_line: bool,
+}
+
+impl TailBuffer {
+    fn new(limit: usize) -> Self {
+        TailBuffer {
+            text: String::new(),
+            limit,
+            partial_line: false,
+        }
+    }
+
+    fn push_str(&mut self, text: &str) {
+        self.text.push_str(text);
+        if self.text.len() > self.limit * 2 {
+            self.trim();
+        }
+    }
+
+    fn trim(&mut self) {
+        if self.text.len() <= self.limit {
+            return;
+        }
+        let mut start = self.text.len() - self.limit;
+        while !self.text.is_char_boundary(start) {
+            start += 1;
+        }
+        self.partial_line = self.text.as_bytes()[start - 1] != b'\n';
+        self.text.drain(..start);
+    }
+
+    /// the tail, starting from a whole line if the text was cut
+    fn into_string(mut self) -> String {
+        self.trim();
+        if self.partial_line
+            && let Some(offset) = self.text.find('\n')
+            && offset + 1 < self.text.len()
+        {
+            self.text.drain(..offset + 1);
+        }
+        self.text
+    }
+}
+
+struct Frame {
+    stream: Stream,
+    text: String,
+    timestamp: DateTime<Local>,
+}
+
+/// Run the command with piped stdout and stderr, dispatch its output to the sinks and collect it.
+/// With `input`, the text is written to the command's stdin, otherwise stdin is inherited.
+pub fn capture(
+    command: &mut Command,
+    input: Option<&str>,
+    mut sinks: Vec<Box<dyn OutputSink>>,
+) -> io::Result<CapturedOutput> {
+    command
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut task_process = TaskProcess::spawn(command)?;
+    let child = task_process.child_mut();
+    let stdout = child.stdout.take().unwrap();
+    let stderr = child.stderr.take().unwrap();
+    // write stdin on its own thread, a child may fill its output pipes before reading all input
+    let stdin_thread = match (input, child.stdin.take()) {
+        (Some(text), Some(mut stdin)) => {
+            let text = text.to_string();
+            Some(std::thread::spawn(move || {
+                let _ = stdin.write_all(text.as_bytes());
+            }))
+        }
+        _ => None,
+    };
+    let (sender, receiver) = mpsc::channel::<Frame>();
+    let stderr_sender = sender.clone();
+    let stdout_thread = std::thread::spawn(move || read_frames(Stream::Stdout, stdout, sender));
+    let stderr_thread =
+        std::thread::spawn(move || read_frames(Stream::Stderr, stderr, stderr_sender));
+    let mut stdout_text = TailBuffer::new(OUTPUT_TAIL_SIZE);
+    let mut stderr_text = TailBuffer::new(OUTPUT_TAIL_SIZE);
+    let mut merged = TailBuffer::new(OUTPUT_TAIL_SIZE);
+    let mut stdout_line_start = true;
+    let mut stderr_line_start = true;
+    // ends when both reader threads are done
+    for frame in receiver {
+        let line_start = match frame.stream {
+            Stream::Stdout => &mut stdout_line_start,
+            Stream::Stderr => &mut stderr_line_start,
+        };
+        let chunk = OutputChunk {
+            stream: frame.stream,
+            text: &frame.text,
+            line_start: *line_start,
+            timestamp: frame.timestamp,
+        };
+        for sink in sinks.iter_mut() {
+            // a broken sink must not break the task
+            let _ = sink.write(&chunk);
+        }
+        *line_start = frame.text.ends_with('\n');
+        match frame.stream {
+            Stream::Stdout => stdout_text.push_str(&frame.text),
+            Stream::Stderr => stderr_text.push_str(&frame.text),
+        }
+        merged.push_str(&frame.text);
+    }
+    stdout_thread.join().ok();
+    stderr_thread.join().ok();
+    if let Some(stdin_thread) = stdin_thread {
+        stdin_thread.join().ok();
+    }
+    for sink in sinks.iter_mut() {
+        let _ = sink.finish();
+    }
+    let status = task_process.wait()?;
+    Ok(CapturedOutput {
+        status,
+        stdout: stdout_text.into_string(),
+        stderr: stderr_text.into_string(),
+        merged: merged.into_string(),
+    })
+}
+
+fn read_frames<R: Read>(stream: Stream, mut reader: R, sender: mpsc::Sender<Frame>) {
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    let mut pending: Vec<u8> = Vec::new();
+    loop {
+        let size = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(size) => size,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => break,
+        };
+        pending.extend_from_slice(&buffer[..size]);
+        let timestamp = Local::now();
+        for text in take_frames(&mut pending) {
+            if sender.send(Frame { stream, text, timestamp }).is_err() {
+                return;
+            }
+        }
+    }
+    if !pending.is_empty() {
+        let text = String::from_utf8_lossy(&pending).to_string();
+        let _ = sender.send(Frame {
+            stream,
+            text,
+            timestamp: Local::now(),
+        });
+    }
+}
+
+/// Take complete lines and the decodable part of an incomplete line out of `pending`.
+/// An incomplete UTF-8 sequence at the end stays in `pending` until more bytes are read.
+fn take_frames(pending: &mut Vec<u8>) -> Vec<String> {
+    let mut frames = vec![];
+    let mut consumed = 0;
+    while let Some(offset) = pending[consumed..].iter().position(|byte| *byte == b'\n') {
+        let line_end = consumed + offset + 1;
+        frames.push(String::from_utf8_lossy(&pending[consumed..line_end]).to_string());
+        consumed = line_end;
+    }
+    let rest = &pending[consumed..];
+    if !rest.is_empty() {
+        match std::str::from_utf8(rest) {
+            Ok(text) => {
+                frames.push(text.to_string());
+                consumed = pending.len();
+            }
+            Err(error) => {
+                if error.error_len().is_none() {
+                    // incomplete sequence at the end
+                    let valid_up_to = error.valid_up_to();
+                    if valid_up_to > 0 {
+                        frames.push(String::from_utf8_lossy(&rest[..valid_up_to]).to_string());
+                        consumed += valid_up_to;
+                    }
+                } else {
+                    frames.push(String::from_utf8_lossy(rest).to_string());
+                    consumed = pending.len();
+                }
+            }
+        }
+    }
+    pending.drain(..consumed);
+    frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_frames() {
+        let text = "first\nsecond\n中文";
+        let bytes = text.as_bytes();
+        // cut in the middle of the last character
+        let mut pending = bytes[..bytes.len() - 1].to_vec();
+        let frames = take_frames(&mut pending);
+        assert_eq!(vec!["first\n", "second\n", "中"], frames);
+        assert_eq!(2, pending.len());
+        pending.extend_from_slice(&bytes[bytes.len() - 1..]);
+        assert_eq!(vec!["文"], take_frames(&mut pending));
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_capture() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo first; echo second >&2; echo third"]);
+        let output = capture(&mut command, None, vec![]).unwrap();
+        assert!(output.status.success());
+        assert_eq!("first\nthird\n", output.stdout);
+        assert_eq!("second\n", output.stderr);
+        assert_eq!(3, output.merged.lines().count());
+    }
+
+    #[test]
+    fn test_tail_buffer() {
+        let mut buffer = TailBuffer::new(16);
+        for index in 0..100 {
+            buffer.push_str(&format!("line {}\n", index));
+            assert!(buffer.text.len() <= 32 + 8);
+        }
+        assert_eq!("line 98\nline 99\n", buffer.into_string());
+        let mut buffer = TailBuffer::new(4);
+        buffer.push_str("中文中文中文");
+        assert_eq!("文", buffer.into_string());
+        let mut buffer = TailBuffer::new(16);
+        buffer.push_str("short\n");
+        assert_eq!("short\n", buffer.into_string());
+    }
+}
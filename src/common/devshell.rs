@@ -0,0 +1,251 @@
+//! Development environment of Nix and direnv, loaded before running tasks with `--nix` or `TK_NIX=1`:
+//! `.envrc` evaluated by direnv, `devenv print-dev-env` for devenv.nix, or `nix print-dev-env` for flake.nix.
+//! Nix environments are cached by the hash of the nix files and their lock file.
+use crate::command_utils::{capture_command_output, is_command_available};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+
+/// variables not applied, the same as `nix develop`
+const IGNORED_VARIABLES: [&str; 17] = [
+    "BASHOPTS",
+    "HOME",
+    "NIX_BUILD_TOP",
+    "NIX_ENFORCE_PURITY",
+    "NIX_LOG_FD",
+    "NIX_REMOTE",
+    "PPID",
+    "SHELL",
+    "SHELLOPTS",
+    "SSL_CERT_FILE",
+    "NIX_SSL_CERT_FILE",
+    "TEMP",
+    "TEMPDIR",
+    "TERM",
+    "TMP",
+    "TMPDIR",
+    "TZ",
+];
+
+/// Where the development environment comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevShellSource {
+    Direnv,
+    Devenv,
+    Flake,
+}
+
+impl DevShellSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DevShellSource::Direnv => "direnv",
+            DevShellSource::Devenv => "devenv",
+            DevShellSource::Flake => "nix",
+        }
+    }
+
+    /// files to compute the cache key
+    fn source_files(&self) -> &'static [&'static str] {
+        match self {
+            DevShellSource::Direnv => &[".envrc"],
+            DevShellSource::Devenv => &["devenv.nix", "devenv.yaml", "devenv.lock"],
+            DevShellSource::Flake => &["flake.nix", "flake.lock"],
+        }
+    }
+}
+
+/// `--nix` or `TK_NIX=1`
+pub fn is_enabled(cli_nix: bool) -> bool {
+    cli_nix
+        || env::var("TK_NIX")
+            .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+}
+
+/// `.envrc` with direnv first, then devenv.nix with devenv, and flake.nix with nix
+pub fn detect_source() -> Option<DevShellSource> {
+    let current_dir = env::current_dir().ok()?;
+    if current_dir.join(".envrc").exists() && is_command_available("direnv") {
+        Some(DevShellSource::Direnv)
+    } else if current_dir.join("devenv.nix").exists() && is_command_available("devenv") {
+        Some(DevShellSource::Devenv)
+    } else if current_dir.join("flake.nix").exists() && is_command_available("nix") {
+        Some(DevShellSource::Flake)
+    } else {
+        None
+    }
+}
+
+/// Variables of the development environment, `None` to unset the variable
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DevShellEnv {
+    variables: HashMap<String, Option<String>>,
+}
+
+/// `nix print-dev-env --json`: `{"variables": {"PATH": {"type": "exported", "value": "..."}}}`
+fn parse_print_dev_env(json: &str) -> Option<DevShellEnv> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    let variables = value
+        .get("variables")?
+        .as_object()?
+        .iter()
+        .filter(|(name, _)| !IGNORED_VARIABLES.contains(&name.as_str()))
+        .filter(|(_, variable)| variable["type"] == "exported")
+        .filter_map(|(name, variable)| {
+            variable["value"]
+                .as_str()
+                .map(|value| (name.clone(), Some(value.to_string())))
+        })
+        .collect();
+    Some(DevShellEnv { variables })
+}
+
+/// `direnv export json`: `{"NAME": "value", "REMOVED": null}`, and direnv's own variables are skipped
+fn parse_direnv_export(json: &str) -> Option<DevShellEnv> {
+    if json.trim().is_empty() {
+        return Some(DevShellEnv::default());
+    }
+    let value: HashMap<String, Option<String>> = serde_json::from_str(json).ok()?;
+    let variables = value
+        .into_iter()
+        .filter(|(name, _)| !name.starts_with("DIRENV_"))
+        .collect();
+    Some(DevShellEnv { variables })
+}
+
+/// cache file: `<cache>/task-keeper/devshell/<sha256 of the directory and source files>.json`
+fn cache_file(source: DevShellSource) -> Option<PathBuf> {
+    hashed_cache_file("devshell", source.name(), source.source_files())
+}
+
+/// cache file of the current directory: `<cache>/task-keeper/<category>/<sha256 of the key, directory and files>.json`,
+/// and it changes with the content of the files
+pub fn hashed_cache_file(category: &str, key: &str, file_names: &[&str]) -> Option<PathBuf> {
+    let current_dir = env::current_dir().ok()?;
+    let mut hasher = Sha256::new();
+    hasher.update(key.as_bytes());
+    hasher.update(current_dir.to_string_lossy().as_bytes());
+    for name in file_names {
+        if let Ok(bytes) = std::fs::read(current_dir.join(name)) {
+            hasher.update(name.as_bytes());
+            hasher.update(&bytes);
+        }
+    }
+    let hash = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    Some(
+        dirs::cache_dir()?
+            .join("task-keeper")
+            .join(category)
+            .join(format!("{}.json", hash)),
+    )
+}
+
+fn compute_env(source: DevShellSource) -> Result<DevShellEnv, String> {
+    let (command_name, args): (&str, &[&str]) = match source {
+        DevShellSource::Direnv => ("direnv", &["export", "json"]),
+        DevShellSource::Devenv => ("devenv", &["print-dev-env", "--json"]),
+        DevShellSource::Flake => ("nix", &["print-dev-env", "--json"]),
+    };
+    let output = capture_command_output(command_name, args).map_err(|error| error.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    let json = String::from_utf8_lossy(&output.stdout);
+    let dev_env = match source {
+        DevShellSource::Direnv => parse_direnv_export(&json),
+        _ => parse_print_dev_env(&json),
+    };
+    dev_env.ok_or_else(|| format!("failed to parse the output of {} {}", command_name, args.join(" ")))
+}
+
+/// load the environment from the cache or compute it, direnv is not cached because `.envrc` may read anything
+fn load_env(source: DevShellSource) -> Result<DevShellEnv, String> {
+    let cache_file = if source == DevShellSource::Direnv {
+        None
+    } else {
+        cache_file(source)
+    };
+    if let Some(cache_file) = &cache_file
+        && let Ok(text) = std::fs::read_to_string(cache_file)
+        && let Ok(dev_env) = serde_json::from_str::<DevShellEnv>(&text)
+    {
+        return Ok(dev_env);
+    }
+    let dev_env = compute_env(source)?;
+    if let Some(cache_file) = &cache_file
+        && let Some(parent) = cache_file.parent()
+        && std::fs::create_dir_all(parent).is_ok()
+        && let Ok(text) = serde_json::to_string(&dev_env)
+    {
+        std::fs::write(cache_file, text).ok();
+    }
+    Ok(dev_env)
+}
+
+/// apply the development environment to the current process, and nix `PATH` is prepended to the current `PATH`
+pub fn apply_env(source: DevShellSource) -> Result<usize, String> {
+    let dev_env = load_env(source)?;
+    for (name, value) in &dev_env.variables {
+        match value {
+            Some(value) if name == "PATH" && source != DevShellSource::Direnv => {
+                let path = env::var("PATH").unwrap_or_default();
+                let new_path = if path.is_empty() {
+                    value.clone()
+                } else {
+                    format!("{}{}{}", value, crate::polyglot::PATH_SEPARATOR, path)
+                };
+                unsafe {
+                    env::set_var("PATH", new_path);
+                }
+            }
+            Some(value) => unsafe {
+                env::set_var(name, value);
+            },
+            None => unsafe {
+                env::remove_var(name);
+            },
+        }
+    }
+    Ok(dev_env.variables.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_print_dev_env() {
+        let json = r#"{
+  "bashFunctions": {"genericBuild": "..."},
+  "variables": {
+    "PATH": {"type": "exported", "value": "/nix/store/abc-cargo/bin:/nix/store/def-rustc/bin"},
+    "RUST_SRC_PATH": {"type": "exported", "value": "/nix/store/ghi-rust-src"},
+    "TMPDIR": {"type": "exported", "value": "/tmp/nix-shell"},
+    "buildInputs": {"type": "var", "value": "/nix/store/abc-cargo"},
+    "outputs": {"type": "array", "value": ["out"]}
+  }
+}"#;
+        let dev_env = parse_print_dev_env(json).unwrap();
+        assert_eq!(2, dev_env.variables.len());
+        assert_eq!(
+            Some(&Some("/nix/store/ghi-rust-src".to_string())),
+            dev_env.variables.get("RUST_SRC_PATH")
+        );
+        assert!(!dev_env.variables.contains_key("TMPDIR"));
+    }
+
+    #[test]
+    fn test_parse_direnv_export() {
+        let json = r#"{"DIRENV_DIFF": "eJ", "DATABASE_URL": "postgres://localhost/demo", "OLD_VAR": null}"#;
+        let dev_env = parse_direnv_export(json).unwrap();
+        assert_eq!(2, dev_env.variables.len());
+        assert_eq!(Some(&None), dev_env.variables.get("OLD_VAR"));
+        assert!(parse_direnv_export("").unwrap().variables.is_empty());
+    }
+}
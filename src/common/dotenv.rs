@@ -0,0 +1,191 @@
+//! dotenv files for tasks: `.env`, `.flaskenv`, `.env.<profile>` and `.env.<profile>.local`,
+//! then the files given by `--env-file`. Later files win, and dotenvx encrypted values are decrypted
+//! with the keys from `.env.keys` or `DOTENV_PRIVATE_KEY_*`. Variables already in the environment
+//! are kept unless `--env-override` is given.
+use dotenvx_rs::common::find_env_file_path;
+use dotenvx_rs::dotenvx;
+use regex::Regex;
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// A variable of dotenv files, with the file it comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvEntry {
+    pub name: String,
+    pub value: String,
+    pub source: PathBuf,
+    /// encrypted by dotenvx in the file
+    pub encrypted: bool,
+}
+
+/// profile from `--env`, or `NODE_ENV` as before
+pub fn resolve_profile(cli_profile: Option<&String>) -> Option<String> {
+    cli_profile
+        .cloned()
+        .or_else(|| env::var("NODE_ENV").ok())
+        .filter(|profile| !profile.trim().is_empty())
+}
+
+/// dotenv files in precedence order, the later wins: `.env`, `.flaskenv`, `.env.<profile>`, `.env.<profile>.local`,
+/// and the files of `--env-file` on top of them
+pub fn resolve_env_files(profile: Option<&str>, cli_env_files: &[String]) -> Vec<PathBuf> {
+    let current_dir = env::current_dir().unwrap_or_default();
+    let mut env_files = vec![];
+    // .env is searched from the current directory to the root
+    if let Some(dotenv_file) = find_env_file_path(&current_dir, ".env") {
+        env_files.push(dotenv_file);
+    }
+    env_files.push(current_dir.join(".flaskenv"));
+    if let Some(profile) = profile {
+        env_files.push(current_dir.join(format!(".env.{}", profile)));
+        env_files.push(current_dir.join(format!(".env.{}.local", profile)));
+    }
+    env_files.extend(cli_env_files.iter().map(PathBuf::from));
+    env_files
+}
+
+/// names of the encrypted variables in the file, such as `API_KEY="encrypted:BDb7..."`
+fn encrypted_names(env_file: &Path) -> Vec<String> {
+    let text = std::fs::read_to_string(env_file).unwrap_or_default();
+    let pattern = Regex::new(r#"(?m)^\s*(?:export\s+)?([\w.]+)\s*=\s*["']?encrypted:"#).unwrap();
+    pattern
+        .captures_iter(&text)
+        .map(|captures| captures[1].to_string())
+        .collect()
+}
+
+/// merged variables of the files, a variable keeps the position of its first definition and the value of its last one.
+/// Missing files are skipped, and files failed to read or decrypt are returned as errors.
+pub fn load_entries(env_files: &[PathBuf]) -> (Vec<EnvEntry>, Vec<String>) {
+    let mut entries: Vec<EnvEntry> = vec![];
+    let mut errors = vec![];
+    for env_file in env_files.iter().filter(|env_file| env_file.is_file()) {
+        let items = match dotenvx::from_path_iter(env_file) {
+            Ok(items) => items,
+            Err(error) => {
+                errors.push(format!("{}: {}", env_file.display(), error));
+                continue;
+            }
+        };
+        let encrypted = encrypted_names(env_file);
+        for (name, value) in items {
+            let entry = EnvEntry {
+                encrypted: encrypted.contains(&name),
+                name,
+                value,
+                source: env_file.clone(),
+            };
+            if let Some(existing) = entries.iter_mut().find(|existing| existing.name == entry.name) {
+                *existing = entry;
+            } else {
+                entries.push(entry);
+            }
+        }
+    }
+    (entries, errors)
+}
+
+/// the variable is exported already, and it wins over dotenv files without `--env-override`
+pub fn is_shadowed(entry: &EnvEntry) -> bool {
+    env::var_os(&entry.name).is_some()
+}
+
+/// set the variables for tasks, and the existing variables are kept unless `override_existing`
+pub fn apply_entries(entries: &[EnvEntry], override_existing: bool) {
+    for entry in entries {
+        if override_existing || !is_shadowed(entry) {
+            unsafe {
+                env::set_var(&entry.name, &entry.value);
+            }
+        }
+    }
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let name = name.to_uppercase();
+    ["SECRET", "PASSWORD", "PASSWD", "TOKEN", "PRIVATE", "CREDENTIAL", "API_KEY", "ACCESS_KEY"]
+        .iter()
+        .any(|word| name.contains(word))
+}
+
+/// value to print: secrets and encrypted values are masked, and the first 2 characters are kept for long values
+pub fn masked_value(entry: &EnvEntry) -> String {
+    if !entry.encrypted && !is_secret_name(&entry.name) {
+        return entry.value.clone();
+    }
+    let chars: Vec<char> = entry.value.chars().collect();
+    if chars.len() > 8 {
+        format!("{}******", chars[..2].iter().collect::<String>())
+    } else {
+        "******".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_entries() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(dir.join(".env"), "APP_NAME=demo\nDB_PASSWORD=secret-password\nPORT=8080\n").unwrap();
+        std::fs::write(dir.join(".env.staging"), "PORT=9090\nDEBUG=false\n").unwrap();
+        let env_files = vec![
+            dir.join(".env"),
+            dir.join(".env.staging"),
+            dir.join(".env.staging.local"),
+        ];
+        let (entries, errors) = load_entries(&env_files);
+        assert!(errors.is_empty());
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(vec!["APP_NAME", "DB_PASSWORD", "PORT", "DEBUG"], names);
+        let port = entries.iter().find(|entry| entry.name == "PORT").unwrap();
+        assert_eq!("9090", port.value);
+        assert!(port.source.ends_with(".env.staging"));
+        let password = entries.iter().find(|entry| entry.name == "DB_PASSWORD").unwrap();
+        assert_eq!("se******", masked_value(password));
+        assert_eq!("demo", masked_value(&entries[0]));
+    }
+
+    #[test]
+    fn test_resolve_env_files() {
+        let cli_env_files = vec!["ci.env".to_string()];
+        let env_files = resolve_env_files(Some("staging"), &cli_env_files);
+        let names: Vec<String> = env_files
+            .iter()
+            .rev()
+            .take(3)
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        // --env-file on top of the profile files
+        assert_eq!(vec!["ci.env", ".env.staging.local", ".env.staging"], names);
+    }
+
+    #[test]
+    fn test_apply_entries() {
+        let entry = |name: &str, value: &str| EnvEntry {
+            name: name.to_string(),
+            value: value.to_string(),
+            source: PathBuf::from(".env"),
+            encrypted: false,
+        };
+        unsafe {
+            env::set_var("TK_DOTENV_EXPORTED", "exported");
+        }
+        apply_entries(&[entry("TK_DOTENV_EXPORTED", "dotenv"), entry("TK_DOTENV_NEW", "dotenv")], false);
+        assert_eq!("exported", env::var("TK_DOTENV_EXPORTED").unwrap());
+        assert_eq!("dotenv", env::var("TK_DOTENV_NEW").unwrap());
+        apply_entries(&[entry("TK_DOTENV_EXPORTED", "dotenv")], true);
+        assert_eq!("dotenv", env::var("TK_DOTENV_EXPORTED").unwrap());
+    }
+
+    #[test]
+    fn test_encrypted_names() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        let env_file = dir.join(".env.production");
+        std::fs::write(&env_file, "DOTENV_PUBLIC_KEY_PRODUCTION=\"02abc\"\nAPI_KEY=\"encrypted:BDb7t\"\nHOST=example.com\n").unwrap();
+        assert_eq!(vec!["API_KEY".to_string()], encrypted_names(&env_file));
+    }
+}
@@ -0,0 +1,263 @@
+//! Log files of tasks: `<log_dir>/<timestamp>-<runner>-<task>.log`, written by the capture layer
+use crate::common::capture::FileSink;
+use crate::managers::MANAGERS;
+use crate::runners::RUNNERS;
+use chrono::{Local, TimeDelta};
+use std::io;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// timestamp with milliseconds, and logs of the same task in the same second are kept
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S%3f";
+/// length of `%Y%m%d-%H%M%S%3f` plus the dash after it, and `%Y%m%d-%H%M%S-` of logs by older versions
+const TIMESTAMP_PREFIX_LENS: [usize; 2] = [19, 16];
+pub const DEFAULT_LOGS_KEPT: usize = 10;
+
+/// How many logs to keep: the latest N logs per task, or the logs of the last N days
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogRetention {
+    Count(usize),
+    Days(u64),
+}
+
+impl Default for LogRetention {
+    fn default() -> Self {
+        LogRetention::Count(DEFAULT_LOGS_KEPT)
+    }
+}
+
+impl LogRetention {
+    /// parse `20` as count or `7d` as days
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if let Some(days) = text.strip_suffix('d') {
+            days.parse::<u64>().ok().map(LogRetention::Days)
+        } else {
+            text.parse::<usize>()
+                .ok()
+                .filter(|count| *count > 0)
+                .map(LogRetention::Count)
+        }
+    }
+}
+
+/// log directory from `--log-dir` or `TK_LOG_DIR`
+pub fn resolve_log_dir(cli_log_dir: Option<&String>) -> Option<PathBuf> {
+    cli_log_dir
+        .cloned()
+        .or_else(|| std::env::var("TK_LOG_DIR").ok())
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// retention from `--log-retention` or `TK_LOG_RETENTION`
+pub fn resolve_log_retention(cli_retention: Option<&String>) -> LogRetention {
+    cli_retention
+        .cloned()
+        .or_else(|| std::env::var("TK_LOG_RETENTION").ok())
+        .and_then(|text| LogRetention::parse(&text))
+        .unwrap_or_default()
+}
+
+/// file name friendly version of a runner or task name, such as `module::task`
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// create the log file for a task and prune old logs
+pub fn create_log_sink(
+    log_dir: &Path,
+    runner: &str,
+    task_name: &str,
+    retention: LogRetention,
+) -> io::Result<FileSink> {
+    std::fs::create_dir_all(log_dir)?;
+    let runner_task = format!("{}-{}", sanitize(runner), sanitize(task_name));
+    let mut timestamp = Local::now();
+    // the log of the same task in the same millisecond exists, and the next millisecond is used
+    let sink = loop {
+        let file_name = format!("{}-{}.log", timestamp.format(TIMESTAMP_FORMAT), runner_task);
+        match FileSink::create(&log_dir.join(file_name), true) {
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                timestamp += TimeDelta::milliseconds(1);
+            }
+            result => break result?,
+        }
+    };
+    prune_logs(log_dir, &runner_task, retention);
+    Ok(sink)
+}
+
+/// `<runner>-<task>` part of a log file name
+fn runner_task_of(file_name: &str) -> Option<&str> {
+    let name = file_name.strip_suffix(".log")?;
+    TIMESTAMP_PREFIX_LENS.iter().find_map(|prefix_len| {
+        if name.len() <= *prefix_len || !name.is_char_boundary(*prefix_len) {
+            return None;
+        }
+        let (timestamp, runner_task) = name.split_at(*prefix_len);
+        let valid_timestamp = timestamp.chars().enumerate().all(|(index, c)| {
+            if index == 8 || index == prefix_len - 1 {
+                c == '-'
+            } else {
+                c.is_ascii_digit()
+            }
+        });
+        if valid_timestamp { Some(runner_task) } else { None }
+    })
+}
+
+/// log files in the directory, oldest first
+fn list_logs(log_dir: &Path) -> Vec<PathBuf> {
+    let mut logs: Vec<PathBuf> = std::fs::read_dir(log_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .and_then(runner_task_of)
+                        .is_some()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    logs.sort();
+    logs
+}
+
+fn prune_logs(log_dir: &Path, runner_task: &str, retention: LogRetention) {
+    let logs = list_logs(log_dir);
+    let expired: Vec<&PathBuf> = match retention {
+        LogRetention::Count(count) => {
+            let task_logs: Vec<&PathBuf> = logs
+                .iter()
+                .filter(|path| {
+                    path.file_name()
+                        .and_then(|name| name.to_str())
+                        .and_then(runner_task_of)
+                        == Some(runner_task)
+                })
+                .collect();
+            let expired_count = task_logs.len().saturating_sub(count);
+            task_logs.into_iter().take(expired_count).collect()
+        }
+        LogRetention::Days(days) => {
+            let max_age = Duration::from_secs(days * 24 * 60 * 60);
+            logs.iter()
+                .filter(|path| {
+                    path.metadata()
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                        .map(|age| age > max_age)
+                        .unwrap_or(false)
+                })
+                .collect()
+        }
+    };
+    for path in expired {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// latest log of a task, whatever runner or manager executed it
+pub fn find_latest_log(log_dir: &Path, task_name: &str) -> Option<PathBuf> {
+    let task_suffix = format!("-{}", sanitize(task_name));
+    list_logs(log_dir).into_iter().rev().find(|path| {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .and_then(runner_task_of)
+            .and_then(|runner_task| runner_task.strip_suffix(&task_suffix))
+            .map(|runner| {
+                RUNNERS.iter().chain(MANAGERS.iter()).any(|name| sanitize(name) == runner)
+            })
+            .unwrap_or(false)
+    })
+}
+
+/// print the log file, and keep printing what is appended to it with `follow`
+pub fn print_log(path: &Path, follow: bool) -> io::Result<()> {
+    let mut file = std::fs::File::open(path)?;
+    let mut stdout = io::stdout();
+    io::copy(&mut file, &mut stdout)?;
+    stdout.flush()?;
+    if follow {
+        let mut position = file.stream_position()?;
+        let mut buffer = Vec::new();
+        loop {
+            std::thread::sleep(Duration::from_millis(500));
+            let length = path.metadata()?.len();
+            if length < position {
+                // truncated or replaced
+                position = 0;
+            }
+            if length > position {
+                file.seek(SeekFrom::Start(position))?;
+                buffer.clear();
+                position += file.read_to_end(&mut buffer)? as u64;
+                stdout.write_all(&buffer)?;
+                stdout.flush()?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retention() {
+        assert_eq!(Some(LogRetention::Count(20)), LogRetention::parse("20"));
+        assert_eq!(Some(LogRetention::Days(7)), LogRetention::parse("7d"));
+        assert_eq!(None, LogRetention::parse("0"));
+        assert_eq!(None, LogRetention::parse("week"));
+    }
+
+    #[test]
+    fn test_runner_task_of() {
+        assert_eq!(
+            Some("cargo-make-build"),
+            runner_task_of("20261019-101010-cargo-make-build.log")
+        );
+        assert_eq!(
+            Some("cargo-make-build"),
+            runner_task_of("20261019-101010123-cargo-make-build.log")
+        );
+        assert_eq!(None, runner_task_of("notes.log"));
+    }
+
+    #[test]
+    fn test_find_latest_log() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let log_dir = temp_dir.path();
+        for file_name in [
+            "20261019-101010-make-build.log",
+            "20261019-111111-make-build.log",
+            "20261019-121212-make-pre-build.log",
+        ] {
+            std::fs::write(log_dir.join(file_name), "").unwrap();
+        }
+        let latest = find_latest_log(log_dir, "build").unwrap();
+        assert!(latest.ends_with("20261019-111111-make-build.log"));
+        prune_logs(log_dir, "make-build", LogRetention::Count(1));
+        assert_eq!(2, list_logs(log_dir).len());
+        // logs of the same task in the same millisecond
+        let first = create_log_sink(log_dir, "make", "test", LogRetention::Count(10));
+        let second = create_log_sink(log_dir, "make", "test", LogRetention::Count(10));
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(4, list_logs(log_dir).len());
+    }
+}
@@ -1,5 +1,13 @@
+pub mod capture;
+pub mod devshell;
+pub mod dotenv;
+pub mod logs;
 pub mod notification;
+pub mod process;
 pub mod pyproject;
+pub mod report;
+pub mod versions;
+pub mod workspace;
 
 use crate::errors::KeeperError;
 use error_stack::{Report, ResultExt};
@@ -9,9 +17,16 @@ use std::{collections::HashMap, fs};
 #[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PackageJson {
+    pub name: Option<String>,
     pub scripts: Option<HashMap<String, String>>,
     /// (npm|pnpm|yarn)@\d+\.\d+\.\d+(-.+)?
     pub package_manager: Option<String>,
+    /// required versions of node, npm etc
+    pub engines: Option<HashMap<String, String>>,
+    /// pinned versions of node, npm, yarn etc by Volta
+    pub volta: Option<HashMap<String, String>>,
+    /// `["packages/*"]` or `{"packages": ["packages/*"]}`
+    pub workspaces: Option<serde_json::Value>,
 }
 
 pub fn parse_package_json() -> core::result::Result<PackageJson, Report<KeeperError>> {
@@ -50,6 +65,41 @@ pub fn get_npm_command(package_json: &PackageJson) -> &'static str {
     "npm"
 }
 
+/// launcher of the binaries in node_modules by the package manager, such as `pnpm exec`
+pub fn get_npm_exec_command(package_json: &PackageJson) -> Vec<&'static str> {
+    match get_npm_command(package_json) {
+        "pnpm" => vec!["pnpm", "exec"],
+        "yarn" => vec!["yarn"],
+        "bun" => vec!["bunx"],
+        _ => vec!["npx"],
+    }
+}
+
+/// command of a node binary: the local install by the package manager launcher, then the global one
+pub fn get_node_bin_command(bin_name: &str) -> Vec<String> {
+    let local_bin = std::env::current_dir()
+        .map(|dir| dir.join("node_modules").join(".bin").join(bin_name).exists())
+        .unwrap_or(false);
+    if !local_bin && which::which(bin_name).is_ok() {
+        return vec![bin_name.to_string()];
+    }
+    let package_json = parse_package_json().unwrap_or_default();
+    let mut command = get_npm_exec_command(&package_json)
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<String>>();
+    command.push(bin_name.to_string());
+    command
+}
+
+/// the binary installed in node_modules or globally
+pub fn is_node_bin_available(bin_name: &str) -> bool {
+    std::env::current_dir()
+        .map(|dir| dir.join("node_modules").join(".bin").join(bin_name).exists())
+        .unwrap_or(false)
+        || which::which(bin_name).is_ok()
+}
+
 pub fn pyproject_toml_has_tool(tool_name: &str) -> bool {
     std::env::current_dir()
         .map(|dir| {
@@ -16,6 +16,8 @@ struct Notification {
     status: i32,
     stdout: Option<String>,
     stderr: Option<String>,
+    #[serde(skip)]
+    merged: Option<String>,
 }
 
 pub fn send_notification(
@@ -32,6 +34,7 @@ pub fn send_notification(
         status: command_output.status.code().unwrap_or(0),
         stdout: command_output.stdout.clone(),
         stderr: command_output.stderr.clone(),
+        merged: command_output.merged.clone(),
     };
     let rt = tokio::runtime::Runtime::new().unwrap();
     rt.block_on(async {
@@ -69,13 +72,18 @@ async fn save_oss(notification: &Notification) -> anyhow::Result<()> {
     let s3_bucket = env::var("S3_BUCKET")?;
     let object_name = &notification.task_id;
     if let Ok(minio_client) = create_oss_client() {
-        let mut text = String::new();
-        if let Some(stdout) = &notification.stdout {
-            text.push_str(stdout);
-        }
-        if let Some(stderr) = &notification.stderr {
-            text.push_str(stderr);
-        }
+        let text = if let Some(merged) = &notification.merged {
+            merged.clone()
+        } else {
+            let mut text = String::new();
+            if let Some(stdout) = &notification.stdout {
+                text.push_str(stdout);
+            }
+            if let Some(stderr) = &notification.stderr {
+                text.push_str(stderr);
+            }
+            text
+        };
         let content = ObjectContent::from(text);
         let mut user_metadata = Multimap::new();
         user_metadata.insert("status".to_string(), notification.status.to_string());
@@ -0,0 +1,302 @@
+//! Spawn task processes in their own process group and forward signals to them.
+//!
+//! Build tools like `./mvnw` or `npm run dev` fork grandchildren (a JVM, a node dev server),
+//! and killing the direct child only is not enough to stop them. On Unix every task is started
+//! as the leader of a new process group, the terminal is handed over to that group while it runs,
+//! and SIGINT/SIGTERM/SIGHUP received by tk are forwarded to the whole group.
+//! If the group is still alive `TK_GRACE_PERIOD` seconds (default 5) after the first signal,
+//! it is killed with SIGKILL. An interrupted task returns an `Interrupted` error, and no more tasks
+//! are spawned afterwards, so tk can write its reports before exiting with `128 + signal`.
+use std::io;
+use std::process::{Child, Command, ExitStatus};
+
+const DEFAULT_GRACE_PERIOD_SECS: u64 = 5;
+
+pub struct TaskProcess {
+    child: Child,
+    #[cfg(unix)]
+    foreground: bool,
+}
+
+impl TaskProcess {
+    pub fn spawn(command: &mut Command) -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            unix::spawn(command)
+        }
+        #[cfg(not(unix))]
+        {
+            command.spawn().map(|child| TaskProcess { child })
+        }
+    }
+
+    pub fn child_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+
+    pub fn wait(self) -> io::Result<ExitStatus> {
+        #[cfg(unix)]
+        {
+            unix::wait(self)
+        }
+        #[cfg(not(unix))]
+        {
+            let mut child = self.child;
+            child.wait()
+        }
+    }
+}
+
+/// Spawn the command in its own process group and wait for it to exit.
+pub fn spawn_and_wait(command: &mut Command) -> io::Result<ExitStatus> {
+    TaskProcess::spawn(command)?.wait()
+}
+
+/// the signal that interrupted tk, such as SIGINT by Ctrl-C
+pub fn interrupted_signal() -> Option<i32> {
+    #[cfg(unix)]
+    {
+        unix::interrupted_signal()
+    }
+    #[cfg(not(unix))]
+    {
+        None
+    }
+}
+
+fn interrupted_error(signal: i32) -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, format!("interrupted by signal {}", signal))
+}
+
+fn grace_period() -> std::time::Duration {
+    let seconds = std::env::var("TK_GRACE_PERIOD")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_GRACE_PERIOD_SECS);
+    std::time::Duration::from_secs(seconds)
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::{grace_period, interrupted_error, TaskProcess};
+    use std::io;
+    use std::os::unix::process::{CommandExt, ExitStatusExt};
+    use std::process::{Command, ExitStatus};
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::mpsc;
+    use std::sync::Once;
+    use std::time::{Duration, Instant};
+
+    const FORWARDED_SIGNALS: [libc::c_int; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+    /// process group of the running task, 0 if no task is running
+    static CHILD_PGID: AtomicI32 = AtomicI32::new(0);
+    /// first signal received by tk, 0 if none. It's never reset, because tk stops running tasks after it
+    static PENDING_SIGNAL: AtomicI32 = AtomicI32::new(0);
+    static INSTALL_HANDLERS: Once = Once::new();
+
+    extern "C" fn forward_signal(signal: libc::c_int) {
+        let pgid = CHILD_PGID.load(Ordering::SeqCst);
+        if pgid > 0 {
+            unsafe {
+                libc::kill(-pgid, signal);
+            }
+        }
+        let _ = PENDING_SIGNAL.compare_exchange(0, signal, Ordering::SeqCst, Ordering::SeqCst);
+    }
+
+    fn install_signal_handlers() {
+        INSTALL_HANDLERS.call_once(|| unsafe {
+            for signal in FORWARDED_SIGNALS {
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = forward_signal as *const () as libc::sighandler_t;
+                action.sa_flags = libc::SA_RESTART;
+                libc::sigemptyset(&mut action.sa_mask);
+                libc::sigaction(signal, &action, std::ptr::null_mut());
+            }
+            // tk takes the terminal back from the task group when it finishes
+            libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+        });
+    }
+
+    /// tk owns the terminal only when stdin is a tty and tk's group is the foreground one
+    fn owns_terminal() -> bool {
+        unsafe {
+            libc::isatty(libc::STDIN_FILENO) == 1
+                && libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp()
+        }
+    }
+
+    fn set_foreground(pgid: libc::pid_t) {
+        unsafe {
+            libc::tcsetpgrp(libc::STDIN_FILENO, pgid);
+        }
+    }
+
+    pub(super) fn interrupted_signal() -> Option<i32> {
+        match PENDING_SIGNAL.load(Ordering::SeqCst) {
+            0 => None,
+            signal => Some(signal),
+        }
+    }
+
+    pub(super) fn spawn(command: &mut Command) -> io::Result<TaskProcess> {
+        install_signal_handlers();
+        // Ctrl-C between tasks, such as `tk a b`
+        if let Some(signal) = interrupted_signal() {
+            return Err(interrupted_error(signal));
+        }
+        let foreground = owns_terminal();
+        command.process_group(0);
+        unsafe {
+            command.pre_exec(move || {
+                // take the terminal before exec, the parent does the same to avoid a race
+                if foreground {
+                    libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpid());
+                }
+                // ignored signals survive exec, so restore the defaults tk has changed
+                libc::signal(libc::SIGTTOU, libc::SIG_DFL);
+                Ok(())
+            });
+        }
+        let child = command.spawn()?;
+        let pgid = child.id() as libc::pid_t;
+        CHILD_PGID.store(pgid, Ordering::SeqCst);
+        if foreground {
+            set_foreground(pgid);
+        }
+        Ok(TaskProcess { child, foreground })
+    }
+
+    enum WaitEvent {
+        Stopped,
+        Exited(ExitStatus),
+        Failed(io::Error),
+    }
+
+    pub(super) fn wait(task_process: TaskProcess) -> io::Result<ExitStatus> {
+        let TaskProcess { child, foreground } = task_process;
+        let pid = child.id() as libc::pid_t;
+        let (sender, receiver) = mpsc::channel::<WaitEvent>();
+        std::thread::spawn(move || {
+            loop {
+                let mut status: libc::c_int = 0;
+                let result = unsafe { libc::waitpid(pid, &mut status, libc::WUNTRACED) };
+                if result < 0 {
+                    let error = io::Error::last_os_error();
+                    if error.kind() == io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    let _ = sender.send(WaitEvent::Failed(error));
+                    break;
+                }
+                if libc::WIFSTOPPED(status) {
+                    if sender.send(WaitEvent::Stopped).is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                let _ = sender.send(WaitEvent::Exited(ExitStatus::from_raw(status)));
+                break;
+            }
+        });
+        let grace_period = grace_period();
+        let mut signal_received_at: Option<Instant> = None;
+        let mut exit_result: Option<io::Result<ExitStatus>> = None;
+        let mut killed = false;
+        loop {
+            if signal_received_at.is_none() && PENDING_SIGNAL.load(Ordering::SeqCst) != 0 {
+                signal_received_at = Some(Instant::now());
+            }
+            let deadline_passed = signal_received_at
+                .map(|received_at| received_at.elapsed() >= grace_period)
+                .unwrap_or(false);
+            if let Some(result) = exit_result.take() {
+                // after a signal, wait for the grandchildren left in the group as well
+                if signal_received_at.is_none() || !group_alive(pid) {
+                    return finish(result, foreground);
+                }
+                if deadline_passed {
+                    if !killed {
+                        kill_group(pid);
+                    }
+                    return finish(result, foreground);
+                }
+                exit_result = Some(result);
+                std::thread::sleep(Duration::from_millis(50));
+                continue;
+            }
+            if deadline_passed && !killed {
+                kill_group(pid);
+                killed = true;
+            }
+            match receiver.recv_timeout(Duration::from_millis(100)) {
+                Ok(WaitEvent::Stopped) => suspend(pid, foreground),
+                Ok(WaitEvent::Exited(status)) => exit_result = Some(Ok(status)),
+                Ok(WaitEvent::Failed(error)) => exit_result = Some(Err(error)),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    exit_result = Some(Err(io::Error::other("lost track of the task process")))
+                }
+            }
+        }
+    }
+
+    fn group_alive(pgid: libc::pid_t) -> bool {
+        unsafe { libc::kill(-pgid, 0) == 0 }
+    }
+
+    fn kill_group(pgid: libc::pid_t) {
+        eprintln!("[tk] task did not exit within the grace period, killing it");
+        unsafe {
+            libc::kill(-pgid, libc::SIGKILL);
+        }
+    }
+
+    /// Ctrl-Z stopped the task: stop tk as well, and resume the task when tk is continued
+    fn suspend(pgid: libc::pid_t, foreground: bool) {
+        if foreground {
+            set_foreground(unsafe { libc::getpgrp() });
+        }
+        unsafe {
+            libc::raise(libc::SIGTSTP);
+        }
+        if foreground {
+            set_foreground(pgid);
+        }
+        unsafe {
+            libc::kill(-pgid, libc::SIGCONT);
+        }
+    }
+
+    fn finish(result: io::Result<ExitStatus>, foreground: bool) -> io::Result<ExitStatus> {
+        CHILD_PGID.store(0, Ordering::SeqCst);
+        if foreground {
+            set_foreground(unsafe { libc::getpgrp() });
+        }
+        // With the terminal handed over, Ctrl-C reaches the task group only
+        if let Ok(status) = &result
+            && status.signal() == Some(libc::SIGINT)
+        {
+            let _ = PENDING_SIGNAL.compare_exchange(0, libc::SIGINT, Ordering::SeqCst, Ordering::SeqCst);
+        }
+        // the task was interrupted, so don't go on with the next tasks
+        match interrupted_signal() {
+            Some(signal) => Err(interrupted_error(signal)),
+            None => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_and_wait() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "exit 3"]);
+        let status = spawn_and_wait(&mut command).unwrap();
+        assert_eq!(Some(3), status.code());
+    }
+}
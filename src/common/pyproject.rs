@@ -5,9 +5,19 @@ use toml::Value;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PyProjectToml {
+    pub project: Option<Project>,
     pub tool: Option<Tool>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct Project {
+    pub name: Option<String>,
+    pub requires_python: Option<String>,
+    /// console entry points, such as `demo = "demo.cli:main"`
+    pub scripts: Option<HashMap<String, String>>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "kebab-case")]
 pub struct Tool {
@@ -15,6 +25,29 @@ pub struct Tool {
     pub uv: Option<ToolUv>,
     pub poetry: Option<ToolPoetry>,
     pub poe: Option<PeoTasks>,
+    pub pdm: Option<ToolPdm>,
+    pub hatch: Option<ToolHatch>,
+}
+
+/// `[tool.pdm.scripts]` with cmd, shell, call and composite scripts
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct ToolPdm {
+    pub scripts: Option<HashMap<String, toml::Value>>,
+}
+
+/// `[tool.hatch]` of pyproject.toml, or hatch.toml without the `tool.hatch` prefix
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct ToolHatch {
+    pub envs: Option<HashMap<String, HatchEnv>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct HatchEnv {
+    pub scripts: Option<HashMap<String, toml::Value>>,
+    pub env_vars: Option<HashMap<String, String>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -102,6 +135,7 @@ impl PeoTasks {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "kebab-case")]
 pub struct ToolPoetry {
+    pub name: Option<String>,
     scripts: Option<HashMap<String, toml::Value>>,
 }
 
@@ -194,6 +228,33 @@ impl PyProjectToml {
             .and_then(|peo_tasks| peo_tasks.get_tasks().clone())
     }
 
+    pub fn get_project_scripts(&self) -> Option<HashMap<String, String>> {
+        self.project.as_ref().and_then(|project| project.scripts.clone())
+    }
+
+    pub fn get_pdm_scripts(&self) -> Option<HashMap<String, toml::Value>> {
+        self.tool
+            .as_ref()
+            .and_then(|tool| tool.pdm.as_ref())
+            .and_then(|pdm| pdm.scripts.clone())
+    }
+
+    /// envs of `[tool.hatch.envs]` and `[envs]` of hatch.toml, and hatch.toml wins for the same env
+    pub fn get_hatch_envs(&self) -> HashMap<String, HatchEnv> {
+        let mut envs = self
+            .tool
+            .as_ref()
+            .and_then(|tool| tool.hatch.as_ref())
+            .and_then(|hatch| hatch.envs.clone())
+            .unwrap_or_default();
+        if let Ok(text) = std::fs::read_to_string("hatch.toml")
+            && let Ok(hatch_toml) = toml::from_str::<ToolHatch>(&text)
+        {
+            envs.extend(hatch_toml.envs.unwrap_or_default());
+        }
+        envs
+    }
+
     pub fn venv_bin_path(&self) -> PathBuf {
         std::env::current_dir().unwrap().join(".venv").join("bin")
     }
@@ -0,0 +1,367 @@
+//! Report of the executed tasks in JUnit XML or TAP format, for CI systems such as GitLab and Jenkins
+use crate::command_utils::CommandOutput;
+use crate::errors::KeeperError;
+use chrono::{DateTime, Local};
+use error_stack::Report;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// lines of stderr kept for each task
+const STDERR_TAIL_LINES: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Junit,
+    Tap,
+}
+
+/// `--report` option: `junit=report.xml` or `tap=report.tap`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportTarget {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+impl ReportTarget {
+    /// parse `format=path`, and the path defaults to `report.xml` or `report.tap`
+    pub fn parse(text: &str) -> Option<Self> {
+        let (format, path) = match text.split_once('=') {
+            Some((format, path)) => (format.trim(), path.trim()),
+            None => (text.trim(), ""),
+        };
+        let format = match format.to_lowercase().as_str() {
+            "junit" | "xml" => ReportFormat::Junit,
+            "tap" => ReportFormat::Tap,
+            _ => return None,
+        };
+        let path = if path.is_empty() {
+            match format {
+                ReportFormat::Junit => "report.xml",
+                ReportFormat::Tap => "report.tap",
+            }
+        } else {
+            path
+        };
+        Some(ReportTarget {
+            format,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskOutcome {
+    Passed,
+    Failed(String),
+    Skipped(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskRecord {
+    pub runner: Option<String>,
+    pub name: String,
+    pub duration: Duration,
+    pub exit_code: Option<i32>,
+    pub outcome: TaskOutcome,
+    pub stderr_tail: String,
+}
+
+struct TaskReport {
+    target: ReportTarget,
+    started: DateTime<Local>,
+    records: Vec<TaskRecord>,
+}
+
+static REPORT: Mutex<Option<TaskReport>> = Mutex::new(None);
+
+/// start to record executed tasks for the report
+pub fn enable(target: ReportTarget) {
+    *REPORT.lock().unwrap() = Some(TaskReport {
+        target,
+        started: Local::now(),
+        records: vec![],
+    });
+}
+
+pub fn is_enabled() -> bool {
+    REPORT.lock().unwrap().is_some()
+}
+
+fn record(task_record: TaskRecord) {
+    if let Some(report) = REPORT.lock().unwrap().as_mut() {
+        report.records.push(task_record);
+    }
+}
+
+/// record the result of a task executed by a runner or a manager
+pub fn record_result(
+    runner: &str,
+    task_name: &str,
+    duration: Duration,
+    result: &Result<CommandOutput, Report<KeeperError>>,
+) {
+    if !is_enabled() {
+        return;
+    }
+    let (exit_code, outcome, stderr_tail) = match result {
+        Ok(output) => {
+            let exit_code = output.status.code();
+            let outcome = if output.status.success() {
+                TaskOutcome::Passed
+            } else if let Some(code) = exit_code {
+                TaskOutcome::Failed(format!("exit code {}", code))
+            } else {
+                TaskOutcome::Failed("terminated by signal".to_string())
+            };
+            let stderr = output.stderr.as_deref().unwrap_or_default();
+            (exit_code, outcome, tail_lines(stderr, STDERR_TAIL_LINES))
+        }
+        Err(err) => (None, TaskOutcome::Failed(err.current_context().to_string()), format!("{:?}", err)),
+    };
+    record(TaskRecord {
+        runner: Some(runner.to_string()),
+        name: task_name.to_string(),
+        duration,
+        exit_code,
+        outcome,
+        stderr_tail,
+    });
+}
+
+/// record a task not executed
+pub fn record_skipped(task_name: &str, reason: &str) {
+    record(TaskRecord {
+        runner: None,
+        name: task_name.to_string(),
+        duration: Duration::ZERO,
+        exit_code: None,
+        outcome: TaskOutcome::Skipped(reason.to_string()),
+        stderr_tail: String::new(),
+    });
+}
+
+/// Summary of a written report
+pub struct ReportSummary {
+    pub path: PathBuf,
+    pub tests: usize,
+    pub failures: usize,
+}
+
+/// write the report file, `None` if no report was requested
+pub fn write_report() -> io::Result<Option<ReportSummary>> {
+    let guard = REPORT.lock().unwrap();
+    let Some(report) = guard.as_ref() else {
+        return Ok(None);
+    };
+    let text = match report.target.format {
+        ReportFormat::Junit => render_junit(&report.records, &report.started),
+        ReportFormat::Tap => render_tap(&report.records),
+    };
+    if let Some(parent) = report.target.path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&report.target.path, text)?;
+    Ok(Some(ReportSummary {
+        path: report.target.path.clone(),
+        tests: report.records.len(),
+        failures: count_failures(&report.records),
+    }))
+}
+
+fn count_failures(records: &[TaskRecord]) -> usize {
+    records
+        .iter()
+        .filter(|record| matches!(record.outcome, TaskOutcome::Failed(_)))
+        .count()
+}
+
+fn tail_lines(text: &str, count: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    lines[lines.len().saturating_sub(count)..].join("\n")
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // control characters are not allowed in XML 1.0, such as ANSI escape sequences
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn render_junit(records: &[TaskRecord], started: &DateTime<Local>) -> String {
+    let total_time: f64 = records.iter().map(|record| record.duration.as_secs_f64()).sum();
+    let failures = count_failures(records);
+    let skipped = records
+        .iter()
+        .filter(|record| matches!(record.outcome, TaskOutcome::Skipped(_)))
+        .count();
+    let summary = format!(
+        r#"tests="{}" failures="{}" errors="0" skipped="{}" time="{:.3}""#,
+        records.len(),
+        failures,
+        skipped,
+        total_time
+    );
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!("<testsuites name=\"tk\" {}>\n", summary));
+    xml.push_str(&format!(
+        "  <testsuite name=\"tk\" {} timestamp=\"{}\">\n",
+        summary,
+        started.format("%Y-%m-%dT%H:%M:%S")
+    ));
+    for record in records {
+        xml.push_str(&format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape_xml(&record.name),
+            escape_xml(record.runner.as_deref().unwrap_or("tk")),
+            record.duration.as_secs_f64()
+        ));
+        match &record.outcome {
+            TaskOutcome::Passed if record.stderr_tail.is_empty() => {
+                xml.push_str("/>\n");
+                continue;
+            }
+            TaskOutcome::Passed => {
+                xml.push_str(">\n");
+                xml.push_str(&format!(
+                    "      <system-err>{}</system-err>\n",
+                    escape_xml(&record.stderr_tail)
+                ));
+            }
+            TaskOutcome::Failed(message) => {
+                xml.push_str(">\n");
+                xml.push_str(&format!(
+                    "      <failure message=\"{}\" type=\"{}\">{}</failure>\n",
+                    escape_xml(message),
+                    if record.exit_code.is_some() { "ExitCode" } else { "Error" },
+                    escape_xml(&record.stderr_tail)
+                ));
+            }
+            TaskOutcome::Skipped(reason) => {
+                xml.push_str(">\n");
+                xml.push_str(&format!("      <skipped message=\"{}\"/>\n", escape_xml(reason)));
+            }
+        }
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n");
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn render_tap(records: &[TaskRecord]) -> String {
+    let mut tap = format!("TAP version 13\n1..{}\n", records.len());
+    for (index, record) in records.iter().enumerate() {
+        let description = match &record.runner {
+            Some(runner) => format!("{} from {}", record.name, runner),
+            None => record.name.clone(),
+        };
+        // '#' starts a directive in TAP
+        let description = description.replace('#', "\\#");
+        let number = index + 1;
+        match &record.outcome {
+            TaskOutcome::Passed => {
+                tap.push_str(&format!("ok {} - {}\n", number, description));
+            }
+            TaskOutcome::Failed(message) => {
+                tap.push_str(&format!("not ok {} - {}\n", number, description));
+                tap.push_str("  ---\n");
+                tap.push_str(&format!("  message: {:?}\n", message));
+                if let Some(exit_code) = record.exit_code {
+                    tap.push_str(&format!("  exit_code: {}\n", exit_code));
+                }
+                if !record.stderr_tail.is_empty() {
+                    tap.push_str("  stderr: |\n");
+                    for line in record.stderr_tail.lines() {
+                        tap.push_str(&format!("    {}\n", line));
+                    }
+                }
+                tap.push_str("  ...\n");
+            }
+            TaskOutcome::Skipped(reason) => {
+                tap.push_str(&format!("ok {} - {} # SKIP {}\n", number, description, reason));
+            }
+        }
+        if !matches!(record.outcome, TaskOutcome::Skipped(_)) {
+            tap.push_str(&format!("# time={:.3}s\n", record.duration.as_secs_f64()));
+        }
+    }
+    tap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_records() -> Vec<TaskRecord> {
+        vec![
+            TaskRecord {
+                runner: Some("make".to_string()),
+                name: "lint".to_string(),
+                duration: Duration::from_millis(1200),
+                exit_code: Some(0),
+                outcome: TaskOutcome::Passed,
+                stderr_tail: String::new(),
+            },
+            TaskRecord {
+                runner: Some("cargo".to_string()),
+                name: "test".to_string(),
+                duration: Duration::from_millis(3400),
+                exit_code: Some(101),
+                outcome: TaskOutcome::Failed("exit code 101".to_string()),
+                stderr_tail: "assertion `left == right` failed\n<left> & <right>".to_string(),
+            },
+            TaskRecord {
+                runner: None,
+                name: "build".to_string(),
+                duration: Duration::ZERO,
+                exit_code: None,
+                outcome: TaskOutcome::Skipped("task not found".to_string()),
+                stderr_tail: String::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_parse_target() {
+        let target = ReportTarget::parse("junit=target/report.xml").unwrap();
+        assert_eq!(ReportFormat::Junit, target.format);
+        assert_eq!(PathBuf::from("target/report.xml"), target.path);
+        assert_eq!(PathBuf::from("report.tap"), ReportTarget::parse("tap").unwrap().path);
+        assert!(ReportTarget::parse("html=report.html").is_none());
+    }
+
+    #[test]
+    fn test_render_junit() {
+        let xml = render_junit(&sample_records(), &Local::now());
+        assert!(xml.contains(r#"tests="3" failures="1" errors="0" skipped="1""#));
+        assert!(xml.contains(r#"<testcase name="lint" classname="make" time="1.200"/>"#));
+        assert!(xml.contains(r#"<failure message="exit code 101" type="ExitCode">"#));
+        assert!(xml.contains("&lt;left&gt; &amp; &lt;right&gt;</failure>"));
+        assert!(xml.contains(r#"<skipped message="task not found"/>"#));
+    }
+
+    #[test]
+    fn test_render_tap() {
+        let tap = render_tap(&sample_records());
+        assert!(tap.starts_with("TAP version 13\n1..3\n"));
+        assert!(tap.contains("ok 1 - lint from make\n"));
+        assert!(tap.contains("not ok 2 - test from cargo\n"));
+        assert!(tap.contains("  exit_code: 101\n"));
+        assert!(tap.contains("ok 3 - build # SKIP task not found\n"));
+    }
+}
@@ -0,0 +1,210 @@
+//! Versions and version requirements: `v20.11.0` from `node --version`, npm ranges, PEP 440 specifiers,
+//! RubyGems requirements and `rust-version` of Cargo.toml, shared by language SDKs and the doctor.
+use regex::Regex;
+use semver::{Version, VersionReq};
+use std::path::Path;
+
+/// first version in the text, such as `v20.11.0` or `Apache Maven 3.9.6`, missing parts as 0
+pub fn parse_version(text: &str) -> Option<Version> {
+    let captures = Regex::new(r"(\d+)(?:\.(\d+))?(?:\.(\d+))?")
+        .unwrap()
+        .captures(text)?;
+    let part = |index: usize| {
+        captures
+            .get(index)
+            .and_then(|part| part.as_str().parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    Some(Version::new(part(1), part(2), part(3)))
+}
+
+/// npm semver range, such as `>=18 <21`, `^18 || ^20`, `18.x` or `16 - 20`
+pub fn npm_range_to_reqs(range: &str) -> Option<Vec<VersionReq>> {
+    let operator_space = Regex::new(r"(>=|<=|>|<|=|~|\^)\s+").unwrap();
+    range
+        .split("||")
+        .map(|alternative| {
+            let alternative = operator_space.replace_all(alternative.trim(), "$1");
+            let comparators: Vec<&str> = alternative.split_whitespace().collect();
+            let req = match comparators.as_slice() {
+                [] | ["*"] | ["x"] => "*".to_string(),
+                [low, "-", high] => format!(">={}, <={}", low, high),
+                _ => comparators.join(", "),
+            };
+            VersionReq::parse(req.trim_start_matches('v')).ok()
+        })
+        .collect()
+}
+
+/// PEP 440 version specifiers, such as `>=3.9,<4` or `~=3.10`
+pub fn pep440_to_req(specifiers: &str) -> Option<VersionReq> {
+    let mut comparators = vec![];
+    for specifier in specifiers.split(',').map(|specifier| specifier.trim()) {
+        if specifier.is_empty() {
+            continue;
+        }
+        let operator_len = specifier
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(specifier.len());
+        let (operator, version) = specifier.split_at(operator_len);
+        let version = version.trim();
+        match operator.trim() {
+            "~=" => {
+                // compatible release: ~=3.10 is >=3.10,<4, and ~=3.10.2 is >=3.10.2,<3.11
+                let parts: Vec<u64> = version
+                    .split('.')
+                    .map(|part| part.parse::<u64>().ok())
+                    .collect::<Option<Vec<u64>>>()?;
+                if parts.len() < 2 {
+                    return None;
+                }
+                let mut upper = parts[..parts.len() - 1].to_vec();
+                *upper.last_mut()? += 1;
+                let upper = upper
+                    .iter()
+                    .map(|part| part.to_string())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                comparators.push(format!(">={}", version));
+                comparators.push(format!("<{}", upper));
+            }
+            "==" | "===" => {
+                if let Some(prefix) = version.strip_suffix(".*") {
+                    comparators.push(format!("={}", prefix));
+                } else {
+                    let version = parse_version(version)?;
+                    comparators.push(format!("={}", version));
+                }
+            }
+            // semver can not express exclusions
+            "!=" => {}
+            operator @ (">=" | "<=" | ">" | "<") => {
+                comparators.push(format!("{}{}", operator, version));
+            }
+            _ => return None,
+        }
+    }
+    if comparators.is_empty() {
+        return Some(VersionReq::STAR);
+    }
+    VersionReq::parse(&comparators.join(", ")).ok()
+}
+
+/// RubyGems requirements, such as `~> 3.2`(`>=3.2, <4`), `~> 3.2.0`(`>=3.2.0, <3.3`), `>= 3.1` or `3.2.2`
+pub fn gem_requirement_to_req(requirements: &[&str]) -> Option<VersionReq> {
+    let mut comparators = vec![];
+    for requirement in requirements.iter().map(|requirement| requirement.trim()) {
+        let operator_len = requirement
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(requirement.len());
+        let (operator, version) = requirement.split_at(operator_len);
+        let version = version.trim();
+        match operator.trim() {
+            "~>" => {
+                // pessimistic operator: the last part is dropped and the new last part is bumped, and `~> 3` is `<4`
+                let parts: Vec<u64> = version
+                    .split('.')
+                    .map(|part| part.parse::<u64>().ok())
+                    .collect::<Option<Vec<u64>>>()?;
+                let mut upper = if parts.len() > 1 {
+                    parts[..parts.len() - 1].to_vec()
+                } else {
+                    parts.clone()
+                };
+                *upper.last_mut()? += 1;
+                let upper = upper
+                    .iter()
+                    .map(|part| part.to_string())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                comparators.push(format!(">={}", version));
+                comparators.push(format!("<{}", upper));
+            }
+            "" | "=" => comparators.push(format!("={}", version)),
+            // semver can not express exclusions
+            "!=" => {}
+            operator @ (">=" | "<=" | ">" | "<") => {
+                comparators.push(format!("{}{}", operator, version));
+            }
+            _ => return None,
+        }
+    }
+    if comparators.is_empty() {
+        return Some(VersionReq::STAR);
+    }
+    VersionReq::parse(&comparators.join(", ")).ok()
+}
+
+/// `rust-version` of `[package]` or `[workspace.package]` in Cargo.toml
+pub fn read_rust_version(cargo_toml: &Path) -> Option<String> {
+    let text = std::fs::read_to_string(cargo_toml).ok()?;
+    let cargo_toml = text.parse::<toml::Table>().ok()?;
+    let package_version = cargo_toml
+        .get("package")
+        .and_then(|package| package.get("rust-version"))
+        .and_then(|version| version.as_str());
+    let workspace_version = cargo_toml
+        .get("workspace")
+        .and_then(|workspace| workspace.get("package"))
+        .and_then(|package| package.get("rust-version"))
+        .and_then(|version| version.as_str());
+    package_version.or(workspace_version).map(|version| version.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(reqs: &[VersionReq], version: &str) -> bool {
+        let version = parse_version(version).unwrap();
+        reqs.iter().any(|req| req.matches(&version))
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(Version::new(20, 11, 0), parse_version("v20.11.0").unwrap());
+        assert_eq!(Version::new(3, 12, 0), parse_version("Python 3.12").unwrap());
+        assert_eq!(
+            Version::new(1, 95, 0),
+            parse_version("rustc 1.95.0 (59807616e 2026-04-14)").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_npm_range() {
+        let reqs = npm_range_to_reqs(">= 18 <21").unwrap();
+        assert!(matches(&reqs, "v20.11.0"));
+        assert!(!matches(&reqs, "v16.20.0"));
+        let reqs = npm_range_to_reqs("^18 || ^22").unwrap();
+        assert!(matches(&reqs, "22.1.0"));
+        assert!(!matches(&reqs, "20.1.0"));
+        assert!(matches(&npm_range_to_reqs("18.x").unwrap(), "18.19.1"));
+        assert!(matches(&npm_range_to_reqs("16 - 20").unwrap(), "20.5.0"));
+    }
+
+    #[test]
+    fn test_pep440() {
+        let req = pep440_to_req(">=3.9,<4").unwrap();
+        assert!(req.matches(&Version::new(3, 12, 1)));
+        assert!(!req.matches(&Version::new(3, 8, 10)));
+        let req = pep440_to_req("~=3.10").unwrap();
+        assert!(req.matches(&Version::new(3, 13, 0)));
+        assert!(!req.matches(&Version::new(4, 0, 0)));
+        assert!(pep440_to_req("==3.11.*").unwrap().matches(&Version::new(3, 11, 4)));
+    }
+
+    #[test]
+    fn test_gem_requirement() {
+        let req = gem_requirement_to_req(&["~> 3.2"]).unwrap();
+        assert!(req.matches(&Version::new(3, 4, 1)));
+        assert!(!req.matches(&Version::new(4, 0, 0)));
+        let req = gem_requirement_to_req(&["~> 3.2.0"]).unwrap();
+        assert!(req.matches(&Version::new(3, 2, 5)));
+        assert!(!req.matches(&Version::new(3, 3, 0)));
+        let req = gem_requirement_to_req(&[">= 3.1", "< 3.4"]).unwrap();
+        assert!(req.matches(&Version::new(3, 3, 6)));
+        assert!(!req.matches(&Version::new(3, 0, 7)));
+        assert!(gem_requirement_to_req(&["~> 3"]).unwrap().matches(&Version::new(3, 9, 0)));
+        assert!(gem_requirement_to_req(&["3.2.2"]).unwrap().matches(&Version::new(3, 2, 2)));
+    }
+}
@@ -0,0 +1,148 @@
+//! Packages of JS monorepos: `workspaces` of package.json and `packages` of pnpm-workspace.yaml.
+use crate::common::PackageJson;
+use std::path::{Path, PathBuf};
+use yaml_rust2::YamlLoader;
+
+/// directories skipped to search packages
+const IGNORED_DIRS: [&str; 4] = ["node_modules", ".git", "dist", "build"];
+
+/// package of the workspace with its name in package.json
+#[derive(Debug, Default)]
+pub struct WorkspacePackage {
+    pub name: String,
+    pub package_json: PackageJson,
+}
+
+/// items of `packages` in pnpm-workspace.yaml, such as `- "packages/*"`
+fn parse_pnpm_workspace(text: &str) -> Vec<String> {
+    YamlLoader::load_from_str(text)
+        .ok()
+        .and_then(|docs| docs.into_iter().next())
+        .and_then(|doc| doc["packages"].as_vec().cloned())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str())
+                .map(|item| item.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn get_workspace_patterns(root: &Path, package_json: &PackageJson) -> Vec<String> {
+    let mut patterns: Vec<String> = vec![];
+    if let Some(workspaces) = &package_json.workspaces {
+        let items = workspaces.get("packages").unwrap_or(workspaces);
+        if let Some(items) = items.as_array() {
+            patterns.extend(items.iter().filter_map(|item| item.as_str()).map(|item| item.to_string()));
+        }
+    }
+    if let Ok(text) = std::fs::read_to_string(root.join("pnpm-workspace.yaml")) {
+        patterns.extend(parse_pnpm_workspace(&text));
+    }
+    patterns
+}
+
+/// sub directories without hidden, node_modules and build output directories
+pub fn sub_dirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .filter(|path| {
+                    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+                    !name.starts_with('.') && !IGNORED_DIRS.contains(&name)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+/// directories of a pattern with `*` and `**` segments, such as `apps/*` and `packages/**`
+pub fn expand_pattern(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![root.to_path_buf()];
+    for segment in pattern.trim_start_matches("./").split('/').filter(|segment| !segment.is_empty()) {
+        dirs = match segment {
+            "*" => dirs.iter().flat_map(|dir| sub_dirs(dir)).collect(),
+            "**" => {
+                let mut all_dirs = vec![];
+                let mut pending = dirs.clone();
+                while let Some(dir) = pending.pop() {
+                    let children = sub_dirs(&dir);
+                    pending.extend(children.iter().cloned());
+                    all_dirs.push(dir);
+                }
+                all_dirs
+            }
+            _ => dirs.iter().map(|dir| dir.join(segment)).filter(|dir| dir.is_dir()).collect(),
+        };
+    }
+    dirs
+}
+
+/// packages of the workspace in the current directory, and `!` patterns exclude packages
+pub fn find_workspace_packages(package_json: &PackageJson) -> Vec<WorkspacePackage> {
+    let Ok(root) = std::env::current_dir() else {
+        return vec![];
+    };
+    let patterns = get_workspace_patterns(&root, package_json);
+    let mut excluded: Vec<PathBuf> = vec![];
+    let mut dirs: Vec<PathBuf> = vec![];
+    for pattern in &patterns {
+        if let Some(pattern) = pattern.strip_prefix('!') {
+            excluded.extend(expand_pattern(&root, pattern));
+        } else {
+            dirs.extend(expand_pattern(&root, pattern));
+        }
+    }
+    dirs.sort();
+    dirs.dedup();
+    dirs.into_iter()
+        .filter(|dir| *dir != root && !excluded.contains(dir))
+        .filter_map(|dir| {
+            let text = std::fs::read_to_string(dir.join("package.json")).ok()?;
+            let package_json: PackageJson = serde_json::from_str(&text).ok()?;
+            let name = package_json.name.clone().unwrap_or_else(|| {
+                dir.file_name().unwrap_or_default().to_string_lossy().to_string()
+            });
+            Some(WorkspacePackage { name, package_json })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pnpm_workspace() {
+        let text = r#"
+packages:
+  - "apps/*"
+  - 'packages/**'
+  # comment
+  - "!**/test/**"
+catalog:
+  react: ^18
+"#;
+        assert_eq!(vec!["apps/*", "packages/**", "!**/test/**"], parse_pnpm_workspace(text));
+        let text = "packages: [\"apps/*\", libs/*]\nonlyBuiltDependencies:\n  - esbuild\n";
+        assert_eq!(vec!["apps/*", "libs/*"], parse_pnpm_workspace(text));
+        assert!(parse_pnpm_workspace("packages: [").is_empty());
+    }
+
+    #[test]
+    fn test_expand_pattern() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("apps/web")).unwrap();
+        std::fs::create_dir_all(root.join("apps/docs/node_modules")).unwrap();
+        std::fs::create_dir_all(root.join("packages/ui/icons")).unwrap();
+        assert_eq!(vec![root.join("apps/docs"), root.join("apps/web")], expand_pattern(root, "apps/*"));
+        assert_eq!(3, expand_pattern(root, "packages/**").len());
+    }
+}
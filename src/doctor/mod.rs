@@ -0,0 +1,1033 @@
+//! Check your system for potential problems to run tasks, and fix them with `tk --doctor --fix`
+pub mod versions;
+
+use crate::command_utils::{is_command_available, run_command, CommandOutput};
+use crate::common::{get_npm_command, parse_package_json};
+use crate::errors::KeeperError;
+use crate::{managers, polyglot, runners};
+use colored::Colorize;
+use error_stack::{IntoReport, Report};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Ok,
+    Suggestion,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// warnings and errors make `tk --doctor` fail
+    pub fn is_failure(&self) -> bool {
+        *self >= Severity::Warning
+    }
+}
+
+/// Result of one check, such as a runner command or a minimum version
+#[derive(Serialize)]
+pub struct Check {
+    /// stable id for CI gating, such as `runner.just`, `manager.maven` or `version.node`
+    pub id: String,
+    pub severity: Severity,
+    pub message: String,
+    /// remediation hint
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    #[serde(skip)]
+    pub fix: Option<Fix>,
+}
+
+impl Check {
+    pub fn new(id: &str, severity: Severity, message: impl Into<String>) -> Self {
+        Check {
+            id: id.to_string(),
+            severity,
+            message: message.into(),
+            hint: None,
+            fix: None,
+        }
+    }
+
+    pub fn ok(id: &str, message: impl Into<String>) -> Self {
+        Check::new(id, Severity::Ok, message)
+    }
+
+    pub fn suggestion(id: &str, message: impl Into<String>) -> Self {
+        Check::new(id, Severity::Suggestion, message)
+    }
+
+    pub fn warning(id: &str, message: impl Into<String>) -> Self {
+        Check::new(id, Severity::Warning, message)
+    }
+
+    pub fn error(id: &str, message: impl Into<String>) -> Self {
+        Check::new(id, Severity::Error, message)
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// the fix is the hint too if no hint given
+    pub fn with_fix(mut self, fix: Option<Fix>) -> Self {
+        if self.hint.is_none() {
+            self.hint = fix.as_ref().map(|fix| fix.description.clone());
+        }
+        self.fix = fix;
+        self
+    }
+}
+
+enum FixAction {
+    Command(String, Vec<String>),
+    Install(fn() -> Result<CommandOutput, Report<KeeperError>>),
+}
+
+/// A concrete remediation for a problem, such as `uv tool install invoke`
+pub struct Fix {
+    pub description: String,
+    action: FixAction,
+}
+
+impl Fix {
+    pub fn command(program: &str, args: &[&str]) -> Self {
+        Fix {
+            description: format!("{} {}", program, args.join(" ")),
+            action: FixAction::Command(
+                program.to_string(),
+                args.iter().map(|arg| arg.to_string()).collect(),
+            ),
+        }
+    }
+
+    /// remediation by an `install()` helper of a runner
+    pub fn install(
+        description: &str,
+        install: fn() -> Result<CommandOutput, Report<KeeperError>>,
+    ) -> Self {
+        Fix {
+            description: description.to_string(),
+            action: FixAction::Install(install),
+        }
+    }
+
+    pub fn apply(&self) -> Result<(), Report<KeeperError>> {
+        let output = match &self.action {
+            FixAction::Command(program, args) => {
+                let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+                run_command(program, &args, true)?
+            }
+            FixAction::Install(install) => install()?,
+        };
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(KeeperError::FailedToRunTasks(self.description.clone()).into_report())
+        }
+    }
+}
+
+/// `cargo binstall` if available, otherwise `cargo install`
+fn cargo_install(crate_name: &str) -> Option<Fix> {
+    if is_command_available("cargo-binstall") {
+        Some(Fix::command("cargo", &["binstall", "-y", crate_name]))
+    } else if is_command_available("cargo") {
+        Some(Fix::command("cargo", &["install", crate_name]))
+    } else {
+        None
+    }
+}
+
+fn npm_install(package: &str) -> Option<Fix> {
+    if is_command_available("npm") {
+        Some(Fix::command("npm", &["i", "-g", package]))
+    } else {
+        None
+    }
+}
+
+/// install dependencies of package.json by its package manager
+fn node_modules_install() -> Option<Fix> {
+    let package_json = parse_package_json().unwrap_or_default();
+    let npm_command = get_npm_command(&package_json);
+    if is_command_available(npm_command) {
+        Some(Fix::command(npm_command, &["install"]))
+    } else {
+        None
+    }
+}
+
+fn uv_tool_install(
+    description: &str,
+    install: fn() -> Result<CommandOutput, Report<KeeperError>>,
+) -> Option<Fix> {
+    if is_command_available("uv") {
+        Some(Fix::install(description, install))
+    } else {
+        None
+    }
+}
+
+/// `sdk` is a shell function, and sdkman-init.sh should be sourced first
+fn sdk_install(candidate: &str, version: &str) -> Option<Fix> {
+    let sdkman_init = dirs::home_dir()?
+        .join(".sdkman")
+        .join("bin")
+        .join("sdkman-init.sh");
+    if !sdkman_init.exists() {
+        return None;
+    }
+    let sdk_command = if version.is_empty() {
+        format!("sdk install {}", candidate)
+    } else {
+        format!("sdk install {} {}", candidate, version)
+    };
+    let command_line = format!("source \"{}\" && {}", sdkman_init.display(), sdk_command);
+    Some(Fix {
+        description: sdk_command,
+        action: FixAction::Command("bash".to_string(), vec!["-c".to_string(), command_line]),
+    })
+}
+
+/// check of the command for a runner or manager: `tool` is the command with its home page
+fn command_check(
+    id: &str,
+    tool: &str,
+    file_name: &str,
+    command_available: bool,
+    fix: impl FnOnce() -> Option<Fix>,
+) -> Check {
+    if command_available {
+        Check::ok(id, format!("{} command available for {}", tool, file_name))
+    } else {
+        Check::warning(id, format!("{} command not available for {}", tool, file_name))
+            .with_fix(fix())
+    }
+}
+
+fn no_fix() -> Option<Fix> {
+    None
+}
+
+/// one check per detected runner, manager and language SDK, and checks of minimum versions
+pub fn diagnose() -> Vec<Check> {
+    let mut checks = vec![];
+    if runners::justfile::is_available() {
+        checks.push(command_check(
+            "runner.just",
+            "just(https://github.com/casey/just)",
+            "justfile",
+            runners::justfile::is_command_available(),
+            || cargo_install("just"),
+        ));
+    }
+    if runners::packagejson::is_available() {
+        checks.push(command_check(
+            "runner.npm",
+            "npm(https://nodejs.org)",
+            "package.json",
+            runners::packagejson::is_command_available(),
+            no_fix,
+        ));
+    }
+    if runners::denojson::is_available() {
+        checks.push(command_check(
+            "runner.deno",
+            "deno(https://deno.land)",
+            "deno.json",
+            runners::denojson::is_command_available(),
+            || npm_install("deno"),
+        ));
+    }
+    if runners::makefile::is_available() {
+        checks.push(command_check(
+            "runner.make",
+            "make(https://www.gnu.org/software/make)",
+            "makefile",
+            runners::makefile::is_command_available(),
+            no_fix,
+        ));
+        if which::which("mmake").is_err() {
+            checks.push(Check::suggestion(
+                "runner.mmake",
+                "mmake(https://github.com/tj/mmake) is more powerful to run Makefile",
+            ));
+        }
+    }
+    if runners::rakefile::is_available() {
+        checks.push(command_check(
+            "runner.rake",
+            "rake(https://ruby.github.io/rake/)",
+            "rakefile",
+            runners::rakefile::is_command_available(),
+            no_fix,
+        ));
+    }
+    if runners::jakefile::is_available() {
+        checks.push(command_check(
+            "runner.jake",
+            "jake(https://jakejs.com/)",
+            "jakefile",
+            runners::jakefile::is_command_available(),
+            || npm_install("jake"),
+        ));
+    }
+    if runners::gulpfile::is_available() {
+        checks.push(command_check(
+            "runner.gulp",
+            "gulp(https://gulpjs.com/)",
+            "gulpfile.js",
+            runners::gulpfile::is_command_available(),
+            || npm_install("gulp-cli"),
+        ));
+    }
+    if runners::gruntfile::is_available() {
+        checks.push(command_check(
+            "runner.grunt",
+            "grunt(https://gruntjs.com/)",
+            "Gruntfile.js",
+            runners::gruntfile::is_command_available(),
+            || npm_install("grunt-cli"),
+        ));
+    }
+    if runners::taskfileyml::is_available() {
+        checks.push(command_check(
+            "runner.task",
+            "task(https://taskfile.dev)",
+            "Taskfile.yml",
+            runners::taskfileyml::is_command_available(),
+            || npm_install("@go-task/cli"),
+        ));
+    }
+    if runners::usql::is_available() {
+        checks.push(command_check(
+            "runner.usql",
+            "usql(https://github.com/xo/usql/)",
+            "queries.sql",
+            runners::usql::is_command_available(),
+            no_fix,
+        ));
+    }
+    if runners::makefiletoml::is_available() {
+        checks.push(command_check(
+            "runner.cargo-make",
+            "cargo-make(https://github.com/sagiegurari/cargo-make)",
+            "Makefile.toml",
+            runners::makefiletoml::is_command_available(),
+            || cargo_install("cargo-make"),
+        ));
+    }
+    if runners::bun_shell::is_available() {
+        checks.push(command_check(
+            "runner.bun",
+            "bun(https://bun.sh/docs/runtime/shell)",
+            "Taskfile.ts",
+            runners::bun_shell::is_command_available(),
+            || npm_install("bun"),
+        ));
+    }
+    if runners::taskspy::is_available() {
+        checks.push(
+            command_check(
+                "runner.invoke",
+                "invoke(https://www.pyinvoke.org)",
+                "tasks.py",
+                runners::taskspy::is_command_available(),
+                || uv_tool_install("uv tool install --python 3.11 invoke", runners::taskspy::install),
+            )
+            .with_hint("uv tool install --python 3.11 invoke"),
+        );
+    }
+    if runners::composer::is_available() {
+        checks.push(command_check(
+            "runner.composer",
+            "composer(https://getcomposer.org/)",
+            "composer.json",
+            runners::composer::is_command_available(),
+            no_fix,
+        ));
+    }
+    if runners::jbang::is_available() {
+        checks.push(command_check(
+            "runner.jbang",
+            "jbang(https://www.jbang.dev/)",
+            "jbang-catalog.json",
+            runners::jbang::is_command_available(),
+            || sdk_install("jbang", ""),
+        ));
+    }
+    if runners::poetry::is_available() {
+        checks.push(
+            command_check(
+                "runner.poetry",
+                "poetry(https://python-poetry.org/)",
+                "pyproject.toml",
+                runners::poetry::is_command_available(),
+                || uv_tool_install("uv tool install --python 3.13 poetry", runners::poetry::install),
+            )
+            .with_hint("uv tool install --python 3.13 poetry"),
+        );
+    }
+    if runners::poe::is_available() {
+        checks.push(
+            command_check(
+                "runner.poe",
+                "poe(https://github.com/nat-n/poethepoet)",
+                "pyproject.toml",
+                runners::poe::is_command_available(),
+                || uv_tool_install("uv tool install --python 3.13 poethepoet", runners::poe::install),
+            )
+            .with_hint("uv tool install --python 3.13 poethepoet"),
+        );
+    }
+    if runners::pdm::is_available() {
+        checks.push(
+            command_check(
+                "runner.pdm",
+                "pdm(https://pdm-project.org)",
+                "pyproject.toml",
+                runners::pdm::is_command_available(),
+                || uv_tool_install("uv tool install pdm", runners::pdm::install),
+            )
+            .with_hint("uv tool install pdm"),
+        );
+    }
+    if runners::hatch::is_available() {
+        checks.push(
+            command_check(
+                "runner.hatch",
+                "hatch(https://hatch.pypa.io)",
+                "pyproject.toml",
+                runners::hatch::is_command_available(),
+                || uv_tool_install("uv tool install hatch", runners::hatch::install),
+            )
+            .with_hint("uv tool install hatch"),
+        );
+    }
+    // pixi.toml is used by both the pixi runner and the pixi manager
+    if runners::pixi::is_available() {
+        checks.push(
+            command_check(
+                "runner.pixi",
+                "pixi(https://pixi.sh)",
+                "pixi.toml",
+                runners::pixi::is_command_available(),
+                no_fix,
+            )
+            .with_hint("curl -fsSL https://pixi.sh/install.sh | sh"),
+        );
+    }
+    if runners::argcfile::is_available() {
+        checks.push(command_check(
+            "runner.argc",
+            "argc(https://github.com/sigoden/argc)",
+            "Argcfile.sh",
+            runners::argcfile::is_command_available(),
+            || cargo_install("argc"),
+        ));
+    }
+    if runners::amberfile::is_available() {
+        checks.push(command_check(
+            "runner.amber",
+            "amber(https://amber-lang.com/)",
+            "Amberfile",
+            runners::amberfile::is_command_available(),
+            no_fix,
+        ));
+    }
+    if runners::maskfile::is_available() {
+        // code blocks of maskfile.md are executed directly without mask
+        if runners::maskfile::is_command_available() {
+            checks.push(command_check(
+                "runner.mask",
+                "mask(https://github.com/jacobdeichert/mask)",
+                "maskfile.md",
+                true,
+                no_fix,
+            ));
+        } else {
+            checks.push(
+                Check::suggestion(
+                    "runner.mask",
+                    "mask(https://github.com/jacobdeichert/mask) command not available for maskfile.md, and code blocks will be executed directly",
+                )
+                .with_fix(cargo_install("mask")),
+            );
+        }
+    }
+    // mise.toml may only declare tools, so the check is for mise tasks
+    if runners::mise::is_available()
+        && runners::mise::list_tasks().is_ok_and(|tasks| !tasks.is_empty())
+    {
+        checks.push(command_check(
+            "runner.mise",
+            "mise(https://mise.jdx.dev)",
+            "mise tasks",
+            runners::mise::is_command_available(),
+            || cargo_install("mise"),
+        ));
+    }
+    // turbo and nx are dev dependencies of monorepos, and installed by the package manager
+    if runners::turbo::is_available() {
+        checks.push(command_check(
+            "runner.turbo",
+            "turbo(https://turborepo.com)",
+            "turbo.json",
+            runners::turbo::is_command_available(),
+            node_modules_install,
+        ));
+    }
+    if runners::nx::is_available() {
+        checks.push(command_check(
+            "runner.nx",
+            "nx(https://nx.dev)",
+            "nx.json",
+            runners::nx::is_command_available(),
+            node_modules_install,
+        ));
+    }
+    if let Some(compose_file) = runners::compose::find_compose_file() {
+        checks.push(command_check(
+            "runner.compose",
+            "docker compose(https://docs.docker.com/compose/)",
+            &compose_file,
+            runners::compose::is_command_available(),
+            no_fix,
+        ));
+    }
+    if runners::nix::is_available() {
+        let nix_tasks = runners::nix::list_nix_tasks();
+        if nix_tasks.iter().any(|task| task.kind != runners::nix::NixTaskKind::App) {
+            checks.push(command_check(
+                "runner.devenv",
+                "devenv(https://devenv.sh)",
+                "devenv.nix",
+                is_command_available("devenv"),
+                no_fix,
+            ));
+        }
+        if nix_tasks.iter().any(|task| task.kind == runners::nix::NixTaskKind::App) {
+            checks.push(command_check(
+                "runner.nix",
+                "nix(https://nixos.org)",
+                "flake.nix",
+                is_command_available("nix"),
+                no_fix,
+            ));
+        }
+    }
+    if runners::earthly::has_earthfile() {
+        checks.push(command_check(
+            "runner.earthly",
+            "earthly(https://earthly.dev)",
+            "Earthfile",
+            runners::earthly::is_earthly_available(),
+            no_fix,
+        ));
+    }
+    if runners::earthly::has_dagger_module() {
+        checks.push(command_check(
+            "runner.dagger",
+            "dagger(https://dagger.io)",
+            "dagger.json",
+            runners::earthly::is_dagger_available(),
+            no_fix,
+        ));
+    }
+    if runners::nurfile::is_available() {
+        checks.push(command_check(
+            "runner.nur",
+            "nur(https://github.com/ddanier/nur)",
+            "nurfile",
+            runners::nurfile::is_command_available(),
+            || cargo_install("nur"),
+        ));
+    }
+    // ==========package managers============
+    if managers::maven::is_available() {
+        checks.push(command_check(
+            "manager.maven",
+            "maven(https://maven.apache.org/)",
+            "pom.xml",
+            managers::maven::is_command_available(),
+            || sdk_install("maven", ""),
+        ));
+        if managers::maven::is_command_available() && !std::path::Path::new("mvnw").exists() {
+            checks.push(
+                Check::suggestion(
+                    "manager.maven.wrapper",
+                    "Maven wrapper(mvnw) not found, and it makes builds reproducible",
+                )
+                .with_fix(Some(Fix::command("mvn", &["-N", "wrapper:wrapper"]))),
+            );
+        }
+    }
+    if managers::gradle::is_available() {
+        checks.push(command_check(
+            "manager.gradle",
+            "Gradle(https://gradle.org/)",
+            "build.gradle",
+            managers::gradle::is_command_available(),
+            || sdk_install("gradle", ""),
+        ));
+        if managers::gradle::is_command_available() {
+            if !std::path::Path::new("gradlew").exists() && is_command_available("gradle") {
+                checks.push(
+                    Check::suggestion(
+                        "manager.gradle.wrapper",
+                        "Gradle wrapper(gradlew) not found, and it makes builds reproducible",
+                    )
+                    .with_fix(Some(Fix::command("gradle", &["wrapper"]))),
+                );
+            }
+            //global plugins for gradle $HOME/.gradle/init.d/plugins.gradle
+            if !dirs::home_dir()
+                .unwrap()
+                .join(".gradle")
+                .join("init.d")
+                .join("plugins.gradle")
+                .exists()
+            {
+                checks.push(
+                    Check::suggestion(
+                        "manager.gradle.plugins",
+                        "global `plugins.gradle` not available for `dependencyUpdates` task",
+                    )
+                    .with_hint("please check https://github.com/linux-china/task-keeper#gradle"),
+                );
+            }
+        }
+    }
+    if managers::kotlin_toolchain::is_available() {
+        checks.push(command_check(
+            "manager.kotlin",
+            "Kotlin Toolchain(https://kotlin-toolchain.org/)",
+            "module.yaml",
+            managers::kotlin_toolchain::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::sbt::is_available() {
+        checks.push(command_check(
+            "manager.sbt",
+            "sbt(https://www.scala-sbt.org/)",
+            "build.sbt",
+            managers::sbt::is_command_available(),
+            || sdk_install("sbt", ""),
+        ));
+        //global plugins for sbt $HOME/.sbt/1.0/plugins/plugins.sbt
+        if managers::sbt::is_command_available()
+            && !dirs::home_dir()
+                .unwrap()
+                .join(".sbt")
+                .join("1.0")
+                .join("plugins")
+                .join("plugins.sbt")
+                .exists()
+        {
+            checks.push(
+                Check::suggestion(
+                    "manager.sbt.plugins",
+                    "global `plugins.sbt` not available for `dependencyUpdates` task",
+                )
+                .with_hint("please check https://github.com/linux-china/task-keeper#sbt"),
+            );
+        }
+    }
+    if managers::lein::is_available() {
+        checks.push(command_check(
+            "manager.lein",
+            "lein(https://leiningen.org/)",
+            "project.clj",
+            managers::lein::is_command_available(),
+            no_fix,
+        ));
+        //global plugins for lein $HOME/.lein/profiles.clj
+        if managers::lein::is_command_available()
+            && !dirs::home_dir()
+                .unwrap()
+                .join(".lein")
+                .join("profiles.clj")
+                .exists()
+        {
+            checks.push(
+                Check::suggestion(
+                    "manager.lein.profiles",
+                    "global `profiles.clj` not available for `outdated` task",
+                )
+                .with_hint("please check https://github.com/linux-china/task-keeper#lein"),
+            );
+        }
+    }
+    if managers::npm::is_available() {
+        checks.push(command_check(
+            "manager.npm",
+            "npm(https://nodejs.org/)",
+            "package.json",
+            managers::npm::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::cargo::is_available() {
+        checks.push(command_check(
+            "manager.cargo",
+            "cargo(https://doc.rust-lang.org/cargo/)",
+            "Cargo.toml",
+            managers::cargo::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::composer::is_available() {
+        checks.push(command_check(
+            "manager.composer",
+            "composer(https://getcomposer.org/)",
+            "composer.json",
+            managers::composer::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::bundler::is_available() {
+        checks.push(command_check(
+            "manager.bundle",
+            "bundle(https://bundler.io/)",
+            "Gemfile",
+            managers::bundler::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::golang::is_available() {
+        checks.push(command_check(
+            "manager.go",
+            "go(https://go.dev/)",
+            "go.mod",
+            managers::golang::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::cmakeconan::is_available() {
+        checks.push(command_check(
+            "manager.cmake",
+            "cmake and conan(https://github.com/conan-io/cmake-conan/)",
+            "CMakeLists.txt and conanfile.txt",
+            managers::cmakeconan::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::meson::is_available() {
+        checks.push(command_check(
+            "manager.meson",
+            "meson(https://mesonbuild.com)",
+            "meson.build",
+            managers::meson::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::swift::is_available() {
+        checks.push(command_check(
+            "manager.swift",
+            "swift(https://www.swift.org/)",
+            "Package.swift",
+            managers::swift::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::bazel::is_available() {
+        checks.push(command_check(
+            "manager.bazel",
+            "bazel(https://bazel.build/)",
+            "WORKSPACE",
+            managers::bazel::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::pipenv::is_available() {
+        checks.push(command_check(
+            "manager.pipenv",
+            "pipenv(https://pipenv.pypa.io/en/latest/)",
+            "Pipfile",
+            managers::pipenv::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::requirements::is_available() {
+        checks.push(command_check(
+            "manager.pip",
+            "pip(https://pypi.org/project/pip/)",
+            "requirements.txt",
+            managers::requirements::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::rebar3::is_available() {
+        checks.push(command_check(
+            "manager.rebar3",
+            "rebar3(https://rebar3.readme.io/)",
+            "rebar.config",
+            managers::rebar3::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::mix::is_available() {
+        checks.push(command_check(
+            "manager.mix",
+            "mix(https://hexdocs.pm/mix/1.13/Mix.html)",
+            "mix.exs",
+            managers::mix::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::dart::is_available() {
+        checks.push(command_check(
+            "manager.dart",
+            "dart(https://dart.dev/guides/packages)",
+            "pubspec.yaml",
+            managers::dart::is_command_available(),
+            no_fix,
+        ));
+    }
+    if managers::zig::is_available() {
+        checks.push(command_check(
+            "manager.zig",
+            "zig(https://ziglang.org/)",
+            "build.zig",
+            managers::zig::is_command_available(),
+            no_fix,
+        ));
+    }
+    // ==========language SDKs============
+    if polyglot::java::is_available()
+        && let Some(java_version) = polyglot::java::get_default_version()
+    {
+        if let Some(java_home) = polyglot::java::find_sdk_home() {
+            checks.push(Check::ok(
+                "sdk.java",
+                format!("Java {} found, and the JDK({}) installed", java_version, java_home.display()),
+            ));
+        } else {
+            checks.push(
+                Check::warning(
+                    "sdk.java",
+                    format!("Java {} found, but the JDK not installed!", java_version),
+                )
+                .with_hint("please install the JDK by sdkman, mise or your package manager"),
+            );
+        }
+    }
+    if polyglot::node::is_available()
+        && let Ok(node_version) = polyglot::node::get_default_version()
+    {
+        if let Some(node_home) = polyglot::node::find_sdk_home() {
+            checks.push(Check::ok(
+                "sdk.node",
+                format!("Node.js {} required, and installed in {}", node_version, node_home.display()),
+            ));
+        } else {
+            checks.push(
+                Check::warning(
+                    "sdk.node",
+                    format!("Node.js {} required, but not installed!", node_version),
+                )
+                .with_hint("please install it by nvm, fnm, Volta or mise"),
+            );
+        }
+    }
+    if polyglot::ruby::is_available()
+        && let Some(requirement) = polyglot::ruby::get_requirement()
+    {
+        let ruby_version = requirement.text();
+        if let Some(ruby_home) = polyglot::ruby::find_sdk_home() {
+            checks.push(Check::ok(
+                "sdk.ruby",
+                format!("Ruby {} found, and installed in {}", ruby_version, ruby_home.display()),
+            ));
+        } else {
+            let hint = match requirement {
+                polyglot::ruby::RubyRequirement::Version(_) => format!(
+                    "please install it by rbenv, rvm, ruby-install or mise, such as `rbenv install {}`",
+                    ruby_version
+                ),
+                polyglot::ruby::RubyRequirement::Range(_) => format!(
+                    "please install a Ruby matching `{}` by rbenv, rvm, ruby-install or mise",
+                    ruby_version
+                ),
+            };
+            checks.push(
+                Check::warning(
+                    "sdk.ruby",
+                    format!("Ruby {} found, but not installed!", ruby_version),
+                )
+                .with_hint(hint),
+            );
+        }
+    }
+    if polyglot::golang::is_available()
+        && let Some(requirement) = polyglot::golang::get_requirement()
+    {
+        let go_version = requirement.version();
+        if let Some(go_root) = polyglot::golang::find_sdk_home() {
+            checks.push(Check::ok(
+                "sdk.go",
+                format!("Go {} required, and installed in {}", go_version, go_root.display()),
+            ));
+        } else {
+            let hint = format!(
+                "please install it by `go install golang.org/dl/go{0}@latest && go{0} download`, goenv or mise",
+                go_version
+            );
+            match polyglot::golang::get_installed_version() {
+                Some(installed) if polyglot::golang::is_installed_version_matched(&requirement, &installed) => {
+                    checks.push(Check::ok(
+                        "sdk.go",
+                        format!("Go {} required, and go {} on PATH", go_version, installed),
+                    ));
+                }
+                Some(installed) => checks.push(
+                    Check::warning(
+                        "sdk.go",
+                        format!("Go {} required, but go {} on PATH", go_version, installed),
+                    )
+                    .with_hint(hint),
+                ),
+                None => checks.push(
+                    Check::warning("sdk.go", format!("Go {} required, but not installed!", go_version))
+                        .with_hint(hint),
+                ),
+            }
+        }
+    }
+    if polyglot::rust::is_available()
+        && let Some(status) = polyglot::rust::get_toolchain_status()
+    {
+        checks.extend(rust_toolchain_checks(&status));
+    }
+    if polyglot::sdkman::is_available() {
+        for candidate in polyglot::sdkman::list_candidates() {
+            let check_id = format!("sdkman.{}", candidate.name);
+            if candidate.installed {
+                checks.push(Check::ok(
+                    &check_id,
+                    format!("{} {} found in .sdkmanrc, and installed", candidate.name, candidate.version),
+                ));
+            } else {
+                checks.push(
+                    Check::warning(
+                        &check_id,
+                        format!("{} {} found in .sdkmanrc, but not installed", candidate.name, candidate.version),
+                    )
+                    .with_hint(format!("sdk install {} {}", candidate.name, candidate.version))
+                    .with_fix(sdk_install(&candidate.name, &candidate.version)),
+                );
+            }
+        }
+    }
+    checks.extend(versions::check_versions());
+    checks
+}
+
+fn rust_toolchain_checks(status: &polyglot::rust::ToolchainStatus) -> Vec<Check> {
+    let mut checks = vec![];
+    let channel = status.toolchain.channel.as_str();
+    if let Some(rust_version) = &status.newer_rust_version {
+        checks.push(
+            Check::error(
+                "sdk.rust.version",
+                format!(
+                    "Rust {} from rust-toolchain is older than rust-version {} in Cargo.toml",
+                    channel, rust_version
+                ),
+            )
+            .with_hint("please update the channel in rust-toolchain.toml"),
+        );
+    }
+    if !status.rustup_available {
+        checks.push(
+            Check::warning(
+                "sdk.rust",
+                format!("Rust {} found in rust-toolchain, but rustup not available", channel),
+            )
+            .with_hint("please install rustup from https://rustup.rs/"),
+        );
+        return checks;
+    }
+    if !status.installed {
+        let args = status.toolchain.install_args();
+        let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+        checks.push(
+            Check::warning(
+                "sdk.rust",
+                format!("Rust {} found in rust-toolchain, but not installed", channel),
+            )
+            .with_fix(Some(Fix::command("rustup", &args))),
+        );
+        return checks;
+    }
+    checks.push(Check::ok(
+        "sdk.rust",
+        format!("Rust {} found in rust-toolchain, and installed", channel),
+    ));
+    if !status.missing_components.is_empty() {
+        let mut args = vec!["component", "add", "--toolchain", channel];
+        args.extend(status.missing_components.iter().map(|name| name.as_str()));
+        checks.push(
+            Check::warning(
+                "sdk.rust.components",
+                format!("Rust components not installed: {}", status.missing_components.join(", ")),
+            )
+            .with_fix(Some(Fix::command("rustup", &args))),
+        );
+    }
+    if !status.missing_targets.is_empty() {
+        let mut args = vec!["target", "add", "--toolchain", channel];
+        args.extend(status.missing_targets.iter().map(|name| name.as_str()));
+        checks.push(
+            Check::warning(
+                "sdk.rust.targets",
+                format!("Rust targets not installed: {}", status.missing_targets.join(", ")),
+            )
+            .with_fix(Some(Fix::command("rustup", &args))),
+        );
+    }
+    checks
+}
+
+/// Apply the fixes of failed checks, confirm each step unless `yes`, and print what was changed.
+/// Return false if any fix failed.
+pub fn fix_problems(checks: &[Check], yes: bool) -> bool {
+    let fixes: Vec<&Fix> = checks
+        .iter()
+        .filter(|check| check.severity != Severity::Ok)
+        .filter_map(|check| check.fix.as_ref())
+        .collect();
+    if fixes.is_empty() {
+        println!("{}", "[tk] nothing to fix".bold().blue());
+        return true;
+    }
+    let mut changes: Vec<String> = vec![];
+    let mut success = true;
+    for fix in fixes {
+        if !yes {
+            let prompt = format!("[tk] run `{}`? [y/N] ", fix.description);
+            let reply = rprompt::prompt_reply(&prompt).unwrap_or_default();
+            if !matches!(reply.trim().to_lowercase().as_str(), "y" | "yes") {
+                changes.push(format!("skipped: {}", fix.description));
+                continue;
+            }
+        }
+        println!("{}", format!("[tk] fix: {}", fix.description).bold().blue());
+        match fix.apply() {
+            Ok(()) => changes.push(format!("done: {}", fix.description)),
+            Err(err) => {
+                success = false;
+                changes.push(format!("failed: {} ({})", fix.description, err));
+            }
+        }
+    }
+    println!("{}", "[tk] doctor fix log:".bold().blue());
+    for change in &changes {
+        println!("  {}", change);
+    }
+    success
+}
@@ -0,0 +1,277 @@
+//! Check resolved tool versions against the minimum versions declared by the project:
+//! `engines` in package.json, `rust-version` in Cargo.toml, `requires-python` in pyproject.toml,
+//! and the Maven version required by pom.xml or the distribution of `maven-wrapper.properties`.
+use crate::command_utils::capture_command_output;
+use crate::common::parse_package_json;
+use crate::common::pyproject::PyProjectToml;
+use crate::common::versions::{npm_range_to_reqs, parse_version, pep440_to_req, read_rust_version};
+use crate::doctor::Check;
+use crate::polyglot;
+use regex::Regex;
+use semver::{Version, VersionReq};
+use std::path::Path;
+
+/// A version requirement declared by the project
+struct Requirement {
+    /// check id, such as `version.node`
+    id: &'static str,
+    tool: &'static str,
+    /// where the requirement is declared, such as `engines.node in package.json`
+    source: String,
+    requirement: String,
+    /// alternatives of the requirement, any of them matched is fine
+    version_reqs: Option<Vec<VersionReq>>,
+}
+
+pub fn check_versions() -> Vec<Check> {
+    let mut checks = vec![];
+    for requirement in declared_requirements() {
+        let Some(resolved) = resolve_version(requirement.tool) else {
+            // missing commands are reported by other checks
+            continue;
+        };
+        let mut check = check_requirement(&requirement, &resolved);
+        if requirement.tool == "python"
+            && check.severity.is_failure()
+            && let Some(venv) = polyglot::python::find_virtual_env()
+        {
+            check = check.with_hint(format!(
+                "the virtual environment {} is created by python {}, please recreate it with python `{}`",
+                venv.display(),
+                resolved,
+                requirement.requirement
+            ));
+        }
+        checks.push(check);
+    }
+    checks
+}
+
+fn check_requirement(requirement: &Requirement, resolved: &Version) -> Check {
+    let Some(version_reqs) = &requirement.version_reqs else {
+        return Check::suggestion(
+            requirement.id,
+            format!(
+                "failed to understand {} requirement `{}` from {}",
+                requirement.tool, requirement.requirement, requirement.source
+            ),
+        );
+    };
+    if version_reqs.iter().any(|version_req| version_req.matches(resolved)) {
+        Check::ok(
+            requirement.id,
+            format!(
+                "{} {} matches `{}` from {}",
+                requirement.tool, resolved, requirement.requirement, requirement.source
+            ),
+        )
+    } else {
+        Check::error(
+            requirement.id,
+            format!(
+                "{} {} does not match `{}` from {}",
+                requirement.tool, resolved, requirement.requirement, requirement.source
+            ),
+        )
+        .with_hint(format!(
+            "please install {} `{}`",
+            requirement.tool, requirement.requirement
+        ))
+    }
+}
+
+fn declared_requirements() -> Vec<Requirement> {
+    let mut requirements = vec![];
+    let dir = std::env::current_dir().unwrap_or_default();
+    if dir.join("package.json").exists()
+        && let Ok(package_json) = parse_package_json()
+        && let Some(engines) = &package_json.engines
+    {
+        for (id, tool) in [("version.node", "node"), ("version.npm", "npm")] {
+            if let Some(range) = engines.get(tool) {
+                requirements.push(Requirement {
+                    id,
+                    tool,
+                    source: format!("engines.{} in package.json", tool),
+                    requirement: range.clone(),
+                    version_reqs: npm_range_to_reqs(range),
+                });
+            }
+        }
+    }
+    if let Some(rust_version) = read_rust_version(&dir.join("Cargo.toml")) {
+        requirements.push(Requirement {
+            id: "version.rust",
+            tool: "rustc",
+            source: "rust-version in Cargo.toml".to_string(),
+            version_reqs: minimum_to_req(&rust_version).map(|req| vec![req]),
+            requirement: rust_version,
+        });
+    }
+    if dir.join("pyproject.toml").exists()
+        && let Some(requires_python) = PyProjectToml::get_default_project()
+            .ok()
+            .and_then(|pyproject| pyproject.project)
+            .and_then(|project| project.requires_python)
+    {
+        requirements.push(Requirement {
+            id: "version.python",
+            tool: "python",
+            source: "requires-python in pyproject.toml".to_string(),
+            version_reqs: pep440_to_req(&requires_python).map(|req| vec![req]),
+            requirement: requires_python,
+        });
+    }
+    if dir.join("pom.xml").exists() {
+        let pom_xml = std::fs::read_to_string(dir.join("pom.xml")).unwrap_or_default();
+        let wrapper_version = read_maven_wrapper_version(&dir);
+        if let Some(maven_version) = read_required_maven_version(&pom_xml) {
+            requirements.push(Requirement {
+                id: "version.maven",
+                tool: "maven",
+                source: "pom.xml".to_string(),
+                version_reqs: maven_range_to_req(&maven_version).map(|req| vec![req]),
+                requirement: maven_version,
+            });
+        } else if let Some(wrapper_version) = wrapper_version
+            && !dir.join("mvnw").exists()
+        {
+            // without mvnw script, the installed Maven should not be older than the wrapper distribution
+            requirements.push(Requirement {
+                id: "version.maven",
+                tool: "maven",
+                source: ".mvn/wrapper/maven-wrapper.properties".to_string(),
+                version_reqs: minimum_to_req(&wrapper_version).map(|req| vec![req]),
+                requirement: format!(">={}", wrapper_version),
+            });
+        }
+    }
+    requirements
+}
+
+/// Maven version from `distributionUrl` in `.mvn/wrapper/maven-wrapper.properties`
+fn read_maven_wrapper_version(dir: &Path) -> Option<String> {
+    let properties_file = dir.join(".mvn").join("wrapper").join("maven-wrapper.properties");
+    let text = std::fs::read_to_string(properties_file).ok()?;
+    let properties = java_properties::read(text.as_bytes()).ok()?;
+    let distribution_url = properties.get("distributionUrl")?;
+    Regex::new(r"apache-maven-([0-9][0-9.]*)-bin")
+        .unwrap()
+        .captures(distribution_url)
+        .map(|captures| captures[1].trim_end_matches('.').to_string())
+}
+
+/// required Maven version from `<prerequisites>` or `requireMavenVersion` of maven-enforcer-plugin
+fn read_required_maven_version(pom_xml: &str) -> Option<String> {
+    let prerequisites =
+        Regex::new(r"(?s)<prerequisites>.*?<maven>\s*([^<\s]+)\s*</maven>").unwrap();
+    let enforcer =
+        Regex::new(r"(?s)<requireMavenVersion>.*?<version>\s*([^<\s]+)\s*</version>").unwrap();
+    enforcer
+        .captures(pom_xml)
+        .or_else(|| prerequisites.captures(pom_xml))
+        .map(|captures| captures[1].to_string())
+}
+
+/// version of the tool used to run tasks
+fn resolve_version(tool: &str) -> Option<Version> {
+    let (command_name, args): (&str, &[&str]) = match tool {
+        "node" => ("node", &["--version"]),
+        "npm" => ("npm", &["--version"]),
+        "rustc" => ("rustc", &["--version"]),
+        "python" => {
+            if let Some(venv) = polyglot::python::find_virtual_env() {
+                let venv_python = polyglot::python::venv_python(&venv);
+                if !venv_python.exists() {
+                    return None;
+                }
+                let output = capture_command_output(&venv_python.to_string_lossy(), &["--version"]).ok()?;
+                return parse_version(&String::from_utf8_lossy(&output.stdout));
+            } else if which::which("python3").is_ok() {
+                ("python3", &["--version"])
+            } else {
+                ("python", &["--version"])
+            }
+        }
+        "maven" => {
+            let dir = std::env::current_dir().ok()?;
+            if dir.join("mvnw").exists() {
+                // mvnw uses the distribution of maven-wrapper.properties
+                if let Some(wrapper_version) = read_maven_wrapper_version(&dir) {
+                    return parse_version(&wrapper_version);
+                }
+            }
+            ("mvn", &["--version"])
+        }
+        _ => return None,
+    };
+    if which::which(command_name).is_err() && !Path::new(command_name).exists() {
+        return None;
+    }
+    let output = capture_command_output(command_name, args).ok()?;
+    // python 2 prints its version to stderr
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    parse_version(&text)
+}
+
+/// `1.70` as `>=1.70`
+fn minimum_to_req(version: &str) -> Option<VersionReq> {
+    VersionReq::parse(&format!(">={}", version.trim())).ok()
+}
+
+/// Maven version range, such as `[3.6.3,)` or `[3.6,4.0)`, and a plain version is the minimum
+fn maven_range_to_req(range: &str) -> Option<VersionReq> {
+    let range = range.trim();
+    if !range.starts_with(['[', '(']) {
+        return minimum_to_req(range);
+    }
+    let lower_inclusive = range.starts_with('[');
+    let upper_inclusive = range.ends_with(']');
+    let inner = range.get(1..range.len() - 1)?;
+    let Some((lower, upper)) = inner.split_once(',') else {
+        // [3.8.1] is an exact version
+        return VersionReq::parse(&format!("={}", inner.trim())).ok();
+    };
+    let mut comparators = vec![];
+    if !lower.trim().is_empty() {
+        let operator = if lower_inclusive { ">=" } else { ">" };
+        comparators.push(format!("{}{}", operator, lower.trim()));
+    }
+    if !upper.trim().is_empty() {
+        let operator = if upper_inclusive { "<=" } else { "<" };
+        comparators.push(format!("{}{}", operator, upper.trim()));
+    }
+    if comparators.is_empty() {
+        return Some(VersionReq::STAR);
+    }
+    VersionReq::parse(&comparators.join(", ")).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maven_range() {
+        let req = maven_range_to_req("[3.6.3,)").unwrap();
+        assert!(req.matches(&Version::new(3, 9, 6)));
+        assert!(!req.matches(&Version::new(3, 6, 0)));
+        assert!(!maven_range_to_req("[3.6,4.0)").unwrap().matches(&Version::new(4, 0, 0)));
+        assert!(maven_range_to_req("3.8.1").unwrap().matches(&Version::new(3, 9, 0)));
+    }
+
+    #[test]
+    fn test_read_required_maven_version() {
+        let pom_xml = r#"<plugin>
+            <artifactId>maven-enforcer-plugin</artifactId>
+            <configuration><rules><requireMavenVersion>
+              <version>[3.6.3,)</version>
+            </requireMavenVersion></rules></configuration>
+        </plugin>"#;
+        assert_eq!(Some("[3.6.3,)".to_string()), read_required_maven_version(pom_xml));
+    }
+}
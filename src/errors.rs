@@ -51,6 +51,27 @@ pub enum KeeperError {
     #[error("TK-211001: failed to read Argcfile.sh")]
     InvalidArgcFile,
 
+    #[error("TK-212001: failed to read maskfile.md")]
+    InvalidMaskfile,
+
+    #[error("TK-213001: failed to parse mise.toml: {0}")]
+    InvalidMiseToml(String),
+
+    #[error("TK-214001: failed to parse turbo.json")]
+    InvalidTurboJson,
+
+    #[error("TK-215001: failed to parse {0}")]
+    InvalidComposeFile(String),
+
+    #[error("TK-216001: failed to parse Cargo.toml")]
+    InvalidCargoToml,
+
+    #[error("TK-217001: failed to parse pixi.toml: {0}")]
+    InvalidPixiToml(String),
+
+    #[error("TK-218001: failed to read Earthfile")]
+    InvalidEarthfile,
+
     #[error("TK-300001: failed to read pom.xml")]
     InvalidPomXml,
     #[error("TK-300002: failed to read maven-metadata.xml")]
@@ -59,6 +80,8 @@ pub enum KeeperError {
     InvalidTasksJson,
     #[error("TK-302001: failed to list uv tasks")]
     InvalidUvTasks,
+    #[error("TK-302002: failed to load env file: {0}")]
+    InvalidEnvFile(String),
 
     #[error("TK-303001: failed to read queries.sql")]
     InvalidQueriesSQL,
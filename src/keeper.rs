@@ -1,11 +1,15 @@
+use crate::common::capture;
 use crate::common::notification::send_notification;
+use crate::common::process;
+use crate::common::report;
 use crate::errors::KeeperError;
 use crate::models::Task;
 use crate::runners::RUNNERS;
 use crate::{managers, runners};
 use colored::Colorize;
-use error_stack::Report;
+use error_stack::{IntoReport, Report};
 use std::collections::HashMap;
+use std::time::Instant;
 
 pub fn run_tasks(
     cli_runner: &str,
@@ -15,6 +19,8 @@ pub fn run_tasks(
     verbose: bool,
 ) -> Result<i32, Report<KeeperError>> {
     let mut task_count = 0;
+    // keep running the rest tasks after a failure, and the failures are reported at the end
+    let mut failed_tasks: Vec<String> = vec![];
     let all_tasks = list_all_runner_tasks(true);
     if let Ok(tasks_hashmap) = all_tasks {
         if !cli_runner.is_empty() {
@@ -22,35 +28,57 @@ pub fn run_tasks(
             if let Some(runner_tasks) = tasks_hashmap.get(cli_runner) {
                 let mut runner_task_found = false;
                 for target_task_name in target_task_names {
+                    if process::interrupted_signal().is_some() {
+                        report::record_skipped(target_task_name, "interrupted");
+                        continue;
+                    }
                     runner_tasks.iter().for_each(|task| {
                         if task.name.as_str() == *target_task_name {
                             task_count += 1;
                             runner_task_found = true;
-                            run_runner_task(
+                            if run_runner_task(
                                 cli_runner,
                                 target_task_name,
                                 task_args,
                                 global_args,
                                 verbose,
-                            );
+                            )
+                            .is_err()
+                            {
+                                failed_tasks.push(target_task_name.to_string());
+                            }
                         }
                     });
                     // execute package manager task
                     if !runner_task_found && managers::COMMANDS.contains(target_task_name) {
                         task_count += 1;
-                        run_manager_task(
+                        if run_manager_task(
                             cli_runner,
                             target_task_name,
                             task_args,
                             global_args,
                             verbose,
-                        )?;
+                        )
+                        .is_err()
+                        {
+                            failed_tasks.push(target_task_name.to_string());
+                        }
+                    } else if !runner_task_found {
+                        report::record_skipped(target_task_name, "task not found");
                     }
                 }
+            } else {
+                for target_task_name in target_task_names {
+                    report::record_skipped(target_task_name, "runner not available");
+                }
             }
         } else {
             //unknown runner
             for target_task_name in target_task_names {
+                if process::interrupted_signal().is_some() {
+                    report::record_skipped(target_task_name, "interrupted");
+                    continue;
+                }
                 let mut runner_task_found = false;
                 RUNNERS.iter().for_each(|runner| {
                     if let Some(tasks) = tasks_hashmap.get(*runner) {
@@ -58,13 +86,17 @@ pub fn run_tasks(
                             if task.name.as_str() == *target_task_name {
                                 task_count += 1;
                                 runner_task_found = true;
-                                run_runner_task(
+                                if run_runner_task(
                                     runner,
                                     target_task_name,
                                     task_args,
                                     global_args,
                                     verbose,
-                                );
+                                )
+                                .is_err()
+                                {
+                                    failed_tasks.push(target_task_name.to_string());
+                                }
                             }
                         });
                     }
@@ -72,17 +104,26 @@ pub fn run_tasks(
                 // execute package manager task
                 if !runner_task_found && managers::COMMANDS.contains(target_task_name) {
                     task_count += 1;
-                    run_manager_task(
+                    if run_manager_task(
                         cli_runner,
                         target_task_name,
                         task_args,
                         global_args,
                         verbose,
-                    )?;
+                    )
+                    .is_err()
+                    {
+                        failed_tasks.push(target_task_name.to_string());
+                    }
+                } else if !runner_task_found {
+                    report::record_skipped(target_task_name, "task not found");
                 }
             }
         }
     }
+    if !failed_tasks.is_empty() {
+        return Err(KeeperError::FailedToRunTasks(failed_tasks.join(", ")).into_report());
+    }
     Ok(task_count)
 }
 
@@ -92,11 +133,25 @@ pub fn run_runner_task(
     task_args: &[&str],
     global_args: &[&str],
     verbose: bool,
-) {
-    let command_output =
-        runners::run_task(runner, task_name, task_args, global_args, verbose).unwrap();
-    if std::env::var("TK_TASK_ID").is_ok() {
-        send_notification(&command_output, task_name, task_args);
+) -> Result<(), Report<KeeperError>> {
+    capture::update_settings(|settings| {
+        settings.runner = Some(runner.to_string());
+        settings.task_name = Some(task_name.to_string());
+    });
+    let started = Instant::now();
+    let result = runners::run_task(runner, task_name, task_args, global_args, verbose);
+    report::record_result(runner, task_name, started.elapsed(), &result);
+    match result {
+        Ok(command_output) => {
+            if std::env::var("TK_TASK_ID").is_ok() {
+                send_notification(&command_output, task_name, task_args);
+            }
+            Ok(())
+        }
+        Err(err) => {
+            eprintln!("{}", format!("[tk] failed to run {}: {}", task_name, err).bold().red());
+            Err(err)
+        }
     }
 }
 
@@ -107,6 +162,7 @@ pub fn run_manager_task(
     global_args: &[&str],
     verbose: bool,
 ) -> Result<(), Report<KeeperError>> {
+    capture::update_settings(|settings| settings.task_name = Some(task_name.to_string()));
     managers::run_task(runner, task_name, task_args, global_args, verbose)
 }
 
@@ -167,6 +223,13 @@ pub fn list_all_runner_tasks(
             }
         }
     }
+    // maskfile.md is parsed by tk, and code blocks are executed directly without mask
+    if runners::maskfile::is_available()
+        && let Ok(runner_tasks) = runners::maskfile::list_tasks()
+        && !runner_tasks.is_empty()
+    {
+        all_tasks.insert("mask".to_string(), runner_tasks);
+    }
     if runners::taskshell::is_available() {
         if let Ok(runner_tasks) = runners::taskshell::list_tasks() {
             if !runner_tasks.is_empty() {
@@ -427,6 +490,51 @@ pub fn list_all_runner_tasks(
             }
         }
     }
+    if runners::pdm::is_available() {
+        if runners::pdm::is_command_available()
+            && let Ok(runner_tasks) = runners::pdm::list_tasks()
+            && !runner_tasks.is_empty()
+        {
+            all_tasks.insert("pdm".to_string(), runner_tasks);
+        } else if error_display && !runners::pdm::is_command_available() {
+            println!(
+                "{}",
+                "[tk] pdm(https://pdm-project.org) command not available for pyproject.toml"
+                    .bold()
+                    .red()
+            );
+        }
+    }
+    if runners::hatch::is_available() {
+        if runners::hatch::is_command_available()
+            && let Ok(runner_tasks) = runners::hatch::list_tasks()
+            && !runner_tasks.is_empty()
+        {
+            all_tasks.insert("hatch".to_string(), runner_tasks);
+        } else if error_display && !runners::hatch::is_command_available() {
+            println!(
+                "{}",
+                "[tk] hatch(https://hatch.pypa.io) command not available for pyproject.toml"
+                    .bold()
+                    .red()
+            );
+        }
+    }
+    if runners::pixi::is_available() {
+        if runners::pixi::is_command_available()
+            && let Ok(runner_tasks) = runners::pixi::list_tasks()
+            && !runner_tasks.is_empty()
+        {
+            all_tasks.insert("pixi-tasks".to_string(), runner_tasks);
+        } else if error_display && !runners::pixi::is_command_available() {
+            println!(
+                "{}",
+                "[tk] pixi(https://pixi.sh) command not available for pixi.toml"
+                    .bold()
+                    .red()
+            );
+        }
+    }
     if runners::argcfile::is_available() {
         if runners::argcfile::is_command_available() {
             if let Ok(runner_tasks) = runners::argcfile::list_tasks() {
@@ -461,7 +569,8 @@ pub fn list_all_runner_tasks(
                 }
             }
         } else {
-            if error_display {
+            // console entry points of `[project.scripts]` are optional tasks without uv
+            if error_display && crate::common::pyproject_toml_has_tool("rye") {
                 println!(
                     "{}",
                     "[tk] uv(https://github.com/astral-sh/uv) command not available for pyproject.toml"
@@ -489,6 +598,95 @@ pub fn list_all_runner_tasks(
             }
         }
     }
+    // mise.toml may only declare tools, so mise is required only with tasks
+    if runners::mise::is_available()
+        && let Ok(runner_tasks) = runners::mise::list_tasks()
+        && !runner_tasks.is_empty()
+    {
+        if runners::mise::is_command_available() {
+            all_tasks.insert("mise".to_string(), runner_tasks);
+        } else if error_display {
+            println!(
+                "{}",
+                "[tk] mise(https://mise.jdx.dev) command not available for mise tasks"
+                    .bold()
+                    .red()
+            );
+        }
+    }
+    if runners::turbo::is_available() {
+        if runners::turbo::is_command_available()
+            && let Ok(runner_tasks) = runners::turbo::list_tasks()
+            && !runner_tasks.is_empty()
+        {
+            all_tasks.insert("turbo".to_string(), runner_tasks);
+        } else if error_display && !runners::turbo::is_command_available() {
+            println!(
+                "{}",
+                "[tk] turbo(https://turborepo.com) command not available for turbo.json"
+                    .bold()
+                    .red()
+            );
+        }
+    }
+    if runners::nx::is_available() {
+        if runners::nx::is_command_available()
+            && let Ok(runner_tasks) = runners::nx::list_tasks()
+            && !runner_tasks.is_empty()
+        {
+            all_tasks.insert("nx".to_string(), runner_tasks);
+        } else if error_display && !runners::nx::is_command_available() {
+            println!(
+                "{}",
+                "[tk] nx(https://nx.dev) command not available for nx.json"
+                    .bold()
+                    .red()
+            );
+        }
+    }
+    // compose files are parsed by tk, and docker or podman is required to run tasks only
+    if runners::compose::is_available()
+        && let Ok(runner_tasks) = runners::compose::list_tasks()
+        && !runner_tasks.is_empty()
+    {
+        all_tasks.insert("compose".to_string(), runner_tasks);
+    }
+    // devenv.nix and flake.nix are parsed statically without devenv and nix
+    if runners::nix::is_available()
+        && let Ok(runner_tasks) = runners::nix::list_tasks()
+        && !runner_tasks.is_empty()
+    {
+        all_tasks.insert("nix".to_string(), runner_tasks);
+    }
+    if runners::jetbrains::is_available()
+        && let Ok(runner_tasks) = runners::jetbrains::list_tasks()
+        && !runner_tasks.is_empty()
+    {
+        all_tasks.insert("jetbrains".to_string(), runner_tasks);
+    }
+    // aliases and scripts are parsed by tk, and cargo is required only when the project has them
+    if runners::cargo_scripts::is_available()
+        && let Ok(runner_tasks) = runners::cargo_scripts::list_tasks()
+        && !runner_tasks.is_empty()
+    {
+        if runners::cargo_scripts::is_command_available() {
+            all_tasks.insert("cargo-scripts".to_string(), runner_tasks);
+        } else if error_display {
+            println!(
+                "{}",
+                "[tk] cargo(https://www.rust-lang.org/tools/install) command not available for Cargo.toml"
+                    .bold()
+                    .red()
+            );
+        }
+    }
+    // Earthfile is parsed by tk, and functions of dagger.json are listed by dagger
+    if runners::earthly::is_available()
+        && let Ok(runner_tasks) = runners::earthly::list_tasks()
+        && !runner_tasks.is_empty()
+    {
+        all_tasks.insert("earthly".to_string(), runner_tasks);
+    }
     if runners::usql::is_available() {
         if runners::usql::is_command_available() {
             if let Ok(runner_tasks) = runners::usql::list_tasks() {
@@ -536,6 +734,6 @@ mod tests {
 
     #[test]
     fn test_run_task() {
-        run_runner_task("npm", "start", &[], &[], true);
+        run_runner_task("npm", "start", &[], &[], true).ok();
     }
 }
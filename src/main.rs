@@ -1,20 +1,27 @@
 use crate::app::build_app;
+use crate::common::devshell;
+use crate::common::dotenv;
+use crate::common::logs;
+use crate::common::process;
+use crate::common::report;
 use crate::keeper::{list_all_runner_tasks, run_tasks};
 use crate::models::TaskContext;
 use crate::polyglot::PATH_SEPARATOR;
 use crate::runners::justfile::init_justfile;
+use crate::runners::mise::init_mise_toml;
 use crate::runners::RUNNERS;
 use colored::Colorize;
-use dotenvx_rs::dotenvx;
+use clap::ArgMatches;
 use std::collections::HashSet;
 use std::env;
 use std::fs::Permissions;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 mod app;
 mod command_utils;
 mod common;
+mod doctor;
 mod errors;
 mod keeper;
 mod managers;
@@ -49,7 +56,25 @@ fn main() {
     }
     // check your system for potential problems to run tasks
     if matches.get_flag("doctor") {
-        diagnose();
+        let format = matches
+            .get_one::<String>("format")
+            .map(|format| format.as_str())
+            .unwrap_or("text");
+        diagnose(matches.get_flag("fix"), matches.get_flag("yes"), format);
+        return;
+    }
+    // print the latest log of a task
+    if let Some(task_name) = matches.get_one::<String>("logs") {
+        print_task_log(
+            task_name,
+            matches.get_one::<String>("log-dir"),
+            matches.get_flag("follow"),
+        );
+        return;
+    }
+    // print variables of dotenv files
+    if matches.get_flag("env-show") {
+        show_env(&matches);
         return;
     }
     // migrate tasks
@@ -128,6 +153,8 @@ fn main() {
                 .write_all(include_bytes!("./templates/nurfile"))
                 .unwrap();
             println!("{}", "nurfile created".bold().green());
+        } else if runner_name == "mise" {
+            init_mise_toml();
         } else {
             println!("[tk] Create task file for {} not support now.", runner_name);
         }
@@ -144,20 +171,56 @@ fn main() {
     if matches.contains_id("tasks") {
         // load .env for tasks
         if !no_dotenv {
-            load_env();
+            load_env(&matches);
+        }
+        common::capture::update_settings(|settings| {
+            settings.timestamps = matches.get_flag("timestamps");
+            settings.prefix = matches.get_flag("prefix");
+            settings.log_dir = logs::resolve_log_dir(matches.get_one::<String>("log-dir"));
+            settings.log_retention =
+                logs::resolve_log_retention(matches.get_one::<String>("log-retention"));
+        });
+        if let Some(report_option) = matches.get_one::<String>("report") {
+            match report::ReportTarget::parse(report_option) {
+                Some(target) => report::enable(target),
+                None => {
+                    eprintln!(
+                        "{}",
+                        format!("[tk] invalid report: {}, please use junit=report.xml or tap=report.tap", report_option)
+                            .bold()
+                            .red()
+                    );
+                    std::process::exit(1);
+                }
+            }
         }
         // inject polyglot for tasks
         polyglot::inject_languages();
+        // load direnv or Nix development environment
+        if devshell::is_enabled(matches.get_flag("nix")) {
+            load_dev_shell(verbose);
+        }
         // setup path
         reset_path_env();
         // check to execute command directly
         let tk_args = env::args().skip(1).collect::<Vec<String>>();
-        if tk_args[0] == "--" && tk_args.len() > 1 {
+        let tasks_values = matches
+            .get_many::<String>("tasks")
+            .map(|values| values.cloned().collect::<Vec<String>>())
+            .unwrap_or_default();
+        // double dash before any task name, such as `tk --env staging -- mvn spring-boot:run`
+        let double_dash_index = tk_args
+            .iter()
+            .position(|arg| arg == "--")
+            .filter(|index| tk_args[index + 1..] == tasks_values[..]);
+        if let Some(index) = double_dash_index
+            && tk_args.len() > index + 1
+        {
             // execute command line after double dash
-            let command = &tk_args[1];
+            let command = &tk_args[index + 1];
             let args = tk_args
                 .iter()
-                .skip(2)
+                .skip(index + 2)
                 .map(|arg| arg.as_str())
                 .collect::<Vec<&str>>();
             if let Err(err) = command_utils::run_command(command, &args, false) {
@@ -178,7 +241,16 @@ fn main() {
         let global_args = &task_context.global_options;
         let default_runner = "".to_owned();
         let runner = task_runner.unwrap_or(&default_runner);
-        match run_tasks(runner, &tasks, task_args, global_args, verbose) {
+        let result = run_tasks(runner, &tasks, task_args, global_args, verbose);
+        let report_failures = write_task_report();
+        if let Some(signal) = process::interrupted_signal() {
+            if let Err(err) = &result {
+                eprintln!("{}", err);
+            }
+            // 130 for Ctrl-C, as shells do
+            std::process::exit(128 + signal);
+        }
+        match result {
             Ok(task_count) => {
                 if task_count == 0 {
                     // no tasks executed
@@ -198,6 +270,9 @@ fn main() {
                 std::process::exit(1);
             }
         }
+        if report_failures > 0 {
+            std::process::exit(1);
+        }
         return;
     }
 
@@ -205,6 +280,62 @@ fn main() {
     list_tasks(None);
 }
 
+/// write the report of executed tasks if requested, and return the count of failed tasks, and a failed write counts as a failure
+fn write_task_report() -> usize {
+    match report::write_report() {
+        Ok(Some(summary)) => {
+            println!(
+                "{}",
+                format!(
+                    "[tk] report written to {}: {} tasks, {} failed",
+                    summary.path.display(),
+                    summary.tests,
+                    summary.failures
+                )
+                .bold()
+                .blue()
+            );
+            summary.failures
+        }
+        Ok(None) => 0,
+        Err(err) => {
+            eprintln!("{}", format!("[tk] failed to write report: {}", err).bold().red());
+            1
+        }
+    }
+}
+
+fn load_dev_shell(verbose: bool) {
+    let Some(source) = devshell::detect_source() else {
+        eprintln!(
+            "{}",
+            "[tk] no .envrc with direnv, devenv.nix with devenv, or flake.nix with nix found"
+                .bold()
+                .yellow()
+        );
+        return;
+    };
+    match devshell::apply_env(source) {
+        Ok(count) if verbose => {
+            println!(
+                "{}",
+                format!("[tk] {} variables loaded from {} environment", count, source.name())
+                    .bold()
+                    .blue()
+            );
+        }
+        Ok(_) => {}
+        Err(err) => {
+            eprintln!(
+                "{}",
+                format!("[tk] failed to load {} environment: {}", source.name(), err)
+                    .bold()
+                    .red()
+            );
+        }
+    }
+}
+
 fn reset_path_env() {
     let current_dir = env::current_dir().unwrap();
     let mut new_path = env::var("PATH").unwrap_or_else(|_| "".to_string());
@@ -331,451 +462,55 @@ fn list_tasks(task_runner: Option<&String>) {
     }
 }
 
-fn diagnose() {
-    let mut problems_count = 0;
-    if runners::justfile::is_available() {
-        if !runners::justfile::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} just(https://github.com/casey/just) command not available for justfile",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::packagejson::is_available() {
-        if !runners::packagejson::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} npm(https://nodejs.org) command not available for package.json",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::denojson::is_available() {
-        if !runners::denojson::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} deno(https://deno.land) command not available for deno.json",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::makefile::is_available() {
-        if !runners::makefile::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} make(https://www.gnu.org/software/make) command not available for makefile",
-                "Warning:".bold().yellow()
-            );
-        }
-        if which::which("mmake").is_err() {
-            println!(
-                "{} mmake(https://github.com/tj/mmake) is more powerful to run Makefile",
-                "Suggestion:".bold().yellow()
-            );
-        }
-    }
-    if runners::rakefile::is_available() {
-        if !runners::rakefile::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} rake(https://ruby.github.io/rake/) command not available for rakefile",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::jakefile::is_available() {
-        if !runners::jakefile::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} jake(https://jakejs.com/) command not available for jakefile",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::gulpfile::is_available() {
-        if !runners::gulpfile::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} gulp(https://gulpjs.com/) command not available for gulpfile.js",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::gruntfile::is_available() {
-        if !runners::gruntfile::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} grunt(https://gruntjs.com/) command not available for Gruntfile.js",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::taskfileyml::is_available() {
-        if !runners::taskfileyml::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} task(https://taskfile.dev) command not available for Taskfile.yml",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::usql::is_available() {
-        if !runners::usql::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} usql(https://github.com/xo/usql/) command not available for queries.sql",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::makefiletoml::is_available() {
-        if !runners::makefiletoml::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} cargo-make(https://github.com/sagiegurari/cargo-make) command not available for Makefile.toml",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::bun_shell::is_available() {
-        if !runners::bun_shell::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} bun(https://bun.sh/docs/runtime/shell) command not available for Taskfile.ts",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::taskspy::is_available() {
-        if !runners::taskspy::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} invoke(https://www.pyinvoke.org) command not available for tasks.py, please use `uv tool install --python 3.11 invoke` to install. ",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::composer::is_available() {
-        if !runners::composer::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} composer(https://getcomposer.org/) command not available for composer.json",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::jbang::is_available() {
-        if !runners::jbang::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} jbang(https://www.jbang.dev/) command not available for jbang-catalog.json",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::poetry::is_available() {
-        if !runners::poetry::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} poetry(https://python-poetry.org/) command not available for pyproject.toml, please use `uv tool install --python 3.11 poetry` to install.",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::poe::is_available() {
-        if !runners::poe::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} poe(https://github.com/nat-n/poethepoet) command not available for pyproject.toml, please use `uv tool install --python 3.11 poethepoet` to install.",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::argcfile::is_available() {
-        if !runners::argcfile::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} argc(https://github.com/sigoden/argc) command not available for Argcfile.sh",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::amberfile::is_available() {
-        if !runners::amberfile::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} amber(https://amber-lang.com/) command not available for Amberfile",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if runners::nurfile::is_available() {
-        if !runners::nurfile::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} nur(https://github.com/ddanier/nur) command not available for nurfile",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    // ==========package managers============
-    if managers::maven::is_available() {
-        if !managers::maven::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} maven(https://maven.apache.org/) command not available for pom.xml",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::gradle::is_available() {
-        if !managers::gradle::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} Gradle(https://gradle.org/) command not available",
-                "Warning:".bold().yellow(),
-            );
-        } else {
-            //global plugins for gradle $HOME/.gradle/init.d/plugins.gradle
-            if !dirs::home_dir()
-                .unwrap()
-                .join(".gradle")
-                .join("init.d")
-                .join("plugins.gradle")
-                .exists()
-            {
-                println!(
-                    "{} global {} not available for {} task, please check https://github.com/linux-china/task-keeper#gradle",
-                    "Suggestion:".bold().yellow(),
-                    "plugins.gradle".bold().blue(),
-                    "dependencyUpdates".bold().blue()
-                );
-            }
-        }
-    }
-    if managers::kotlin_toolchain::is_available() {
-        if !managers::kotlin_toolchain::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} Kotlin Toolchain(https://kotlin-toolchain.org/) command not available",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::sbt::is_available() {
-        if !managers::sbt::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} sbt(https://www.scala-sbt.org/) command not available for build.sbt",
-                "Warning:".bold().yellow()
-            );
-        } else {
-            //global plugins for sbt $HOME/.sbt/1.0/plugins/plugins.sbt
-            if !dirs::home_dir()
-                .unwrap()
-                .join(".sbt")
-                .join("1.0")
-                .join("plugins")
-                .join("plugins.sbt")
-                .exists()
-            {
-                println!(
-                    "{} global {} not available for {} task, please check https://github.com/linux-china/task-keeper#sbt",
-                    "Suggestion:".bold().yellow(),
-                    "plugins.sbt".bold().blue(),
-                    "dependencyUpdates".bold().blue()
-                );
-            }
+fn diagnose(fix: bool, yes: bool, format: &str) {
+    // resolve versions of languages as tasks do
+    polyglot::inject_languages();
+    let checks = doctor::diagnose();
+    let failures_count = checks
+        .iter()
+        .filter(|check| check.severity.is_failure())
+        .count();
+    if format == "json" {
+        let count_of = |severity: doctor::Severity| {
+            checks
+                .iter()
+                .filter(|check| check.severity == severity)
+                .count()
+        };
+        let report = serde_json::json!({
+            "success": failures_count == 0,
+            "summary": {
+                "ok": count_of(doctor::Severity::Ok),
+                "suggestion": count_of(doctor::Severity::Suggestion),
+                "warning": count_of(doctor::Severity::Warning),
+                "error": count_of(doctor::Severity::Error),
+            },
+            "checks": checks,
+        });
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        if failures_count > 0 {
+            std::process::exit(1);
         }
+        return;
     }
-    if managers::lein::is_available() {
-        if !managers::lein::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} lein(https://leiningen.org/) command not available for project.clj",
-                "Warning:".bold().yellow()
-            );
+    for check in &checks {
+        let label = match check.severity {
+            doctor::Severity::Ok => continue,
+            doctor::Severity::Suggestion => "Suggestion:".bold().yellow(),
+            doctor::Severity::Warning => "Warning:".bold().yellow(),
+            doctor::Severity::Error => "Error:".bold().red(),
+        };
+        if let Some(hint) = &check.hint {
+            println!("{} {}, {}", label, check.message, hint);
         } else {
-            //global plugins for lein $HOME/.lein/profiles.clj
-            if !dirs::home_dir()
-                .unwrap()
-                .join(".lein")
-                .join("profiles.clj")
-                .exists()
-            {
-                println!(
-                    "{} global {} not available for {} task, please check https://github.com/linux-china/task-keeper#lein",
-                    "Suggestion:".bold().yellow(),
-                    "profiles.clj".bold().blue(),
-                    "outdated".bold().blue()
-                );
-            }
-        }
-    }
-    if managers::npm::is_available() {
-        if !managers::npm::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} npm(https://nodejs.org/) command not available for package.json",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::cargo::is_available() {
-        if !managers::cargo::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} cargo(https://doc.rust-lang.org/cargo/) command not available for Cargo.toml",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::composer::is_available() {
-        if !managers::composer::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} composer(https://getcomposer.org/) command not available for composer.json",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::bundler::is_available() {
-        if !managers::bundler::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} bundle(https://bundler.io/) command not available for Gemfile",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::golang::is_available() {
-        if !managers::golang::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} go(https://go.dev/) command not available for go.mod",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::cmakeconan::is_available() {
-        if !managers::cmakeconan::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} cmake and conan(https://github.com/conan-io/cmake-conan/) command not available for CMakeLists.txt and conanfile.txt",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::meson::is_available() {
-        if !managers::meson::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} meson(https://mesonbuild.com) command not available for meson.build",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::swift::is_available() {
-        if !managers::swift::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} swift(https://www.swift.org/) command not available for Package.swift",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::bazel::is_available() {
-        if !managers::bazel::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} bazel(https://bazel.build/) command not available for WORKSPACE",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::pipenv::is_available() {
-        if !managers::pipenv::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} pipenv(https://pipenv.pypa.io/en/latest/) command not available for Pipfile",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::requirements::is_available() {
-        if !managers::requirements::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} pip(https://pypi.org/project/pip/) command not available for requirements.txt",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::rebar3::is_available() {
-        if !managers::rebar3::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} rebar3(https://rebar3.readme.io/) command not available for rebar.config",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::mix::is_available() {
-        if !managers::mix::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} mix(https://hexdocs.pm/mix/1.13/Mix.html) command not available for mix.exs",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::dart::is_available() {
-        if !managers::dart::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} dart(https://dart.dev/guides/packages) command not available for pubspec.yaml",
-                "Warning:".bold().yellow()
-            );
-        }
-    }
-    if managers::zig::is_available() {
-        if !managers::zig::is_command_available() {
-            problems_count += 1;
-            println!(
-                "{} zig(https://ziglang.org/) command not available for build.zig",
-                "Warning:".bold().yellow()
-            );
+            println!("{} {}", label, check.message);
         }
     }
-    if polyglot::java::is_available() {
-        if polyglot::java::find_sdk_home().is_none() {
-            problems_count += 1;
-            println!(
-                "{} .java-version found, but the JDK({}) not installed!",
-                "Warning:".bold().yellow(),
-                polyglot::java::find_sdk_home().unwrap().display()
-            );
-        }
-    }
-    if polyglot::node::is_available() {
-        if polyglot::node::find_sdk_home().is_none() {
-            problems_count += 1;
-            println!(
-                "{} .node-version found, but the Node.js({}) not installed!",
-                "Warning:".bold().yellow(),
-                polyglot::node::get_default_version().unwrap()
-            );
-        }
-    }
-    if polyglot::sdkman::is_available() {
-        problems_count += polyglot::sdkman::diagnose();
-    }
-    if problems_count > 0 {
+    if failures_count > 0 {
         println!(
             "{} {} problems found!",
             "Warning:".bold().yellow(),
-            problems_count
+            failures_count
         );
     } else {
         println!(
@@ -783,6 +518,50 @@ fn diagnose() {
             "Success:".bold().green()
         );
     }
+    if fix {
+        if !doctor::fix_problems(&checks, yes) {
+            std::process::exit(1);
+        }
+        return;
+    }
+    if checks
+        .iter()
+        .any(|check| check.severity != doctor::Severity::Ok && check.fix.is_some())
+    {
+        println!(
+            "{} run `tk --doctor --fix` to fix problems automatically",
+            "Suggestion:".bold().yellow()
+        );
+    }
+    if failures_count > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn print_task_log(task_name: &str, cli_log_dir: Option<&String>, follow: bool) {
+    let Some(log_dir) = logs::resolve_log_dir(cli_log_dir) else {
+        eprintln!(
+            "{}",
+            "[tk] no log directory, please use --log-dir or TK_LOG_DIR"
+                .bold()
+                .red()
+        );
+        std::process::exit(1);
+    };
+    if let Some(log_file) = logs::find_latest_log(&log_dir, task_name) {
+        if let Err(err) = logs::print_log(&log_file, follow) {
+            eprintln!("[tk] failed to read {}: {}", log_file.display(), err);
+            std::process::exit(1);
+        }
+    } else {
+        eprintln!(
+            "{}",
+            format!("[tk] no log found for {} in {}", task_name, log_dir.display())
+                .bold()
+                .red()
+        );
+        std::process::exit(1);
+    }
 }
 
 fn format_description(description: &str) -> String {
@@ -797,13 +576,51 @@ fn format_description(description: &str) -> String {
     short_desc
 }
 
-fn load_env() {
-    dotenvx::dotenv().ok();
-    if env::current_dir().unwrap().join(".flaskenv").exists() {
-        dotenvx::from_filename(".flaskenv").ok();
+fn resolve_env_files(matches: &ArgMatches) -> Vec<PathBuf> {
+    let profile = dotenv::resolve_profile(matches.get_one::<String>("env"));
+    let cli_env_files: Vec<String> = matches
+        .get_many::<String>("env-file")
+        .map(|files| files.cloned().collect())
+        .unwrap_or_default();
+    dotenv::resolve_env_files(profile.as_deref(), &cli_env_files)
+}
+
+fn load_env(matches: &ArgMatches) {
+    let (entries, errors) = dotenv::load_entries(&resolve_env_files(matches));
+    for error in errors {
+        eprintln!("{}", format!("[tk] failed to load env file {}", error).bold().red());
     }
-    if let Ok(node_env) = env::var("NODE_ENV") {
-        dotenvx::from_filename(format!(".env.{}", node_env)).ok();
+    dotenv::apply_entries(&entries, matches.get_flag("env-override"));
+}
+
+fn show_env(matches: &ArgMatches) {
+    let env_files = resolve_env_files(matches);
+    let (entries, errors) = dotenv::load_entries(&env_files);
+    for error in errors {
+        eprintln!("{}", format!("[tk] failed to load env file {}", error).bold().red());
+    }
+    if entries.is_empty() {
+        println!("{}", "[tk] no variables found in dotenv files".bold().yellow());
+        return;
+    }
+    let current_dir = env::current_dir().unwrap_or_default();
+    let override_existing = matches.get_flag("env-override");
+    for entry in &entries {
+        let mut source = entry
+            .source
+            .strip_prefix(&current_dir)
+            .unwrap_or(&entry.source)
+            .display()
+            .to_string();
+        if !override_existing && dotenv::is_shadowed(entry) {
+            source.push_str(", the environment wins");
+        }
+        println!(
+            "{}={}  {}",
+            entry.name.bold(),
+            dotenv::masked_value(entry),
+            format!("# {}", source).dimmed()
+        );
     }
 }
 
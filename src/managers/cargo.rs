@@ -1,5 +1,6 @@
-use crate::command_utils::{run_command_line, CommandOutput};
+use crate::command_utils::{run_command, run_command_line, CommandOutput};
 use crate::errors::KeeperError;
+use crate::polyglot;
 use error_stack::{IntoReport, Report};
 use std::collections::HashMap;
 use which::which;
@@ -42,6 +43,9 @@ pub fn run_task(
     _global_args: &[&str],
     verbose: bool,
 ) -> Result<CommandOutput, Report<KeeperError>> {
+    if task == "install" {
+        install_toolchain(verbose)?;
+    }
     if let Some(command_line) = get_task_command_map().get(task) {
         run_command_line(command_line, verbose)
     } else {
@@ -51,3 +55,19 @@ pub fn run_task(
         ).into_report())
     }
 }
+
+/// install the toolchain with components and targets declared by rust-toolchain.toml before `cargo fetch`
+fn install_toolchain(verbose: bool) -> Result<(), Report<KeeperError>> {
+    if polyglot::rust::is_available()
+        && which("rustup").is_ok()
+        && let Some(toolchain) = polyglot::rust::get_toolchain()
+    {
+        let args = toolchain.install_args();
+        let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+        let output = run_command("rustup", &args, verbose)?;
+        if !output.status.success() {
+            return Err(KeeperError::FailedToRunTasks(format!("rustup {}", args.join(" "))).into_report());
+        }
+    }
+    Ok(())
+}
@@ -142,7 +142,7 @@ pub fn run_task(
     }
 }
 
-fn get_gradle_command() -> &'static str {
+pub fn get_gradle_command() -> &'static str {
     if cfg!(windows) {
         let wrapper_available = std::env::current_dir()
             .map(|dir| dir.join("gradlew.bat").exists())
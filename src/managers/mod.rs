@@ -1,9 +1,12 @@
 use crate::command_utils::CommandOutput;
+use crate::common::capture;
 use crate::common::notification::send_notification;
+use crate::common::report;
 use crate::errors::KeeperError;
 use colored::Colorize;
 use error_stack::Report;
 use std::collections::HashMap;
+use std::time::Instant;
 
 pub mod bazel;
 pub mod bld;
@@ -21,6 +24,7 @@ pub mod meson;
 pub mod mix;
 pub mod npm;
 pub mod pipenv;
+pub mod pixi;
 pub mod poetry;
 pub mod rebar3;
 pub mod requirements;
@@ -49,7 +53,7 @@ pub const COMMANDS: &'static [&'static str] = &[
 ];
 pub const MANAGERS: &'static [&'static str] = &[
     "maven", "gradle", "kotlin", "sbt", "bld", "npm", "cargo", "cmake", "meson", "composer",
-    "bundle", "cmake", "go", "swift", "bazel", "poetry", "pip", "pipenv", "uv", "lein", "rebar3",
+    "bundle", "cmake", "go", "swift", "bazel", "poetry", "pip", "pipenv", "uv", "pixi", "lein", "rebar3",
     "mix", "dart", "zig", "xmake",
 ];
 
@@ -109,6 +113,9 @@ pub fn get_available_managers() -> Vec<String> {
     if uv::is_available() {
         managers.push("uv".to_string());
     }
+    if pixi::is_available() {
+        managers.push("pixi".to_string());
+    }
     if lein::is_available() {
         managers.push("lein".to_string());
     }
@@ -152,6 +159,7 @@ pub fn get_manager_file_name(runner: &str) -> &'static str {
         "mix" => "mix.exs",
         "pip" => "requirements.txt",
         "uv" => "uv.lock",
+        "pixi" => "pixi.toml",
         "pipenv" => "Pipfile",
         "dart" => "pubspec.yaml",
         "zig" => "build.zig",
@@ -183,6 +191,7 @@ pub fn get_manager_web_url(runner: &str) -> &'static str {
         "pip" => "https://pip.pypa.io/en/stable/reference/requirements-file-format/",
         "pipenv" => "https://pipenv.pypa.io",
         "uv" => "https://github.com/astral-sh/uv",
+        "pixi" => "https://pixi.sh",
         "dart" => "https://dart.dev/guides/packages",
         "zig" => "https://ziglang.org/learn/build-system/",
         "xmake" => "https://xmake.io",
@@ -213,6 +222,7 @@ pub fn get_manager_command_map(runner: &str) -> HashMap<String, String> {
         "pip" => requirements::get_task_command_map(),
         "pipenv" => pipenv::get_task_command_map(),
         "uv" => uv::get_task_command_map(),
+        "pixi" => pixi::get_task_command_map(),
         "dart" => dart::get_task_command_map(),
         "zig" => zig::get_task_command_map(),
         "xmake" => xmake::get_task_command_map(),
@@ -425,6 +435,18 @@ pub fn run_task(
             );
         }
     }
+    if pixi::is_available() {
+        if pixi::is_command_available() {
+            queue.insert("pixi", pixi::run_task);
+        } else {
+            println!(
+                "{}",
+                "[tk] pixi(https://pixi.sh) command not available for pixi.toml"
+                    .bold()
+                    .red()
+            );
+        }
+    }
     if requirements::is_available() {
         if requirements::is_command_available() {
             queue.insert("requirements", requirements::run_task);
@@ -502,6 +524,7 @@ pub fn run_task(
     if queue.is_empty() {
         // no manager found
         println!("{}", "[tk] no available manager detected".bold().red());
+        report::record_skipped(task_name, "no available manager");
     } else if !runner.is_empty() {
         // run task by runner name
         if let Some(task) = queue.get(runner) {
@@ -511,7 +534,14 @@ pub fn run_task(
                     .bold()
                     .blue()
             );
-            task(task_name, task_args, global_args, verbose)?;
+            capture::update_settings(|settings| settings.runner = Some(runner.to_string()));
+            let started = Instant::now();
+            let result = task(task_name, task_args, global_args, verbose);
+            report::record_result(runner, task_name, started.elapsed(), &result);
+            if let Err(err) = result {
+                eprintln!("{}", format!("[tk] failed to run {} from {}: {}", task_name, runner, err).bold().red());
+                return Err(err);
+            }
         } else {
             println!(
                 "{}",
@@ -519,6 +549,7 @@ pub fn run_task(
                     .bold()
                     .red()
             );
+            report::record_skipped(task_name, "manager not available");
         }
     } else {
         // run task by all available managers
@@ -536,6 +567,8 @@ pub fn run_task(
                 }
             }*/
             _ => {
+                // run the task by the rest managers after a failure, and return the first error
+                let mut first_error: Option<Report<KeeperError>> = None;
                 queue.iter().for_each(|(runner_name, task)| {
                     println!(
                         "{}",
@@ -543,11 +576,32 @@ pub fn run_task(
                             .bold()
                             .blue()
                     );
-                    let command_output = task(task_name, task_args, global_args, verbose).unwrap();
-                    if std::env::var("TK_TASK_ID").is_ok() {
-                        send_notification(&command_output, task_name, task_args);
+                    capture::update_settings(|settings| {
+                        settings.runner = Some(runner_name.to_string())
+                    });
+                    let started = Instant::now();
+                    let result = task(task_name, task_args, global_args, verbose);
+                    report::record_result(runner_name, task_name, started.elapsed(), &result);
+                    match result {
+                        Ok(command_output) => {
+                            if std::env::var("TK_TASK_ID").is_ok() {
+                                send_notification(&command_output, task_name, task_args);
+                            }
+                        }
+                        Err(err) => {
+                            eprintln!(
+                                "{}",
+                                format!("[tk] failed to run {} from {}: {}", task_name, runner_name, err)
+                                    .bold()
+                                    .red()
+                            );
+                            first_error.get_or_insert(err);
+                        }
                     }
                 });
+                if let Some(err) = first_error {
+                    return Err(err);
+                }
             }
         }
     }
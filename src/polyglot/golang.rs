@@ -0,0 +1,253 @@
+use crate::polyglot::{toolversions, PATH_SEPARATOR};
+use regex::Regex;
+use std::env;
+use std::path::{Path, PathBuf};
+
+pub fn is_available() -> bool {
+    env::current_dir()
+        .map(|dir| dir.join("go.work").exists() || dir.join("go.mod").exists())
+        .unwrap_or(false)
+        && get_default_version().is_some()
+}
+
+/// Go version wanted by the project
+#[derive(Debug, Clone, PartialEq)]
+pub enum GoRequirement {
+    /// `toolchain go1.22.1` or the version of `.tool-versions`/`mise.toml`: the same major and minor, and not older
+    Toolchain(String),
+    /// `go 1.22`: the minimum version by the semantics of Go 1.21+
+    Minimum(String),
+}
+
+impl GoRequirement {
+    pub fn version(&self) -> &str {
+        match self {
+            GoRequirement::Toolchain(version) | GoRequirement::Minimum(version) => version,
+        }
+    }
+
+    fn matches(&self, installed: &[u32]) -> bool {
+        let wanted = version_parts(self.version());
+        match self {
+            GoRequirement::Toolchain(_) => is_version_matched(&wanted, installed),
+            GoRequirement::Minimum(_) => installed.len() >= 2 && padded(installed) >= padded(&wanted),
+        }
+    }
+}
+
+/// the `toolchain` directive first, then the `go` directive, from go.work or go.mod, and `.tool-versions`/`mise.toml` at last
+pub fn get_requirement() -> Option<GoRequirement> {
+    ["go.work", "go.mod"]
+        .iter()
+        .filter_map(|name| std::fs::read_to_string(name).ok())
+        .find_map(|code| extract_go_version(&code))
+        .or_else(|| toolversions::get_tool_version("golang").map(GoRequirement::Toolchain))
+}
+
+pub fn get_default_version() -> Option<String> {
+    get_requirement().map(|requirement| requirement.version().to_string())
+}
+
+/// `toolchain go1.22.1` or `go 1.22`, and `toolchain default` is ignored
+fn extract_go_version(code: &str) -> Option<GoRequirement> {
+    let toolchain = Regex::new(r"(?m)^\s*toolchain\s+go(\d[\w.+-]*)")
+        .unwrap()
+        .captures(code)
+        .map(|captures| GoRequirement::Toolchain(captures[1].to_string()));
+    toolchain.or_else(|| {
+        Regex::new(r"(?m)^\s*go\s+(\d[\w.]*)")
+            .unwrap()
+            .captures(code)
+            .map(|captures| GoRequirement::Minimum(captures[1].to_string()))
+    })
+}
+
+/// version parts of `1.22.1`, `go1.22.1` or `go1.22rc1`: `[1, 22, 1]`, `[1, 22, 1]` and `[1, 22]`
+fn version_parts(version: &str) -> Vec<u32> {
+    let mut parts = vec![];
+    for part in version.trim_start_matches("go").split('.') {
+        let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+        match digits.parse::<u32>() {
+            Ok(number) => parts.push(number),
+            Err(_) => break,
+        }
+        // pre-release such as `22rc1`
+        if digits.len() != part.len() {
+            break;
+        }
+    }
+    parts
+}
+
+/// major, minor and patch versions, missing parts as 0
+fn padded(parts: &[u32]) -> [u32; 3] {
+    [0, 1, 2].map(|index| parts.get(index).copied().unwrap_or(0))
+}
+
+/// an installed Go matches if major and minor versions are the same, and the patch version is not older
+fn is_version_matched(wanted: &[u32], installed: &[u32]) -> bool {
+    installed.len() >= 2
+        && wanted.len() >= 2
+        && wanted[..2] == installed[..2]
+        && installed.get(2).unwrap_or(&0) >= wanted.get(2).unwrap_or(&0)
+}
+
+/// Go SDKs: `~/sdk/go1.22.1`, `~/go/pkg/mod/golang.org/toolchain@v0.0.1-go1.22.1.linux-amd64`,
+/// goenv versions and asdf/mise installs, the version of the directory name is extracted by the pattern
+fn go_install_dirs() -> Vec<(PathBuf, Regex)> {
+    let mut dirs = vec![];
+    let plain_version = Regex::new(r"^(\d+\.\d+[\w.]*)$").unwrap();
+    if let Some(home) = dirs::home_dir() {
+        dirs.push((home.join("sdk"), Regex::new(r"^go(\d+\.\d+[\w.]*)$").unwrap()));
+        let mod_cache = env::var("GOMODCACHE").map(PathBuf::from).unwrap_or_else(|_| {
+            env::var("GOPATH")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| home.join("go"))
+                .join("pkg")
+                .join("mod")
+        });
+        dirs.push((
+            mod_cache.join("golang.org"),
+            Regex::new(r"^toolchain@v[\d.]+-go(\d+\.\d+[\w.]*?)\.[a-z]+-[a-z0-9]+$").unwrap(),
+        ));
+        let goenv_root = env::var("GOENV_ROOT")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| home.join(".goenv"));
+        dirs.push((goenv_root.join("versions"), plain_version.clone()));
+        for install_dir in toolversions::get_install_dirs("golang") {
+            dirs.push((install_dir, plain_version.clone()));
+        }
+    }
+    dirs
+}
+
+/// the latest matched Go in the directory
+fn find_go_in_dir(install_dir: &Path, pattern: &Regex, requirement: &GoRequirement) -> Option<PathBuf> {
+    std::fs::read_dir(install_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let installed = version_parts(&pattern.captures(&name)?[1]);
+            if requirement.matches(&installed) {
+                Some((installed, entry.path()))
+            } else {
+                None
+            }
+        })
+        .max_by(|(left, _), (right, _)| left.cmp(right))
+        .map(|(_, path)| go_root_of(&path))
+}
+
+/// asdf golang plugin installs go into the `go` sub directory
+fn go_root_of(dir: &Path) -> PathBuf {
+    if dir.join("go").join("bin").exists() {
+        dir.join("go")
+    } else {
+        dir.to_path_buf()
+    }
+}
+
+pub fn find_sdk_home() -> Option<PathBuf> {
+    let requirement = get_requirement()?;
+    go_install_dirs()
+        .iter()
+        .filter(|(dir, _)| dir.exists())
+        .find_map(|(dir, pattern)| find_go_in_dir(dir, pattern, &requirement))
+}
+
+/// version of `go` on PATH, such as `1.22.1`
+pub fn get_installed_version() -> Option<String> {
+    let output = std::process::Command::new("go")
+        .args(["env", "GOVERSION"])
+        .output()
+        .ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && version.starts_with("go") {
+        Some(version.trim_start_matches("go").to_string())
+    } else {
+        None
+    }
+}
+
+/// whether the installed Go meets the requirement
+pub fn is_installed_version_matched(requirement: &GoRequirement, installed: &str) -> bool {
+    requirement.matches(&version_parts(installed))
+}
+
+pub fn init_env() {
+    if let Some(go_root) = find_sdk_home() {
+        reset_go_root(&go_root);
+    }
+}
+
+fn reset_go_root(go_root: &Path) {
+    unsafe {
+        env::set_var("GOROOT", go_root);
+        // the selected Go is used, not switched to another toolchain by go command
+        env::set_var("GOTOOLCHAIN", "local");
+    }
+    if let Ok(path) = env::var("PATH") {
+        let go_bin_path = go_root.join("bin").to_string_lossy().to_string();
+        unsafe {
+            env::set_var("PATH", format!("{}{}{}", go_bin_path, PATH_SEPARATOR, path));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_go_version() {
+        let code = r#"
+module example.com/demo
+
+go 1.21
+
+toolchain go1.22.1
+
+require github.com/google/uuid v1.6.0
+"#;
+        assert_eq!(Some(GoRequirement::Toolchain("1.22.1".to_string())), extract_go_version(code));
+        assert_eq!(
+            Some(GoRequirement::Minimum("1.21.5".to_string())),
+            extract_go_version("module demo\n\ngo 1.21.5\n")
+        );
+        assert_eq!(
+            Some(GoRequirement::Minimum("1.22".to_string())),
+            extract_go_version("go 1.22\ntoolchain default\n")
+        );
+    }
+
+    #[test]
+    fn test_find_go_in_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let install_dir = temp_dir.path();
+        for name in ["go1.22.0", "go1.22.3", "go1.21.5", "toolchain@v0.0.1-go1.22.9.linux-amd64"] {
+            std::fs::create_dir_all(install_dir.join(name)).unwrap();
+        }
+        let sdk_pattern = Regex::new(r"^go(\d+\.\d+[\w.]*)$").unwrap();
+        let toolchain = |version: &str| GoRequirement::Toolchain(version.to_string());
+        let minimum = |version: &str| GoRequirement::Minimum(version.to_string());
+        assert!(find_go_in_dir(install_dir, &sdk_pattern, &toolchain("1.22")).unwrap().ends_with("go1.22.3"));
+        assert!(find_go_in_dir(install_dir, &sdk_pattern, &toolchain("1.22.1")).unwrap().ends_with("go1.22.3"));
+        assert!(find_go_in_dir(install_dir, &sdk_pattern, &toolchain("1.22.4")).is_none());
+        assert!(find_go_in_dir(install_dir, &sdk_pattern, &minimum("1.21")).unwrap().ends_with("go1.22.3"));
+        assert!(find_go_in_dir(install_dir, &sdk_pattern, &minimum("1.22.4")).is_none());
+        let toolchain_pattern = Regex::new(r"^toolchain@v[\d.]+-go(\d+\.\d+[\w.]*?)\.[a-z]+-[a-z0-9]+$").unwrap();
+        assert!(
+            find_go_in_dir(install_dir, &toolchain_pattern, &toolchain("1.22.4"))
+                .unwrap()
+                .ends_with("toolchain@v0.0.1-go1.22.9.linux-amd64")
+        );
+        assert!(is_installed_version_matched(&toolchain("1.22"), "1.22.0"));
+        assert!(!is_installed_version_matched(&toolchain("1.22.1"), "1.21.9"));
+        assert!(!is_installed_version_matched(&toolchain("1.22"), "1.23.0"));
+        assert!(is_installed_version_matched(&minimum("1.22"), "1.23.0"));
+        assert!(is_installed_version_matched(&minimum("1.22.1"), "1.22.1"));
+        assert!(!is_installed_version_matched(&minimum("1.22.1"), "1.22.0"));
+    }
+}
@@ -1,6 +1,7 @@
 use std::env;
-use std::path::PathBuf;
-use crate::polyglot::PATH_SEPARATOR;
+use std::path::{Path, PathBuf};
+use crate::polyglot::{toolversions, PATH_SEPARATOR};
+use regex::Regex;
 
 pub fn is_available() -> bool {
     let current_dir = env::current_dir().unwrap();
@@ -9,6 +10,7 @@ pub fn is_available() -> bool {
         || current_dir.join("pom.xml").exists()
         || current_dir.join("build.gradle.kts").exists()
         || current_dir.join("build.gradle").exists()
+        || toolversions::get_tool_version("java").is_some()
 }
 
 pub fn get_default_version() -> Option<String> {
@@ -17,6 +19,8 @@ pub fn get_default_version() -> Option<String> {
     } else if let Ok(text) = std::fs::read_to_string(".sdkmanrc") {
         let map = java_properties::read(text.as_bytes()).unwrap();
         return map.get("java").map(|version| version.to_string());
+    } else if let Some(version) = toolversions::get_tool_version("java") {
+        return Some(version);
     } else if let Ok(xml) = std::fs::read_to_string("pom.xml") {
         return extract_java_version_from_pom(&xml);
     } else if let Ok(code) = std::fs::read_to_string("build.gradle.kts") {
@@ -27,12 +31,75 @@ pub fn get_default_version() -> Option<String> {
     None
 }
 
+/// Java version of a JDK: `17`, `17.0.9-tem`, `21.0.2-graal` or `1.8`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct JavaVersion {
+    major: u32,
+    minor: Option<u32>,
+    patch: Option<u32>,
+    /// distribution of sdkman style, such as `tem` or `graal`
+    distribution: Option<String>,
+}
+
+impl JavaVersion {
+    /// parse the first version in the text, such as `17.0.9-tem` or `java-17-openjdk-amd64`
+    fn parse(text: &str) -> Option<Self> {
+        let captures = Regex::new(r"(\d+)(?:\.(\d+))?(?:\.(\d+))?(?:[.+_]\d+)*(?:-([A-Za-z][\w.]*))?")
+            .unwrap()
+            .captures(text)?;
+        let number = |index: usize| {
+            captures
+                .get(index)
+                .and_then(|part| part.as_str().parse::<u32>().ok())
+        };
+        let mut major = number(1)?;
+        let mut minor = number(2);
+        let mut patch = number(3);
+        // legacy version: 1.8.0 is 8
+        if major == 1 && minor.is_some() {
+            major = minor.unwrap();
+            minor = patch;
+            patch = None;
+        }
+        Some(JavaVersion {
+            major,
+            minor,
+            patch,
+            distribution: captures.get(4).map(|part| part.as_str().to_lowercase()),
+        })
+    }
+
+    /// the wanted version matches a candidate if all given parts are the same:
+    /// `17` matches `17.0.9-tem`, but `17.0.9-tem` does not match `17.0.9-zulu` or `21.0.2-graal`
+    fn matches(&self, candidate_name: &str) -> bool {
+        let Some(candidate) = JavaVersion::parse(candidate_name) else {
+            return false;
+        };
+        if self.major != candidate.major {
+            return false;
+        }
+        if self.minor.is_some() && self.minor != candidate.minor.or(Some(0)) {
+            return false;
+        }
+        if self.patch.is_some() && self.patch != candidate.patch.or(Some(0)) {
+            return false;
+        }
+        if let Some(distribution) = &self.distribution {
+            return candidate_name.to_lowercase().contains(distribution.as_str());
+        }
+        true
+    }
+}
+
 fn extract_java_version_from_pom(xml: &str) -> Option<String> {
-    if let Some(offset) = xml.find("<java.version>") {
-        let end = xml.find("</java.version").unwrap();
-        let java_version = xml[offset + 14..end].trim();
-        if str::parse::<u32>(java_version).is_ok() {
-            return Some(java_version.to_string());
+    for property in ["java.version", "maven.compiler.release", "maven.compiler.source"] {
+        let pattern = format!(r"<{}>\s*([^<]+?)\s*</{}>", regex::escape(property), regex::escape(property));
+        if let Some(captures) = Regex::new(&pattern).unwrap().captures(xml) {
+            let java_version = &captures[1];
+            // skip placeholders such as ${java.version}
+            if JavaVersion::parse(java_version).is_some() && !java_version.contains('$') {
+                return Some(java_version.to_string());
+            }
         }
     }
     None
@@ -40,46 +107,95 @@ fn extract_java_version_from_pom(xml: &str) -> Option<String> {
 
 
 fn extract_java_version_from_gradle(code: &str) -> Option<String> {
-    if let Some(offset) = code.find("JavaLanguageVersion.of(") {
-        let start = offset + 23;
-        let end = code[start..].find(")").unwrap();
-        let java_version = code[start..start + end].trim();
-        if str::parse::<u32>(java_version).is_ok() {
-            return Some(java_version.to_string());
-        }
+    let patterns = [
+        r"JavaLanguageVersion\.of\(\s*(\d+)\s*\)",
+        r"jvmToolchain\(\s*(\d+)\s*\)",
+        r"jvmToolchain\s+(\d+)",
+    ];
+    patterns.iter().find_map(|pattern| {
+        Regex::new(pattern)
+            .unwrap()
+            .captures(code)
+            .map(|captures| captures[1].to_string())
+    })
+}
+
+/// installation directories of JDKs, in the order of preference
+fn jdk_install_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join(".sdkman").join("candidates").join("java"));
+        dirs.extend(toolversions::get_install_dirs("java"));
+        dirs.push(home.join(".gradle").join("jdks"));
     }
-    None
+    dirs.push(PathBuf::from("/usr/lib/jvm"));
+    dirs.push(PathBuf::from("/Library/Java/JavaVirtualMachines"));
+    dirs
 }
 
-pub fn find_sdk_home() -> Option<PathBuf> {
-    if let Some(text) = get_default_version() {
-        let java_version = text.trim();
-        if let Some(java_home) = dirs::home_dir()
-            .map(|dir| {
-                dir.join(".jbang").join("cache").join("jdks").join(java_version)
-            })
-            .filter(|dir| dir.exists()) {
-            return Some(java_home);
-        }
-        if let Some(java_candidates_home) = dirs::home_dir()
-            .map(|dir| {
-                dir.join(".sdkman").join("candidates").join("java")
-            })
-            .filter(|dir| dir.exists()) {
-            if let Ok(paths) = std::fs::read_dir(java_candidates_home) {
-                for path in paths {
-                    if let Ok(path) = path {
-                        if let Some(name) = path.file_name().to_str() {
-                            if name.starts_with(java_version) {
-                                return Some(path.path());
-                            }
-                        }
-                    }
-                }
+/// JDK home of an installation directory, `Contents/Home` for macOS bundles
+fn jdk_home_of(dir: &Path) -> PathBuf {
+    let bundle_home = dir.join("Contents").join("Home");
+    if bundle_home.exists() {
+        bundle_home
+    } else {
+        dir.to_path_buf()
+    }
+}
+
+/// find the JDK in the directory: the exact name first, otherwise the latest matched version
+fn find_jdk_in_dir(install_dir: &Path, wanted: &str) -> Option<PathBuf> {
+    let exact_home = install_dir.join(wanted);
+    if exact_home.is_dir() {
+        return Some(jdk_home_of(&exact_home));
+    }
+    let wanted_version = JavaVersion::parse(wanted)?;
+    std::fs::read_dir(install_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // `current` of sdkman and `default` of mise are links to other versions
+            if name == "current" || !wanted_version.matches(&name) {
+                return None;
             }
-        }
+            JavaVersion::parse(&name).map(|version| (version, entry.path()))
+        })
+        .max_by(|(left, _), (right, _)| left.cmp(right))
+        .map(|(_, path)| jdk_home_of(&path))
+}
+
+/// `JAVA_HOME_17_X64` style variables, provided by GitHub Actions and Azure Pipelines
+fn find_jdk_from_env(wanted: &str) -> Option<PathBuf> {
+    let wanted_version = JavaVersion::parse(wanted)?;
+    let pattern = Regex::new(r"^JAVA_HOME_(\d+)_(X64|ARM64|AARCH64)$").unwrap();
+    env::vars()
+        .filter(|(name, _)| {
+            pattern
+                .captures(name)
+                .map(|captures| wanted_version.matches(&captures[1]))
+                .unwrap_or(false)
+        })
+        .map(|(_, value)| PathBuf::from(value))
+        .find(|path| path.exists())
+}
+
+pub fn find_sdk_home() -> Option<PathBuf> {
+    let text = get_default_version()?;
+    let java_version = text.trim();
+    if let Some(java_home) = dirs::home_dir()
+        .map(|dir| {
+            dir.join(".jbang").join("cache").join("jdks").join(java_version)
+        })
+        .filter(|dir| dir.exists()) {
+        return Some(java_home);
     }
-    None
+    jdk_install_dirs()
+        .iter()
+        .filter(|dir| dir.exists())
+        .find_map(|dir| find_jdk_in_dir(dir, java_version))
+        .or_else(|| find_jdk_from_env(java_version))
 }
 
 pub fn init_env() {
@@ -127,6 +243,15 @@ mod tests {
             </project>
             "#;
         assert_eq!(Some("11".to_string()), extract_java_version_from_pom(xml));
+        let xml = r#"
+            <properties>
+                <java.version>${maven.compiler.release}</java.version>
+                <maven.compiler.release>21</maven.compiler.release>
+            </properties>
+            "#;
+        assert_eq!(Some("21".to_string()), extract_java_version_from_pom(xml));
+        let xml = "<maven.compiler.source>1.8</maven.compiler.source>";
+        assert_eq!(Some("1.8".to_string()), extract_java_version_from_pom(xml));
     }
 
     #[test]
@@ -142,5 +267,39 @@ mod tests {
             }
         "#;
         assert_eq!(Some("11".to_string()), extract_java_version_from_gradle(code));
+        let code = r#"
+            kotlin {
+                jvmToolchain(17)
+            }
+        "#;
+        assert_eq!(Some("17".to_string()), extract_java_version_from_gradle(code));
+    }
+
+    #[test]
+    fn test_java_version_matches() {
+        let version = JavaVersion::parse("17").unwrap();
+        assert!(version.matches("17.0.9-tem"));
+        assert!(version.matches("java-17-openjdk-amd64"));
+        assert!(version.matches("temurin-17.0.9+9"));
+        assert!(!version.matches("21.0.2-graal"));
+        assert!(!version.matches("1.7.0"));
+        let version = JavaVersion::parse("17.0.9-tem").unwrap();
+        assert!(version.matches("17.0.9-tem"));
+        assert!(!version.matches("17.0.9-zulu"));
+        assert!(!version.matches("17.0.10-tem"));
+        assert!(JavaVersion::parse("1.8").unwrap().matches("java-8-openjdk-amd64"));
+    }
+
+    #[test]
+    fn test_find_jdk_in_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let install_dir = temp_dir.path();
+        for name in ["17.0.2-tem", "17.0.9-tem", "21.0.2-graal", "current"] {
+            std::fs::create_dir_all(install_dir.join(name)).unwrap();
+        }
+        assert!(find_jdk_in_dir(install_dir, "17").unwrap().ends_with("17.0.9-tem"));
+        assert!(find_jdk_in_dir(install_dir, "17.0.2-tem").unwrap().ends_with("17.0.2-tem"));
+        assert!(find_jdk_in_dir(install_dir, "21").unwrap().ends_with("21.0.2-graal"));
+        assert!(find_jdk_in_dir(install_dir, "11").is_none());
     }
 }
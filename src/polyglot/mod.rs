@@ -1,8 +1,11 @@
+pub mod golang;
 pub mod java;
 pub mod node;
 pub mod python;
-pub mod sdkman;
 pub mod ruby;
+pub mod rust;
+pub mod sdkman;
+pub mod toolversions;
 
 cfg_if::cfg_if! {
     if #[cfg(target_os = "windows")] {
@@ -22,7 +25,16 @@ pub fn inject_languages() {
     if python::is_available() {
         python::init_env();
     }
+    if ruby::is_available() {
+        ruby::init_env();
+    }
+    if golang::is_available() {
+        golang::init_env();
+    }
     if sdkman::is_available() {
         sdkman::init_env();
     }
+    if toolversions::is_available() {
+        toolversions::init_env();
+    }
 }
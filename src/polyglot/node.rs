@@ -1,72 +1,272 @@
-use crate::polyglot::PATH_SEPARATOR;
+use crate::common::{parse_package_json, PackageJson};
+use crate::common::versions::{npm_range_to_reqs, parse_version};
+use crate::polyglot::{toolversions, PATH_SEPARATOR};
+use semver::{Version, VersionReq};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// codenames of Node.js LTS releases, used by `lts/iron` in `.nvmrc`
+const LTS_CODENAMES: [(&str, u64); 11] = [
+    ("argon", 4),
+    ("boron", 6),
+    ("carbon", 8),
+    ("dubnium", 10),
+    ("erbium", 12),
+    ("fermium", 14),
+    ("gallium", 16),
+    ("hydrogen", 18),
+    ("iron", 20),
+    ("jod", 22),
+    ("krypton", 24),
+];
 
 pub fn is_available() -> bool {
     env::current_dir()
-        .map(|dir| dir.join(".node-version").exists())
+        .map(|dir| dir.join(".node-version").exists() || dir.join(".nvmrc").exists())
         .unwrap_or(false)
+        || get_default_version().is_ok()
 }
 
+/// Node.js version of the project, from `.node-version`, `.nvmrc`, `.tool-versions`/`mise.toml`,
+/// `volta.node` or `engines.node` in package.json, such as `20.11.0`, `lts/*` or `>=18 <21`
 pub fn get_default_version() -> std::io::Result<String> {
-    std::fs::read_to_string(".node-version").map(|text| text.trim().to_string())
+    std::fs::read_to_string(".node-version")
+        .or_else(|_| std::fs::read_to_string(".nvmrc"))
+        .map(|text| text.trim().to_string())
+        .or_else(|error| {
+            toolversions::get_tool_version("nodejs")
+                .or_else(|| package_json_version(&parse_package_json().ok()?))
+                .ok_or(error)
+        })
 }
 
-pub fn find_sdk_home() -> Option<PathBuf> {
-    if let Ok(text) = get_default_version() {
-        let node_version = text.trim();
-        // find version from nvm
-        let node_candidates_path =
-            dirs::home_dir().map(|dir| dir.join(".nvm").join("versions").join("node"));
-        let mut node_home_path = find_node_home(node_version, &node_candidates_path);
-        if node_home_path.is_none() {
-            let node_candidates_path = dirs::home_dir()
-                .map(|dir| dir.join(".volta").join("tools").join("image").join("node"));
-            node_home_path = find_node_home(node_version, &node_candidates_path);
+/// `volta.node` pin first, then the `engines.node` range, and the highest installed version in the range is used
+fn package_json_version(package_json: &PackageJson) -> Option<String> {
+    package_json
+        .volta
+        .as_ref()
+        .and_then(|volta| volta.get("node"))
+        .or_else(|| package_json.engines.as_ref()?.get("node"))
+        .cloned()
+}
+
+/// Node.js version requirement: a version prefix, an LTS alias or a semver range
+#[derive(Debug)]
+enum NodeRequirement {
+    /// `20` matches `20.11.0`, and `20.1` does not match `20.11.0`
+    Prefix(Vec<u64>),
+    /// `lts/*` for any LTS release(even major version), `lts/iron` for `20`
+    Lts(Option<u64>),
+    /// `node` or `stable` of nvm, the latest installed version
+    Latest,
+    Range(Vec<VersionReq>),
+}
+
+impl NodeRequirement {
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches('v');
+        if text == "node" || text == "stable" {
+            return Some(NodeRequirement::Latest);
+        }
+        if let Some(codename) = text.strip_prefix("lts/") {
+            if codename == "*" {
+                return Some(NodeRequirement::Lts(None));
+            }
+            return LTS_CODENAMES
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(codename))
+                .map(|(_, major)| NodeRequirement::Lts(Some(*major)));
+        }
+        if !text.is_empty() && text.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            let parts = text
+                .split('.')
+                .filter(|part| !part.is_empty())
+                .map(|part| part.parse::<u64>().ok())
+                .collect::<Option<Vec<u64>>>()?;
+            return Some(NodeRequirement::Prefix(parts));
         }
-        return node_home_path;
+        npm_range_to_reqs(text).map(NodeRequirement::Range)
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        match self {
+            NodeRequirement::Prefix(parts) => {
+                let installed = [version.major, version.minor, version.patch];
+                parts.iter().zip(installed.iter()).all(|(left, right)| left == right)
+            }
+            NodeRequirement::Lts(Some(major)) => version.major == *major,
+            NodeRequirement::Lts(None) => version.major >= 4 && version.major.is_multiple_of(2),
+            NodeRequirement::Latest => true,
+            NodeRequirement::Range(reqs) => reqs.iter().any(|req| req.matches(version)),
+        }
+    }
+}
+
+/// installation directories of Node.js: nvm, Volta, fnm, asdf and mise
+fn node_install_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Some(home) = dirs::home_dir() {
+        let nvm_dir = env::var("NVM_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| home.join(".nvm"));
+        dirs.push(nvm_dir.join("versions").join("node"));
+        dirs.push(home.join(".volta").join("tools").join("image").join("node"));
+        let fnm_dir = env::var("FNM_DIR").map(PathBuf::from).unwrap_or_else(|_| {
+            dirs::data_dir()
+                .map(|dir| dir.join("fnm"))
+                .unwrap_or_else(|| home.join(".local").join("share").join("fnm"))
+        });
+        dirs.push(fnm_dir.join("node-versions"));
+        dirs.extend(toolversions::get_install_dirs("nodejs"));
     }
-    None
+    dirs
+}
+
+/// Node.js home of an installation directory, fnm installs Node.js into `installation`
+fn node_home_of(dir: &Path) -> PathBuf {
+    let installation = dir.join("installation");
+    if installation.exists() {
+        installation
+    } else {
+        dir.to_path_buf()
+    }
+}
+
+/// the highest installed version that satisfies the requirement, the earlier directory wins for the same version
+fn find_node_home(requirement: &NodeRequirement, install_dirs: &[PathBuf]) -> Option<PathBuf> {
+    let mut candidates: Vec<(Version, PathBuf)> = install_dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|paths| paths.flatten())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.trim_start_matches('v').starts_with(|c: char| c.is_ascii_digit()) {
+                return None;
+            }
+            let version = parse_version(&name)?;
+            if requirement.matches(&version) {
+                Some((version, node_home_of(&entry.path())))
+            } else {
+                None
+            }
+        })
+        .collect();
+    // stable sort keeps the order of directories for the same version
+    candidates.sort_by(|(left, _), (right, _)| right.cmp(left));
+    candidates.into_iter().next().map(|(_, path)| path)
+}
+
+pub fn find_sdk_home() -> Option<PathBuf> {
+    let node_version = get_default_version().ok()?;
+    let requirement = NodeRequirement::parse(&node_version)?;
+    find_node_home(&requirement, &node_install_dirs())
 }
 
 pub fn init_env() {
     if let Some(node_home) = find_sdk_home() {
         reset_node_home(&node_home);
     }
+    if let Some(package_manager_bin) = find_package_manager_bin() {
+        prepend_path(&package_manager_bin);
+    }
 }
 
-fn find_node_home(node_version: &str, node_candidates_path: &Option<PathBuf>) -> Option<PathBuf> {
-    if let Some(node_candidates_home) = node_candidates_path {
-        if let Ok(paths) = std::fs::read_dir(node_candidates_home) {
-            for path in paths {
-                if let Ok(node_home) = path {
-                    if let Some(file_name) = node_home.file_name().to_str() {
-                        let mut real_node_version = file_name;
-                        if file_name.starts_with('v') {
-                            real_node_version = &file_name[1..];
-                        }
-                        if real_node_version.starts_with(node_version) {
-                            return Some(node_home.path());
-                        }
-                    }
-                }
-            }
+/// `pnpm@8.15.0+sha256.abc` as `("pnpm", "8.15.0")`
+fn parse_package_manager(text: &str) -> Option<(String, String)> {
+    let (name, version) = text.split_once('@')?;
+    let version = version.split('+').next().unwrap_or(version);
+    Some((name.to_string(), version.to_string()))
+}
+
+/// corepack cache: `$COREPACK_HOME` or `~/.cache/node/corepack`, with `v1` layout for newer corepack
+fn corepack_cache_dirs() -> Vec<PathBuf> {
+    let corepack_home = env::var("COREPACK_HOME").map(PathBuf::from).ok().or_else(|| {
+        if cfg!(target_os = "windows") {
+            dirs::data_local_dir().map(|dir| dir.join("node").join("corepack"))
+        } else {
+            env::var("XDG_CACHE_HOME")
+                .map(PathBuf::from)
+                .ok()
+                .or_else(|| dirs::home_dir().map(|dir| dir.join(".cache")))
+                .map(|dir| dir.join("node").join("corepack"))
+        }
+    });
+    corepack_home
+        .map(|home| vec![home.join("v1"), home])
+        .unwrap_or_default()
+}
+
+/// entry script of the package manager in corepack cache, from `bin` of its package.json, or `yarn.js` of Yarn Berry
+fn find_corepack_entry(package_dir: &Path, name: &str) -> Option<PathBuf> {
+    if let Ok(text) = std::fs::read_to_string(package_dir.join("package.json"))
+        && let Ok(json) = serde_json::from_str::<serde_json::Value>(&text)
+    {
+        let bin = match &json["bin"] {
+            serde_json::Value::String(bin) => Some(bin.clone()),
+            serde_json::Value::Object(bins) => bins.get(name).and_then(|bin| bin.as_str()).map(String::from),
+            _ => None,
+        };
+        if let Some(bin) = bin {
+            return Some(package_dir.join(bin)).filter(|path| path.exists());
+        }
+    }
+    Some(package_dir.join(format!("{}.js", name))).filter(|path| path.exists())
+}
+
+/// bin directory of the package manager version from `packageManager` in package.json:
+/// bun from asdf/mise installs, pnpm and yarn from corepack cache by generated shims
+fn find_package_manager_bin() -> Option<PathBuf> {
+    let package_manager = parse_package_json().ok()?.package_manager?;
+    let (name, version) = parse_package_manager(&package_manager)?;
+    if name == "bun" {
+        return toolversions::find_install_home("bun", &version).map(|home| home.join("bin"));
+    }
+    let entry = corepack_cache_dirs()
+        .iter()
+        .map(|dir| dir.join(&name).join(&version))
+        .find(|dir| dir.exists())
+        .and_then(|package_dir| find_corepack_entry(&package_dir, &name))?;
+    create_shim(&name, &version, &entry)
+}
+
+/// shim to run the entry script of corepack cache by node, in `task-keeper/shims/<name>-<version>` of the cache directory
+fn create_shim(name: &str, version: &str, entry: &Path) -> Option<PathBuf> {
+    let shim_dir = dirs::cache_dir()?
+        .join("task-keeper")
+        .join("shims")
+        .join(format!("{}-{}", name, version));
+    std::fs::create_dir_all(&shim_dir).ok()?;
+    if cfg!(target_os = "windows") {
+        let script = format!("@node \"{}\" %*\r\n", entry.display());
+        std::fs::write(shim_dir.join(format!("{}.cmd", name)), script).ok()?;
+    } else {
+        let shim = shim_dir.join(name);
+        let script = format!("#!/bin/sh\nexec node \"{}\" \"$@\"\n", entry.display());
+        std::fs::write(&shim, script).ok()?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&shim, std::fs::Permissions::from_mode(0o755)).ok()?;
         }
     }
-    None
+    Some(shim_dir)
 }
 
-fn reset_node_home(node_home_path: &PathBuf) {
+fn reset_node_home(node_home_path: &Path) {
     let node_home = node_home_path.to_string_lossy().to_string();
     unsafe {
         env::set_var("NODE_HOME", &node_home);
     }
+    prepend_path(&node_home_path.join("bin"));
+}
+
+fn prepend_path(bin_path: &Path) {
     if let Ok(path) = env::var("PATH") {
-        let node_bin_path = node_home_path.join("bin").to_string_lossy().to_string();
         unsafe {
             env::set_var(
                 "PATH",
-                format!("{}{}{}", node_bin_path, PATH_SEPARATOR, path),
+                format!("{}{}{}", bin_path.to_string_lossy(), PATH_SEPARATOR, path),
             );
         }
     }
@@ -82,4 +282,66 @@ mod tests {
         println!("NODE_HOME: {}", env::var("NODE_HOME").unwrap());
         println!("PATH: {}", env::var("PATH").unwrap());
     }
+
+    #[test]
+    fn test_node_requirement() {
+        let version = Version::parse("20.11.0").unwrap();
+        assert!(NodeRequirement::parse("20").unwrap().matches(&version));
+        assert!(NodeRequirement::parse("v20.11").unwrap().matches(&version));
+        assert!(!NodeRequirement::parse("20.1").unwrap().matches(&version));
+        assert!(!NodeRequirement::parse("2").unwrap().matches(&version));
+        assert!(NodeRequirement::parse("lts/*").unwrap().matches(&version));
+        assert!(NodeRequirement::parse("lts/iron").unwrap().matches(&version));
+        assert!(!NodeRequirement::parse("lts/hydrogen").unwrap().matches(&version));
+        assert!(!NodeRequirement::parse("lts/*").unwrap().matches(&Version::parse("21.6.0").unwrap()));
+        assert!(NodeRequirement::parse(">=18 <21").unwrap().matches(&version));
+        assert!(!NodeRequirement::parse("^18 || ^22").unwrap().matches(&version));
+    }
+
+    #[test]
+    fn test_find_node_home() {
+        let nvm_temp_dir = tempfile::tempdir().unwrap();
+        let nvm_dir = nvm_temp_dir.path();
+        let fnm_temp_dir = tempfile::tempdir().unwrap();
+        let fnm_dir = fnm_temp_dir.path();
+        for name in ["v18.19.1", "v20.9.0", "v21.6.0"] {
+            std::fs::create_dir_all(nvm_dir.join(name)).unwrap();
+        }
+        std::fs::create_dir_all(fnm_dir.join("v20.11.0").join("installation")).unwrap();
+        let install_dirs = vec![nvm_dir.to_path_buf(), fnm_dir.to_path_buf()];
+        let find = |text: &str| find_node_home(&NodeRequirement::parse(text).unwrap(), &install_dirs);
+        assert!(find("lts/*").unwrap().ends_with("v20.11.0/installation"));
+        assert!(find("18").unwrap().ends_with("v18.19.1"));
+        assert!(find(">=18 <20").unwrap().ends_with("v18.19.1"));
+        assert!(find("node").unwrap().ends_with("v21.6.0"));
+        assert!(find("16").is_none());
+    }
+
+    #[test]
+    fn test_find_node_home_by_engines() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let nvm_dir = temp_dir.path();
+        for name in ["v18.19.1", "v20.9.0", "v20.11.0", "v22.3.0"] {
+            std::fs::create_dir_all(nvm_dir.join(name)).unwrap();
+        }
+        let package_json: PackageJson = serde_json::from_str(r#"{"engines": {"node": ">=18 <22"}}"#).unwrap();
+        let node_version = package_json_version(&package_json).unwrap();
+        let requirement = NodeRequirement::parse(&node_version).unwrap();
+        assert!(matches!(requirement, NodeRequirement::Range(_)));
+        let node_home = find_node_home(&requirement, &[nvm_dir.to_path_buf()]).unwrap();
+        assert!(node_home.ends_with("v20.11.0"));
+        // volta.node wins over engines.node
+        let package_json: PackageJson =
+            serde_json::from_str(r#"{"volta": {"node": "18.19.1"}, "engines": {"node": ">=18"}}"#).unwrap();
+        assert_eq!(Some("18.19.1".to_string()), package_json_version(&package_json));
+    }
+
+    #[test]
+    fn test_parse_package_manager() {
+        assert_eq!(
+            Some(("pnpm".to_string(), "8.15.0".to_string())),
+            parse_package_manager("pnpm@8.15.0+sha256.abc")
+        );
+        assert_eq!(None, parse_package_manager("yarn"));
+    }
 }
@@ -1,4 +1,11 @@
-use crate::polyglot::PATH_SEPARATOR;
+use crate::common::pyproject::PyProjectToml;
+use crate::common::versions::{parse_version, pep440_to_req};
+use crate::polyglot::{toolversions, PATH_SEPARATOR};
+use base64::engine::general_purpose::URL_SAFE;
+use base64::Engine;
+use regex::Regex;
+use semver::{Version, VersionReq};
+use sha2::{Digest, Sha256};
 use std::env;
 use std::path::Path;
 use std::path::PathBuf;
@@ -7,25 +14,38 @@ pub fn is_available() -> bool {
     env::current_dir()
         .map(|dir| dir.join(".python-version").exists())
         .unwrap_or(false)
+        || toolversions::get_tool_version("python").is_some()
+        || get_requires_python().is_some()
+        || find_virtual_env().is_some()
 }
 
 pub fn get_default_version() -> std::io::Result<String> {
-    std::fs::read_to_string(".python-version").map(|text| text.trim().to_string())
+    std::fs::read_to_string(".python-version")
+        .map(|text| text.trim().to_string())
+        .or_else(|error| toolversions::get_tool_version("python").ok_or(error))
+}
+
+/// `requires-python` of `[project]` in pyproject.toml
+pub fn get_requires_python() -> Option<String> {
+    PyProjectToml::get_default_project()
+        .ok()?
+        .project?
+        .requires_python
 }
 
 pub fn find_sdk_home() -> Option<PathBuf> {
+    #[cfg(windows)]
+    let home_dir = dirs_sys::known_folder_profile().unwrap();
+    #[cfg(not(windows))]
+    let home_dir = dirs_sys::home_dir().unwrap();
+    let python_versions_dir = home_dir
+        .join(".local")
+        .join("share")
+        .join("uv")
+        .join("python");
     if let Ok(text) = get_default_version() {
-        #[cfg(windows)]
-        let home_dir = dirs_sys::known_folder_profile().unwrap();
-        #[cfg(not(windows))]
-        let home_dir = dirs_sys::home_dir().unwrap();
         let python_version = text.trim();
         // find python from uv
-        let python_versions_dir = home_dir
-            .join(".local")
-            .join("share")
-            .join("uv")
-            .join("python");
         if python_versions_dir.exists() {
             let prefix = format!("cpython-{}", python_version);
             if let Some(path) = find_sub_directory(&python_versions_dir, &prefix) {
@@ -40,22 +60,197 @@ pub fn find_sdk_home() -> Option<PathBuf> {
         if python_home.exists() {
             return Some(python_home);
         }
+        // find python from asdf or mise
+        return toolversions::find_install_home("python", python_version);
+    }
+    // the highest installed python that satisfies `requires-python`
+    let version_req = pep440_to_req(&get_requires_python()?)?;
+    let mut install_dirs = vec![python_versions_dir, home_dir.join(".pyenv").join("versions")];
+    install_dirs.extend(toolversions::get_install_dirs("python"));
+    find_python_by_req(&version_req, &install_dirs)
+}
+
+/// installed pythons: `cpython-3.12.3-linux-x86_64-gnu` of uv, `3.12.3` of pyenv, asdf and mise
+fn find_python_by_req(version_req: &VersionReq, install_dirs: &[PathBuf]) -> Option<PathBuf> {
+    let pattern = Regex::new(r"^(?:cpython-)?(\d+\.\d+\.\d+)(?:-|$)").unwrap();
+    let mut candidates: Vec<(Version, PathBuf)> = install_dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|paths| paths.flatten())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let version = parse_version(&pattern.captures(&name)?[1])?;
+            if version_req.matches(&version) {
+                Some((version, entry.path()))
+            } else {
+                None
+            }
+        })
+        .collect();
+    candidates.sort_by(|(left, _), (right, _)| right.cmp(left));
+    candidates.into_iter().next().map(|(_, path)| path)
+}
+
+/// virtual environment of the project: `.venv` or `venv` in the project, `.pdm-python` of PDM,
+/// or the environments of Poetry, Hatch and Pipenv outside the project
+pub fn find_virtual_env() -> Option<PathBuf> {
+    let current_dir = env::current_dir().ok()?;
+    for name in [".venv", "venv"] {
+        let venv = current_dir.join(name);
+        if venv.join("pyvenv.cfg").exists() || venv_bin_dir(&venv).exists() {
+            return Some(venv);
+        }
+    }
+    // `.pdm-python` is the python interpreter used by PDM
+    if let Ok(text) = std::fs::read_to_string(current_dir.join(".pdm-python"))
+        && let Some(venv) = Path::new(text.trim()).parent().and_then(|bin| bin.parent())
+        && venv.join("pyvenv.cfg").exists()
+    {
+        return Some(venv.to_path_buf());
+    }
+    let pyproject = PyProjectToml::get_default_project().ok();
+    let project_name = pyproject.as_ref().and_then(|pyproject| {
+        pyproject
+            .project
+            .as_ref()
+            .and_then(|project| project.name.clone())
+            .or_else(|| pyproject.tool.as_ref()?.poetry.as_ref()?.name.clone())
+    });
+    if let Some(project_name) = &project_name {
+        let is_poetry = pyproject
+            .as_ref()
+            .map(|pyproject| pyproject.poetry_available())
+            .unwrap_or(false);
+        if is_poetry && let Some(venv) = find_poetry_env(project_name, &current_dir) {
+            return Some(venv);
+        }
+        let is_hatch = current_dir.join("hatch.toml").exists()
+            || std::fs::read_to_string(current_dir.join("pyproject.toml"))
+                .map(|text| text.contains("[tool.hatch"))
+                .unwrap_or(false);
+        if is_hatch && let Some(venv) = find_hatch_env(project_name, &current_dir) {
+            return Some(venv);
+        }
+    }
+    if current_dir.join("Pipfile").exists() {
+        return find_pipenv_env(&current_dir);
     }
     None
 }
 
+/// first 8 characters of urlsafe base64 of sha256 digest, used by Poetry, Pipenv and Hatch to name environments
+fn path_hash(text: &str, digest_len: usize) -> String {
+    let digest = Sha256::digest(text.as_bytes());
+    URL_SAFE.encode(&digest[..digest_len])[..8].to_string()
+}
+
+/// Poetry: `<cache>/pypoetry/virtualenvs/<name>-<hash>-py3.12`
+fn find_poetry_env(project_name: &str, project_dir: &Path) -> Option<PathBuf> {
+    let virtualenvs_dir = env::var("POETRY_VIRTUALENVS_PATH")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| dirs::cache_dir().map(|dir| dir.join("pypoetry").join("virtualenvs")))?;
+    let sanitized_name: String = Regex::new(r#"[ $`!*@"\\\r\n\t]"#)
+        .unwrap()
+        .replace_all(&project_name.to_lowercase(), "_")
+        .chars()
+        .take(42)
+        .collect();
+    let project_dir = std::fs::canonicalize(project_dir).unwrap_or(project_dir.to_path_buf());
+    let prefix = format!(
+        "{}-{}-py",
+        sanitized_name,
+        path_hash(&project_dir.to_string_lossy(), 32)
+    );
+    find_sub_directory(&virtualenvs_dir, &prefix)
+}
+
+/// Hatch: `<data>/hatch/env/virtual/<name>/<hash>/<name>`
+fn find_hatch_env(project_name: &str, project_dir: &Path) -> Option<PathBuf> {
+    let data_dir = env::var("HATCH_DATA_DIR")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| dirs::data_local_dir().map(|dir| dir.join("hatch")))?;
+    let mut project_path = project_dir.to_string_lossy().to_string();
+    if cfg!(any(target_os = "windows", target_os = "macos")) {
+        project_path = project_path.to_lowercase();
+    }
+    let storage_dir = data_dir
+        .join("env")
+        .join("virtual")
+        .join(project_name)
+        .join(path_hash(&project_path, 32));
+    let venv = storage_dir.join(project_name);
+    if venv.exists() {
+        Some(venv)
+    } else {
+        find_sub_directory(&storage_dir, "")
+    }
+}
+
+/// Pipenv: `$WORKON_HOME/<directory name>-<hash>` or `~/.local/share/virtualenvs/<directory name>-<hash>`
+fn find_pipenv_env(project_dir: &Path) -> Option<PathBuf> {
+    let virtualenvs_dir = env::var("WORKON_HOME")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| dirs::home_dir().map(|dir| dir.join(".local").join("share").join("virtualenvs")))?;
+    let dir_name = project_dir.file_name()?.to_string_lossy().to_string();
+    let sanitized_name: String = Regex::new(r#"[ &$`!*@"()\[\]\\\r\n\t]"#)
+        .unwrap()
+        .replace_all(&dir_name, "_")
+        .chars()
+        .take(42)
+        .collect();
+    let pipfile = project_dir.join("Pipfile");
+    let venv = virtualenvs_dir.join(format!(
+        "{}-{}",
+        sanitized_name,
+        path_hash(&pipfile.to_string_lossy(), 6)
+    ));
+    if venv.exists() { Some(venv) } else { None }
+}
+
+fn venv_bin_dir(venv: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        venv.join("Scripts")
+    } else {
+        venv.join("bin")
+    }
+}
+
+/// python interpreter of the virtual environment
+pub fn venv_python(venv: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        venv_bin_dir(venv).join("python.exe")
+    } else {
+        venv_bin_dir(venv).join("python")
+    }
+}
+
 pub fn init_env() {
-    if !env::current_dir()
-        .map(|dir| dir.join("venv").exists() || dir.join(".venv").exists())
-        .unwrap_or(false)
-    {
-        if let Some(python_home) = find_sdk_home() {
-            reset_python_home(&python_home);
+    if let Some(venv) = find_virtual_env() {
+        activate_virtual_env(&venv);
+    } else if let Some(python_home) = find_sdk_home() {
+        reset_python_home(&python_home);
+    }
+}
+
+/// the same as `source venv/bin/activate`
+fn activate_virtual_env(venv: &Path) {
+    unsafe {
+        env::set_var("VIRTUAL_ENV", venv);
+        env::remove_var("PYTHONHOME");
+    }
+    if let Ok(path) = env::var("PATH") {
+        let venv_bin_path = venv_bin_dir(venv).to_string_lossy().to_string();
+        unsafe {
+            env::set_var("PATH", format!("{}{}{}", venv_bin_path, PATH_SEPARATOR, path));
         }
     }
 }
 
-fn reset_python_home(python_home_path: &PathBuf) {
+fn reset_python_home(python_home_path: &Path) {
     if let Ok(path) = env::var("PATH") {
         let node_bin_path = python_home_path.join("bin").to_string_lossy().to_string();
         unsafe {
@@ -105,4 +300,30 @@ mod tests {
         let path = find_sub_directory(&python_versions_dir, prefix).unwrap();
         println!("path: {:?}", path);
     }
+
+    #[test]
+    fn test_find_python_by_req() {
+        let uv_temp_dir = tempfile::tempdir().unwrap();
+        let uv_dir = uv_temp_dir.path();
+        let pyenv_temp_dir = tempfile::tempdir().unwrap();
+        let pyenv_dir = pyenv_temp_dir.path();
+        for name in ["cpython-3.11.9-linux-x86_64-gnu", "cpython-3.13.1-linux-x86_64-gnu"] {
+            std::fs::create_dir_all(uv_dir.join(name)).unwrap();
+        }
+        std::fs::create_dir_all(pyenv_dir.join("3.12.3")).unwrap();
+        let install_dirs = vec![uv_dir.to_path_buf(), pyenv_dir.to_path_buf()];
+        let find = |requires_python: &str| {
+            find_python_by_req(&pep440_to_req(requires_python).unwrap(), &install_dirs)
+        };
+        assert!(find(">=3.10,<3.13").unwrap().ends_with("3.12.3"));
+        assert!(find("~=3.11.0").unwrap().ends_with("cpython-3.11.9-linux-x86_64-gnu"));
+        assert!(find(">=3.14").is_none());
+    }
+
+    #[test]
+    fn test_path_hash() {
+        // `base64.urlsafe_b64encode(hashlib.sha256(b"/home/demo/app").digest())[:8]` of Python
+        assert_eq!("Lu7zyo5C", path_hash("/home/demo/app", 32));
+        assert_eq!("02h03vvr", path_hash("/home/demo/app/Pipfile", 6));
+    }
 }
@@ -1,11 +1,283 @@
+use crate::common::versions::{gem_requirement_to_req, parse_version};
+use crate::polyglot::{toolversions, PATH_SEPARATOR};
+use regex::Regex;
 use std::env;
+use std::path::{Path, PathBuf};
 
 pub fn is_available() -> bool {
     env::current_dir()
-        .map(|dir| dir.join(".ruby-version").exists())
+        .map(|dir| {
+            dir.join(".ruby-version").exists()
+                || dir.join("Gemfile").exists()
+                || toolversions::is_available()
+        })
         .unwrap_or(false)
+        && get_requirement().is_some()
 }
 
-pub fn get_default_version() -> std::io::Result<String> {
-    std::fs::read_to_string(".ruby-version").map(|text| text.trim().to_string())
+/// Ruby version wanted by the project
+#[derive(Debug, Clone, PartialEq)]
+pub enum RubyRequirement {
+    /// `.ruby-version`, `.tool-versions`/`mise.toml` or `ruby "3.2.2"` in Gemfile, and `3.2` is the latest 3.2.x
+    Version(String),
+    /// `ruby "~> 3.2"` or `ruby ">= 3.1", "< 3.4"` in Gemfile, and the latest installed version in the range
+    Range(Vec<String>),
+}
+
+impl RubyRequirement {
+    pub fn text(&self) -> String {
+        match self {
+            RubyRequirement::Version(version) => version.clone(),
+            RubyRequirement::Range(requirements) => requirements.join(", "),
+        }
+    }
+}
+
+/// version from `.ruby-version`, `.tool-versions`/`mise.toml`, or the `ruby` directive in Gemfile
+pub fn get_requirement() -> Option<RubyRequirement> {
+    std::fs::read_to_string(".ruby-version")
+        .ok()
+        .map(|text| text.trim().trim_start_matches("ruby-").to_string())
+        .or_else(|| toolversions::get_tool_version("ruby"))
+        .map(RubyRequirement::Version)
+        .or_else(|| {
+            std::fs::read_to_string("Gemfile")
+                .ok()
+                .and_then(|code| extract_ruby_requirement_from_gemfile(&code))
+        })
+}
+
+/// `ruby "3.2.2"`, `ruby '~> 3.2.0'` or `ruby ">= 3.1", "< 3.4"` in Gemfile
+fn extract_ruby_requirement_from_gemfile(code: &str) -> Option<RubyRequirement> {
+    let directive = Regex::new(r#"(?m)^\s*ruby\s*\(?\s*((?:["'][^"'\n]*["']\s*,?\s*)+)"#)
+        .unwrap()
+        .captures(code)?;
+    let requirements: Vec<String> = Regex::new(r#"["']([^"']*)["']"#)
+        .unwrap()
+        .captures_iter(&directive[1])
+        .map(|captures| captures[1].trim().to_string())
+        .filter(|requirement| !requirement.is_empty())
+        .collect();
+    match requirements.as_slice() {
+        [] => None,
+        [version] if version.starts_with(|c: char| c.is_ascii_digit()) => {
+            Some(RubyRequirement::Version(version.clone()))
+        }
+        _ => Some(RubyRequirement::Range(requirements)),
+    }
+}
+
+/// A Ruby installation with its gem directories, which depend on the version manager
+struct RubyInstall {
+    home: PathBuf,
+    gem_home: PathBuf,
+    gem_path: Vec<PathBuf>,
+}
+
+#[derive(Clone, Copy)]
+enum RubyManager {
+    Rbenv,
+    Rvm,
+    Chruby,
+    Asdf,
+}
+
+fn ruby_install_dirs() -> Vec<(RubyManager, PathBuf)> {
+    let mut dirs = vec![];
+    if let Some(home) = dirs::home_dir() {
+        dirs.push((RubyManager::Rbenv, home.join(".rbenv").join("versions")));
+        dirs.push((RubyManager::Rvm, home.join(".rvm").join("rubies")));
+        dirs.push((RubyManager::Chruby, home.join(".rubies")));
+        for install_dir in toolversions::get_install_dirs("ruby") {
+            dirs.push((RubyManager::Asdf, install_dir));
+        }
+    }
+    dirs.push((RubyManager::Chruby, PathBuf::from("/opt/rubies")));
+    dirs
+}
+
+fn version_parts(version: &str) -> Vec<u32> {
+    version
+        .split(['.', '-', 'p'])
+        .map_while(|part| part.parse::<u32>().ok())
+        .collect()
+}
+
+/// find the Ruby in the directory: the exact version first, otherwise the latest version with the prefix,
+/// and `3.2` matches `3.2.2` or `ruby-3.2.2`, but not `3.20.0`. For a range, the latest version in the range.
+fn find_ruby_in_dir(install_dir: &Path, requirement: &RubyRequirement) -> Option<PathBuf> {
+    let matches: Box<dyn Fn(&str) -> bool> = match requirement {
+        RubyRequirement::Version(ruby_version) => {
+            for name in [ruby_version.to_string(), format!("ruby-{}", ruby_version)] {
+                if install_dir.join(&name).is_dir() {
+                    return Some(install_dir.join(name));
+                }
+            }
+            let prefix = format!("{}.", ruby_version);
+            Box::new(move |version| version.starts_with(&prefix))
+        }
+        RubyRequirement::Range(requirements) => {
+            let requirements: Vec<&str> = requirements.iter().map(|item| item.as_str()).collect();
+            let req = gem_requirement_to_req(&requirements)?;
+            Box::new(move |version| {
+                version.starts_with(|c: char| c.is_ascii_digit())
+                    && parse_version(version).is_some_and(|version| req.matches(&version))
+            })
+        }
+    };
+    std::fs::read_dir(install_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let version = name.trim_start_matches("ruby-").to_string();
+            if matches(&version) {
+                Some((version_parts(&version), entry.path()))
+            } else {
+                None
+            }
+        })
+        .max_by(|(left, _), (right, _)| left.cmp(right))
+        .map(|(_, path)| path)
+}
+
+/// gem directory of the Ruby itself, such as `lib/ruby/gems/3.2.0`
+fn find_gem_root(ruby_home: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(ruby_home.join("lib").join("ruby").join("gems"))
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| path.is_dir())
+}
+
+fn find_ruby() -> Option<RubyInstall> {
+    let requirement = get_requirement()?;
+    let (manager, home) = ruby_install_dirs()
+        .into_iter()
+        .filter(|(_, dir)| dir.exists())
+        .find_map(|(manager, dir)| {
+            find_ruby_in_dir(&dir, &requirement).map(|home| (manager, home))
+        })?;
+    let full_version = home
+        .file_name()
+        .map(|name| name.to_string_lossy().trim_start_matches("ruby-").to_string())
+        .unwrap_or(requirement.text());
+    let gem_root = find_gem_root(&home);
+    let user_home = dirs::home_dir().unwrap_or_default();
+    let (gem_home, mut gem_path) = match manager {
+        RubyManager::Rvm => {
+            let gems_dir = user_home.join(".rvm").join("gems");
+            let gem_home = gems_dir.join(format!("ruby-{}", full_version));
+            let global_gems = gems_dir.join(format!("ruby-{}@global", full_version));
+            (gem_home.clone(), vec![gem_home, global_gems])
+        }
+        RubyManager::Chruby => {
+            let gem_home = user_home.join(".gem").join("ruby").join(&full_version);
+            (gem_home.clone(), vec![gem_home])
+        }
+        // gems are installed into the Ruby itself
+        RubyManager::Rbenv | RubyManager::Asdf => {
+            let gem_home = gem_root.clone().unwrap_or_else(|| home.clone());
+            (gem_home, vec![])
+        }
+    };
+    if let Some(gem_root) = gem_root
+        && !gem_path.contains(&gem_root)
+    {
+        gem_path.push(gem_root);
+    }
+    if gem_path.is_empty() {
+        gem_path.push(gem_home.clone());
+    }
+    Some(RubyInstall {
+        home,
+        gem_home,
+        gem_path,
+    })
+}
+
+pub fn find_sdk_home() -> Option<PathBuf> {
+    find_ruby().map(|ruby| ruby.home)
+}
+
+pub fn init_env() {
+    if let Some(ruby) = find_ruby() {
+        reset_ruby_home(&ruby);
+    }
+}
+
+fn reset_ruby_home(ruby: &RubyInstall) {
+    let gem_path = ruby
+        .gem_path
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect::<Vec<String>>()
+        .join(&PATH_SEPARATOR.to_string());
+    unsafe {
+        env::set_var("RUBY_ROOT", &ruby.home);
+        env::set_var("GEM_HOME", &ruby.gem_home);
+        env::set_var("GEM_PATH", gem_path);
+    }
+    if let Ok(path) = env::var("PATH") {
+        let ruby_bin_path = ruby.home.join("bin").to_string_lossy().to_string();
+        let gem_bin_path = ruby.gem_home.join("bin").to_string_lossy().to_string();
+        unsafe {
+            env::set_var(
+                "PATH",
+                format!(
+                    "{}{}{}{}{}",
+                    gem_bin_path, PATH_SEPARATOR, ruby_bin_path, PATH_SEPARATOR, path
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_ruby_requirement_from_gemfile() {
+        let code = r#"
+source "https://rubygems.org"
+
+ruby "3.2.2"
+gem "rails", "~> 7.1"
+"#;
+        assert_eq!(
+            Some(RubyRequirement::Version("3.2.2".to_string())),
+            extract_ruby_requirement_from_gemfile(code)
+        );
+        assert_eq!(
+            Some(RubyRequirement::Range(vec!["~> 3.3.0".to_string()])),
+            extract_ruby_requirement_from_gemfile("ruby '~> 3.3.0'")
+        );
+        assert_eq!(
+            Some(RubyRequirement::Range(vec![">= 3.1".to_string(), "< 3.4".to_string()])),
+            extract_ruby_requirement_from_gemfile("ruby \">= 3.1\", \"< 3.4\"\ngem \"rake\"")
+        );
+        assert_eq!(None, extract_ruby_requirement_from_gemfile("gem 'ruby-progressbar'"));
+        assert_eq!(None, extract_ruby_requirement_from_gemfile("ruby file: \".ruby-version\""));
+    }
+
+    #[test]
+    fn test_find_ruby_in_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let install_dir = temp_dir.path();
+        for name in ["ruby-3.2.1", "ruby-3.2.10", "3.20.0"] {
+            std::fs::create_dir_all(install_dir.join(name)).unwrap();
+        }
+        let version = |version: &str| RubyRequirement::Version(version.to_string());
+        let range = |items: &[&str]| RubyRequirement::Range(items.iter().map(|item| item.to_string()).collect());
+        assert!(find_ruby_in_dir(install_dir, &version("3.2")).unwrap().ends_with("ruby-3.2.10"));
+        assert!(find_ruby_in_dir(install_dir, &version("3.2.1")).unwrap().ends_with("ruby-3.2.1"));
+        assert!(find_ruby_in_dir(install_dir, &version("3.3")).is_none());
+        // `~> 3.2` is `>= 3.2, < 4`, and the latest version in the range wins
+        assert!(find_ruby_in_dir(install_dir, &range(&["~> 3.2"])).unwrap().ends_with("3.20.0"));
+        assert!(find_ruby_in_dir(install_dir, &range(&["~> 3.2.0"])).unwrap().ends_with("ruby-3.2.10"));
+        assert!(find_ruby_in_dir(install_dir, &range(&[">= 3.1", "< 3.2.5"])).unwrap().ends_with("ruby-3.2.1"));
+        assert!(find_ruby_in_dir(install_dir, &range(&[">= 3.21"])).is_none());
+    }
 }
@@ -0,0 +1,214 @@
+//! Rust toolchain declared by `rust-toolchain.toml` or `rust-toolchain`, and checked with rustup.
+use crate::command_utils::{capture_command_output, is_command_available};
+use crate::common::versions::{parse_version, read_rust_version};
+use std::env;
+use std::path::Path;
+
+/// `[toolchain]` of rust-toolchain.toml
+#[derive(Debug, Default, PartialEq)]
+pub struct RustToolchain {
+    pub channel: String,
+    pub components: Vec<String>,
+    pub targets: Vec<String>,
+}
+
+impl RustToolchain {
+    /// arguments of `rustup toolchain install` with the declared components and targets
+    pub fn install_args(&self) -> Vec<String> {
+        let mut args = vec!["toolchain".to_string(), "install".to_string(), self.channel.clone()];
+        if !self.components.is_empty() {
+            args.push("--component".to_string());
+            args.push(self.components.join(","));
+        }
+        if !self.targets.is_empty() {
+            args.push("--target".to_string());
+            args.push(self.targets.join(","));
+        }
+        args
+    }
+}
+
+pub fn is_available() -> bool {
+    env::current_dir()
+        .map(|dir| dir.join("rust-toolchain.toml").exists() || dir.join("rust-toolchain").exists())
+        .unwrap_or(false)
+}
+
+pub fn get_toolchain() -> Option<RustToolchain> {
+    if let Ok(text) = std::fs::read_to_string("rust-toolchain.toml") {
+        return parse_toolchain(&text);
+    }
+    std::fs::read_to_string("rust-toolchain")
+        .ok()
+        .and_then(|text| parse_toolchain(&text))
+}
+
+pub fn get_default_version() -> Option<String> {
+    get_toolchain().map(|toolchain| toolchain.channel)
+}
+
+/// TOML with `[toolchain]` table, or the legacy file with the channel only
+fn parse_toolchain(text: &str) -> Option<RustToolchain> {
+    let Ok(table) = text.parse::<toml::Table>() else {
+        let channel = text.trim();
+        return if channel.is_empty() || channel.contains(['\n', '=']) {
+            None
+        } else {
+            Some(RustToolchain {
+                channel: channel.to_string(),
+                ..Default::default()
+            })
+        };
+    };
+    let toolchain = table.get("toolchain")?.as_table()?;
+    let strings = |key: &str| -> Vec<String> {
+        toolchain
+            .get(key)
+            .and_then(|value| value.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item.as_str())
+                    .map(|item| item.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    Some(RustToolchain {
+        channel: toolchain
+            .get("channel")
+            .and_then(|channel| channel.as_str())
+            .unwrap_or("stable")
+            .to_string(),
+        components: strings("components"),
+        targets: strings("targets"),
+    })
+}
+
+/// `stable` matches `stable-x86_64-unknown-linux-gnu`, but `nightly` does not match `nightly-2024-01-01-x86_64-unknown-linux-gnu`
+fn is_toolchain_matched(channel: &str, installed: &str) -> bool {
+    let installed = installed.split_whitespace().next().unwrap_or_default();
+    installed == channel
+        || installed
+            .strip_prefix(channel)
+            .and_then(|rest| rest.strip_prefix('-'))
+            .map(|host| !host.starts_with(|c: char| c.is_ascii_digit()))
+            .unwrap_or(false)
+}
+
+/// lines of rustup output, empty if rustup failed
+fn rustup_lines(args: &[&str]) -> Vec<String> {
+    capture_command_output("rustup", args)
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(|line| line.trim().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn is_toolchain_installed(channel: &str) -> bool {
+    rustup_lines(&["toolchain", "list"])
+        .iter()
+        .any(|installed| is_toolchain_matched(channel, installed))
+}
+
+/// declared names missing in the installed list, and `clippy` matches `clippy-x86_64-unknown-linux-gnu`
+fn find_missing(declared: &[String], installed: &[String]) -> Vec<String> {
+    declared
+        .iter()
+        .filter(|name| {
+            !installed
+                .iter()
+                .any(|item| item == *name || item.starts_with(&format!("{}-", name)))
+        })
+        .cloned()
+        .collect()
+}
+
+/// the declared toolchain and what is missing in rustup
+pub struct ToolchainStatus {
+    pub toolchain: RustToolchain,
+    /// `rust-version` of Cargo.toml newer than the channel of the toolchain
+    pub newer_rust_version: Option<String>,
+    pub rustup_available: bool,
+    pub installed: bool,
+    pub missing_components: Vec<String>,
+    pub missing_targets: Vec<String>,
+}
+
+pub fn get_toolchain_status() -> Option<ToolchainStatus> {
+    let toolchain = get_toolchain()?;
+    let channel = toolchain.channel.as_str();
+    // `rust-version` of Cargo.toml is the minimum version of the toolchain
+    let newer_rust_version = read_rust_version(Path::new("Cargo.toml")).filter(|rust_version| {
+        match (
+            parse_version(channel).filter(|_| channel.starts_with(|c: char| c.is_ascii_digit())),
+            parse_version(rust_version),
+        ) {
+            (Some(channel_version), Some(minimum)) => channel_version < minimum,
+            _ => false,
+        }
+    });
+    let rustup_available = is_command_available("rustup");
+    let installed = rustup_available && is_toolchain_installed(channel);
+    let (missing_components, missing_targets) = if installed {
+        (
+            find_missing(
+                &toolchain.components,
+                &rustup_lines(&["component", "list", "--installed", "--toolchain", channel]),
+            ),
+            find_missing(
+                &toolchain.targets,
+                &rustup_lines(&["target", "list", "--installed", "--toolchain", channel]),
+            ),
+        )
+    } else {
+        (vec![], vec![])
+    };
+    Some(ToolchainStatus {
+        toolchain,
+        newer_rust_version,
+        rustup_available,
+        installed,
+        missing_components,
+        missing_targets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toolchain() {
+        let text = r#"
+[toolchain]
+channel = "1.84"
+components = ["rustfmt", "clippy"]
+targets = ["wasm32-unknown-unknown"]
+"#;
+        let toolchain = parse_toolchain(text).unwrap();
+        assert_eq!("1.84", toolchain.channel);
+        assert_eq!(vec!["rustfmt", "clippy"], toolchain.components);
+        assert_eq!(
+            vec!["toolchain", "install", "1.84", "--component", "rustfmt,clippy", "--target", "wasm32-unknown-unknown"],
+            toolchain.install_args()
+        );
+        assert_eq!("nightly-2024-01-01", parse_toolchain("nightly-2024-01-01\n").unwrap().channel);
+    }
+
+    #[test]
+    fn test_is_toolchain_matched() {
+        assert!(is_toolchain_matched("stable", "stable-x86_64-unknown-linux-gnu (default)"));
+        assert!(is_toolchain_matched("1.84", "1.84-aarch64-apple-darwin"));
+        assert!(!is_toolchain_matched("1.84", "1.84.1-aarch64-apple-darwin"));
+        assert!(!is_toolchain_matched("nightly", "nightly-2024-01-01-x86_64-unknown-linux-gnu"));
+        let installed = vec!["clippy-x86_64-unknown-linux-gnu".to_string(), "rust-src".to_string()];
+        let declared = vec!["clippy".to_string(), "rust-src".to_string(), "miri".to_string()];
+        assert_eq!(vec!["miri".to_string()], find_missing(&declared, &installed));
+    }
+}
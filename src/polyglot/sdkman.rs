@@ -2,7 +2,6 @@ use std::env;
 use std::fs::File;
 use std::io::BufReader;
 use crate::polyglot::PATH_SEPARATOR;
-use colored::Colorize;
 
 
 pub fn is_available() -> bool {
@@ -40,21 +39,29 @@ pub fn init_env() {
     }
 }
 
-pub fn diagnose() -> i32 {
-    let sdkmanrc_file = File::open(".sdkmanrc").unwrap();
-    let sdkman_map = java_properties::read(BufReader::new(sdkmanrc_file)).unwrap();
-    let candidates_home = dirs::home_dir().unwrap().join(".sdkman").join("candidates");
-    let mut problems_count = 0;
-    for (key, value) in sdkman_map.iter() {
-        let candidate_home_path = candidates_home.join(key).join(value);
-        if !candidate_home_path.exists() {
-            problems_count += 1;
-            println!("{} {} found in .sdkmanrc, but not installed, please use `sdk install {} {}` to install it.",
-                     "Warning:".bold().yellow(),
-                     key, key, value);
-        }
-    }
-    problems_count
+/// candidate of .sdkmanrc, such as `java=21.0.2-tem`
+pub struct SdkmanCandidate {
+    pub name: String,
+    pub version: String,
+    pub installed: bool,
+}
+
+pub fn list_candidates() -> Vec<SdkmanCandidate> {
+    let Ok(sdkmanrc_file) = File::open(".sdkmanrc") else {
+        return vec![];
+    };
+    let sdkman_map = java_properties::read(BufReader::new(sdkmanrc_file)).unwrap_or_default();
+    let candidates_home = dirs::home_dir().unwrap_or_default().join(".sdkman").join("candidates");
+    let mut candidates: Vec<SdkmanCandidate> = sdkman_map
+        .iter()
+        .map(|(key, value)| SdkmanCandidate {
+            name: key.clone(),
+            version: value.clone(),
+            installed: candidates_home.join(key).join(value).exists(),
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.name.cmp(&b.name));
+    candidates
 }
 
 
@@ -0,0 +1,255 @@
+//! `.tool-versions`(asdf) and `mise.toml` as version sources of all languages and tools.
+//!
+//! Versions of java, node, python, ruby and golang are used by their own modules, and other tools
+//! (rust, deno, bun, gradle, maven) are added to PATH from asdf/mise installs or sdkman candidates.
+use crate::polyglot::PATH_SEPARATOR;
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+
+const MISE_CONFIG_FILES: [&str; 4] = ["mise.local.toml", ".mise.local.toml", "mise.toml", ".mise.toml"];
+
+/// tools added to PATH by this module: tool name, its `*_HOME` variable, sdkman candidate
+const EXTRA_TOOLS: [(&str, &str, Option<&str>); 5] = [
+    ("rust", "", None),
+    ("deno", "DENO_INSTALL", None),
+    ("bun", "BUN_INSTALL", None),
+    ("gradle", "GRADLE_HOME", Some("gradle")),
+    ("maven", "MAVEN_HOME", Some("maven")),
+];
+
+pub fn is_available() -> bool {
+    env::current_dir()
+        .map(|dir| {
+            dir.join(".tool-versions").exists()
+                || MISE_CONFIG_FILES.iter().any(|name| dir.join(name).exists())
+        })
+        .unwrap_or(false)
+}
+
+/// canonical tool name: asdf plugin names are used, such as `nodejs` and `golang`
+fn canonical_name(tool: &str) -> &str {
+    match tool {
+        "node" => "nodejs",
+        "go" => "golang",
+        other => other,
+    }
+}
+
+/// name of the tool in mise installs directory
+fn mise_name(tool: &str) -> &str {
+    match tool {
+        "nodejs" => "node",
+        "golang" => "go",
+        other => other,
+    }
+}
+
+fn parse_tool_versions(text: &str) -> HashMap<String, String> {
+    let mut versions = HashMap::new();
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let mut parts = line.split_whitespace();
+        if let (Some(tool), Some(version)) = (parts.next(), parts.next()) {
+            versions.insert(canonical_name(tool).to_string(), version.to_string());
+        }
+    }
+    versions
+}
+
+/// `[tools]` in mise.toml: `node = "20"`, `python = ["3.12", "3.11"]` or `java = { version = "21" }`
+fn parse_mise_toml(text: &str) -> HashMap<String, String> {
+    let mut versions = HashMap::new();
+    let Ok(table) = text.parse::<toml::Table>() else {
+        return versions;
+    };
+    if let Some(tools) = table.get("tools").and_then(|tools| tools.as_table()) {
+        for (tool, value) in tools {
+            let version = match value {
+                toml::Value::String(version) => Some(version.clone()),
+                toml::Value::Array(versions) => versions
+                    .first()
+                    .and_then(|version| version.as_str())
+                    .map(|version| version.to_string()),
+                toml::Value::Table(options) => options
+                    .get("version")
+                    .and_then(|version| version.as_str())
+                    .map(|version| version.to_string()),
+                _ => None,
+            };
+            // `core:` prefix of mise core plugins
+            let tool = tool.trim_start_matches("core:");
+            if let Some(version) = version {
+                versions.insert(canonical_name(tool).to_string(), version);
+            }
+        }
+    }
+    versions
+}
+
+/// versions from `.tool-versions` and mise config files, and mise's local config wins
+pub fn get_tool_versions() -> HashMap<String, String> {
+    let mut versions = HashMap::new();
+    if let Ok(text) = std::fs::read_to_string(".tool-versions") {
+        versions.extend(parse_tool_versions(&text));
+    }
+    for name in MISE_CONFIG_FILES.iter().rev() {
+        if let Ok(text) = std::fs::read_to_string(name) {
+            versions.extend(parse_mise_toml(&text));
+        }
+    }
+    // `system` and `latest` are not real versions
+    versions.retain(|_, version| version != "system" && version != "latest");
+    versions
+}
+
+/// version of a tool, such as `java`, `nodejs`(or `node`), `python`
+pub fn get_tool_version(tool: &str) -> Option<String> {
+    get_tool_versions().remove(canonical_name(tool))
+}
+
+/// install directories of a tool by asdf and mise
+pub fn get_install_dirs(tool: &str) -> Vec<PathBuf> {
+    let tool = canonical_name(tool);
+    let mut dirs = vec![];
+    if let Some(home) = dirs::home_dir() {
+        let asdf_data_dir = env::var("ASDF_DATA_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| home.join(".asdf"));
+        dirs.push(asdf_data_dir.join("installs").join(tool));
+        let mise_data_dir = env::var("MISE_DATA_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| home.join(".local").join("share").join("mise"));
+        dirs.push(mise_data_dir.join("installs").join(mise_name(tool)));
+    }
+    dirs
+}
+
+/// the exact version first, otherwise the latest version with the prefix: `20` matches `20.11.0`
+pub fn find_version_dir(install_dir: &Path, version: &str) -> Option<PathBuf> {
+    let exact_dir = install_dir.join(version);
+    if exact_dir.is_dir() {
+        return Some(exact_dir);
+    }
+    let prefix = format!("{}.", version);
+    std::fs::read_dir(install_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with(&prefix) {
+                let parts: Vec<u32> = name
+                    .split(['.', '-', '+'])
+                    .map_while(|part| part.parse::<u32>().ok())
+                    .collect();
+                Some((parts, entry.path()))
+            } else {
+                None
+            }
+        })
+        .max_by(|(left, _), (right, _)| left.cmp(right))
+        .map(|(_, path)| path)
+}
+
+/// install home of the tool in asdf or mise installs, used directly without asdf or mise on PATH
+pub fn find_install_home(tool: &str, version: &str) -> Option<PathBuf> {
+    get_install_dirs(tool)
+        .iter()
+        .filter(|dir| dir.exists())
+        .find_map(|dir| find_version_dir(dir, version))
+}
+
+/// add tools without their own module to PATH
+pub fn init_env() {
+    let versions = get_tool_versions();
+    for (tool, home_name, sdkman_candidate) in EXTRA_TOOLS {
+        let Some(version) = versions.get(tool) else {
+            continue;
+        };
+        let tool_home = find_install_home(tool, version).or_else(|| {
+            let candidate = sdkman_candidate?;
+            let candidates_dir = dirs::home_dir()?
+                .join(".sdkman")
+                .join("candidates")
+                .join(candidate);
+            find_version_dir(&candidates_dir, version)
+        });
+        if let Some(tool_home) = tool_home {
+            reset_tool_home(&tool_home, home_name);
+        }
+    }
+}
+
+fn reset_tool_home(tool_home: &Path, home_name: &str) {
+    if !home_name.is_empty() {
+        unsafe {
+            env::set_var(home_name, tool_home);
+        }
+    }
+    let bin_path = tool_home.join("bin");
+    let bin_path = if bin_path.exists() {
+        bin_path
+    } else {
+        tool_home.to_path_buf()
+    };
+    if let Ok(path) = env::var("PATH") {
+        unsafe {
+            env::set_var(
+                "PATH",
+                format!("{}{}{}", bin_path.to_string_lossy(), PATH_SEPARATOR, path),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tool_versions() {
+        let text = r#"
+# tools of the project
+nodejs 20.11.0 18.19.1
+java temurin-17.0.9+9
+go 1.22.1 # go as golang
+python system
+"#;
+        let versions = parse_tool_versions(text);
+        assert_eq!(Some(&"20.11.0".to_string()), versions.get("nodejs"));
+        assert_eq!(Some(&"temurin-17.0.9+9".to_string()), versions.get("java"));
+        assert_eq!(Some(&"1.22.1".to_string()), versions.get("golang"));
+    }
+
+    #[test]
+    fn test_parse_mise_toml() {
+        let text = r#"
+[env]
+NODE_ENV = "production"
+
+[tools]
+node = "20"
+python = ["3.12", "3.11"]
+java = { version = "21" }
+"core:ruby" = "3.3"
+"#;
+        let versions = parse_mise_toml(text);
+        assert_eq!(Some(&"20".to_string()), versions.get("nodejs"));
+        assert_eq!(Some(&"3.12".to_string()), versions.get("python"));
+        assert_eq!(Some(&"21".to_string()), versions.get("java"));
+        assert_eq!(Some(&"3.3".to_string()), versions.get("ruby"));
+    }
+
+    #[test]
+    fn test_find_version_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let install_dir = temp_dir.path();
+        for name in ["20.9.0", "20.11.0", "18.19.1"] {
+            std::fs::create_dir_all(install_dir.join(name)).unwrap();
+        }
+        assert!(find_version_dir(install_dir, "20").unwrap().ends_with("20.11.0"));
+        assert!(find_version_dir(install_dir, "18.19.1").unwrap().ends_with("18.19.1"));
+        assert!(find_version_dir(install_dir, "2").is_none());
+    }
+}
@@ -0,0 +1,315 @@
+//! Cargo: `[alias]` of `.cargo/config.toml` files in the config hierarchy, and cargo-run-script style
+//! `[package.metadata.scripts]`/`[workspace.metadata.scripts]` tables. Scripts of workspace members are named as `member:script`.
+use crate::command_utils::{run_command, run_command_by_shell_in_dir, CommandOutput};
+use crate::common::workspace::expand_pattern;
+use crate::errors::KeeperError;
+use crate::models::Task;
+use crate::task;
+use error_stack::{IntoReport, Report, ResultExt};
+use std::path::{Path, PathBuf};
+use which::which;
+
+/// alias with the arguments of cargo, and `b = "build"` or `rr = ["run", "--release"]`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CargoAlias {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+/// script of `[package.metadata.scripts]`, and `dir` is the directory of the workspace member
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CargoScript {
+    pub name: String,
+    pub command: String,
+    pub dir: Option<PathBuf>,
+}
+
+pub fn is_available() -> bool {
+    std::env::current_dir()
+        .map(|dir| dir.join("Cargo.toml").exists())
+        .unwrap_or(false)
+}
+
+pub fn is_command_available() -> bool {
+    which("cargo").is_ok()
+}
+
+/// `config` without extension is used if both `config` and `config.toml` exist, as cargo does
+fn config_file(cargo_dir: &Path) -> Option<PathBuf> {
+    [cargo_dir.join("config"), cargo_dir.join("config.toml")]
+        .into_iter()
+        .find(|path| path.is_file())
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    std::env::var("CARGO_HOME")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))
+}
+
+/// config files from the current directory to the root, and nearer files win.
+/// Global aliases of `$CARGO_HOME/config.toml` are not tasks of the project, and the file is skipped.
+fn config_files(dir: &Path, cargo_home: Option<&Path>) -> Vec<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(".cargo"))
+        .filter(|cargo_dir| Some(cargo_dir.as_path()) != cargo_home)
+        .filter_map(|cargo_dir| config_file(&cargo_dir))
+        .collect()
+}
+
+fn parse_aliases(text: &str) -> Vec<CargoAlias> {
+    let Ok(table) = text.parse::<toml::Table>() else {
+        return vec![];
+    };
+    table
+        .get("alias")
+        .and_then(|aliases| aliases.as_table())
+        .map(|aliases| {
+            aliases
+                .iter()
+                .filter_map(|(name, value)| {
+                    let args: Vec<String> = match value {
+                        toml::Value::String(command) => {
+                            command.split_whitespace().map(|arg| arg.to_string()).collect()
+                        }
+                        toml::Value::Array(items) => items
+                            .iter()
+                            .filter_map(|item| item.as_str())
+                            .map(|item| item.to_string())
+                            .collect(),
+                        _ => return None,
+                    };
+                    Some(CargoAlias {
+                        name: name.clone(),
+                        args,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn list_aliases() -> Vec<CargoAlias> {
+    let Ok(current_dir) = std::env::current_dir() else {
+        return vec![];
+    };
+    let mut aliases: Vec<CargoAlias> = vec![];
+    for file in config_files(&current_dir, cargo_home().as_deref()) {
+        let text = std::fs::read_to_string(&file).unwrap_or_default();
+        for alias in parse_aliases(&text) {
+            if !aliases.iter().any(|existing| existing.name == alias.name) {
+                aliases.push(alias);
+            }
+        }
+    }
+    aliases.sort_by(|a, b| a.name.cmp(&b.name));
+    aliases
+}
+
+fn metadata_scripts(table: &toml::Table, section: &str) -> Vec<(String, String)> {
+    table
+        .get(section)
+        .and_then(|section| section.get("metadata"))
+        .and_then(|metadata| metadata.get("scripts"))
+        .and_then(|scripts| scripts.as_table())
+        .map(|scripts| {
+            scripts
+                .iter()
+                .filter_map(|(name, command)| command.as_str().map(|command| (name.clone(), command.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn string_items(value: Option<&toml::Value>) -> Vec<String> {
+    value
+        .and_then(|value| value.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str())
+                .map(|item| item.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// member directories of `[workspace] members`, without `exclude` directories and the root
+fn workspace_members(root: &Path, table: &toml::Table) -> Vec<PathBuf> {
+    let Some(workspace) = table.get("workspace") else {
+        return vec![];
+    };
+    let excluded: Vec<PathBuf> = string_items(workspace.get("exclude"))
+        .iter()
+        .flat_map(|pattern| expand_pattern(root, pattern))
+        .collect();
+    let mut members: Vec<PathBuf> = string_items(workspace.get("members"))
+        .iter()
+        .flat_map(|pattern| expand_pattern(root, pattern))
+        .filter(|dir| dir != root && !excluded.contains(dir) && dir.join("Cargo.toml").is_file())
+        .collect();
+    members.sort();
+    members.dedup();
+    members
+}
+
+/// scripts of the package and the workspace in the directory, and scripts of members with the package name prefix
+fn collect_scripts(root: &Path) -> Result<Vec<CargoScript>, Report<KeeperError>> {
+    let text = std::fs::read_to_string(root.join("Cargo.toml")).change_context(KeeperError::InvalidCargoToml)?;
+    let table = text
+        .parse::<toml::Table>()
+        .map_err(|_| KeeperError::InvalidCargoToml.into_report())?;
+    let mut scripts: Vec<CargoScript> = vec![];
+    for section in ["package", "workspace"] {
+        for (name, command) in metadata_scripts(&table, section) {
+            if !scripts.iter().any(|script| script.name == name) {
+                scripts.push(CargoScript { name, command, dir: None });
+            }
+        }
+    }
+    for member_dir in workspace_members(root, &table) {
+        let Some(member_table) = std::fs::read_to_string(member_dir.join("Cargo.toml"))
+            .ok()
+            .and_then(|text| text.parse::<toml::Table>().ok())
+        else {
+            continue;
+        };
+        let member_name = member_table
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(|name| name.as_str())
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| member_dir.file_name().unwrap_or_default().to_string_lossy().to_string());
+        for (name, command) in metadata_scripts(&member_table, "package") {
+            scripts.push(CargoScript {
+                name: format!("{}:{}", member_name, name),
+                command,
+                dir: Some(member_dir.clone()),
+            });
+        }
+    }
+    Ok(scripts)
+}
+
+pub fn list_scripts() -> Result<Vec<CargoScript>, Report<KeeperError>> {
+    let current_dir = std::env::current_dir().change_context(KeeperError::InvalidCargoToml)?;
+    collect_scripts(&current_dir)
+}
+
+/// aliases first, and a script with the same name of an alias is skipped
+pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
+    let aliases = list_aliases();
+    let mut tasks: Vec<Task> = aliases
+        .iter()
+        .map(|alias| task!(alias.name, "cargo-scripts", format!("cargo {}", alias.args.join(" "))))
+        .collect();
+    for script in list_scripts()? {
+        if !aliases.iter().any(|alias| alias.name == script.name) {
+            tasks.push(task!(script.name, "cargo-scripts", script.command));
+        }
+    }
+    Ok(tasks)
+}
+
+pub fn run_task(
+    task: &str,
+    task_args: &[&str],
+    global_args: &[&str],
+    verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    if list_aliases().iter().any(|alias| alias.name == task) {
+        let mut args: Vec<&str> = vec![];
+        args.extend(global_args);
+        args.push(task);
+        args.extend(task_args);
+        return run_command("cargo", &args, verbose);
+    }
+    let script = list_scripts()?
+        .into_iter()
+        .find(|script| script.name == task)
+        .ok_or_else(|| KeeperError::TaskNotFound(task.to_string()).into_report())?;
+    let mut command_line = script.command.clone();
+    if !task_args.is_empty() {
+        command_line.push(' ');
+        command_line.push_str(&shlex::try_join(task_args.to_vec()).unwrap_or(task_args.join(" ")));
+    }
+    let working_dir = script.dir.map(|dir| dir.to_string_lossy().to_string());
+    run_command_by_shell_in_dir(&command_line, &working_dir, verbose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_aliases() {
+        let text = r#"
+[alias]
+b = "build"
+rr = "run --release"
+recursive_example = ["run", "--release", "--example", "recursions"]
+
+[build]
+jobs = 4
+"#;
+        let aliases = parse_aliases(text);
+        assert_eq!(3, aliases.len());
+        let args_of = |name: &str| aliases.iter().find(|alias| alias.name == name).unwrap().args.clone();
+        assert_eq!(vec!["run", "--release"], args_of("rr"));
+        assert_eq!(4, args_of("recursive_example").len());
+    }
+
+    #[test]
+    fn test_config_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let home = temp_dir.path();
+        let project = home.join("demo");
+        for dir in [home.join(".cargo"), project.join(".cargo")] {
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("config.toml"), "[alias]\nb = \"build\"\n").unwrap();
+        }
+        let files = config_files(&project, Some(&home.join(".cargo")));
+        let all_files = config_files(&project, None);
+        assert_eq!(vec![project.join(".cargo").join("config.toml")], files);
+        assert_eq!(2, all_files.len());
+    }
+
+    #[test]
+    fn test_collect_scripts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("crates/core")).unwrap();
+        std::fs::create_dir_all(root.join("crates/legacy")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            r#"
+[workspace]
+members = ["crates/*"]
+exclude = ["crates/legacy"]
+
+[workspace.metadata.scripts]
+lint = "cargo clippy --workspace"
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("crates/core/Cargo.toml"),
+            r#"
+[package]
+name = "demo-core"
+
+[package.metadata.scripts]
+bench = "cargo bench -p demo-core"
+"#,
+        )
+        .unwrap();
+        std::fs::write(root.join("crates/legacy/Cargo.toml"), "[package]\nname = \"legacy\"\n").unwrap();
+        let scripts = collect_scripts(root).unwrap();
+        assert_eq!(2, scripts.len());
+        assert_eq!("lint", scripts[0].name);
+        assert_eq!(None, scripts[0].dir);
+        assert_eq!("demo-core:bench", scripts[1].name);
+        assert_eq!(Some(root.join("crates/core")), scripts[1].dir);
+    }
+}
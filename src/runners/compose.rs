@@ -0,0 +1,293 @@
+//! docker compose: tasks for services of compose.yaml(or docker-compose.yml), such as `up:<service>` and `logs:<service>`,
+//! and one-off commands of the `x-tk-tasks` extension. Tasks are executed by `docker compose` or `podman compose`.
+use crate::command_utils::{capture_command_output, run_command, CommandOutput};
+use crate::errors::KeeperError;
+use crate::models::Task;
+use crate::task;
+use error_stack::{IntoReport, Report, ResultExt};
+use std::env;
+use which::which;
+use yaml_rust2::{Yaml, YamlLoader};
+
+const COMPOSE_FILE_NAMES: [&str; 4] = [
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComposeService {
+    pub name: String,
+    /// image or build context
+    pub image: Option<String>,
+    pub profiles: Vec<String>,
+}
+
+/// one-off command of `x-tk-tasks`, executed by `compose run --rm <service> <command>`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComposeTask {
+    pub name: String,
+    pub service: String,
+    pub command: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComposeFile {
+    pub services: Vec<ComposeService>,
+    pub tasks: Vec<ComposeTask>,
+}
+
+impl ComposeFile {
+    pub fn profiles(&self) -> Vec<String> {
+        let mut profiles: Vec<String> = self
+            .services
+            .iter()
+            .flat_map(|service| service.profiles.clone())
+            .collect();
+        profiles.sort();
+        profiles.dedup();
+        profiles
+    }
+}
+
+pub fn is_available() -> bool {
+    find_compose_file().is_some()
+}
+
+/// the compose sub command is available, and docker may be installed without the compose plugin
+fn is_compose_plugin_available(engine: &str) -> bool {
+    which(engine).is_ok()
+        && capture_command_output(engine, &["compose", "version"])
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+}
+
+/// `docker compose`, `podman compose`, or standalone `docker-compose` and `podman-compose`
+pub fn get_compose_command() -> Option<Vec<&'static str>> {
+    if is_compose_plugin_available("docker") {
+        Some(vec!["docker", "compose"])
+    } else if is_compose_plugin_available("podman") {
+        Some(vec!["podman", "compose"])
+    } else if which("docker-compose").is_ok() {
+        Some(vec!["docker-compose"])
+    } else if which("podman-compose").is_ok() {
+        Some(vec!["podman-compose"])
+    } else {
+        None
+    }
+}
+
+pub fn is_command_available() -> bool {
+    get_compose_command().is_some()
+}
+
+pub fn find_compose_file() -> Option<String> {
+    let current_dir = env::current_dir().ok()?;
+    COMPOSE_FILE_NAMES
+        .iter()
+        .find(|name| current_dir.join(name).exists())
+        .map(|name| name.to_string())
+}
+
+fn yaml_strings(value: &Yaml) -> Vec<String> {
+    match value {
+        Yaml::String(item) => vec![item.clone()],
+        Yaml::Array(items) => items
+            .iter()
+            .filter_map(|item| item.as_str())
+            .map(|item| item.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn parse_compose_yaml(text: &str) -> Option<ComposeFile> {
+    let docs = YamlLoader::load_from_str(text).ok()?;
+    let doc = docs.first()?;
+    let mut compose_file = ComposeFile::default();
+    if let Some(services) = doc["services"].as_hash() {
+        for (name, service) in services {
+            let Some(name) = name.as_str() else {
+                continue;
+            };
+            let image = service["image"].as_str().map(|image| image.to_string()).or_else(|| {
+                // `build: ./app` or `build: {context: ./app}`
+                service["build"]
+                    .as_str()
+                    .or(service["build"]["context"].as_str())
+                    .map(|context| format!("build {}", context))
+            });
+            compose_file.services.push(ComposeService {
+                name: name.to_string(),
+                image,
+                profiles: yaml_strings(&service["profiles"]),
+            });
+        }
+    }
+    if let Some(tasks) = doc["x-tk-tasks"].as_hash() {
+        for (name, options) in tasks {
+            let (Some(name), Some(service)) = (name.as_str(), options["service"].as_str()) else {
+                continue;
+            };
+            let command = match &options["command"] {
+                Yaml::String(command) => command.clone(),
+                // `command: ["sh", "-c", "..."]` is quoted for the shell
+                command => {
+                    let items = yaml_strings(command);
+                    shlex::try_join(items.iter().map(|item| item.as_str())).unwrap_or(items.join(" "))
+                }
+            };
+            compose_file.tasks.push(ComposeTask {
+                name: name.to_string(),
+                service: service.to_string(),
+                description: options["description"]
+                    .as_str()
+                    .map(|description| description.to_string())
+                    .unwrap_or_else(|| format!("run --rm {} {}", service, command).trim().to_string()),
+                command,
+            });
+        }
+    }
+    Some(compose_file)
+}
+
+pub fn read_compose_file() -> Result<ComposeFile, Report<KeeperError>> {
+    let file_name = find_compose_file().ok_or(KeeperError::InvalidComposeFile("compose.yaml".to_string()))?;
+    let text = std::fs::read_to_string(&file_name)
+        .change_context(KeeperError::InvalidComposeFile(file_name.clone()))?;
+    parse_compose_yaml(&text).ok_or_else(|| KeeperError::InvalidComposeFile(file_name).into_report())
+}
+
+pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
+    let compose_file = read_compose_file()?;
+    // `compose:` prefix leaves `tk up` and `tk logs` to other runners and managers
+    let mut tasks: Vec<Task> = vec![
+        task!("compose:up", "compose", "start services without profiles in background"),
+        task!("compose:down", "compose", "stop and remove containers and networks"),
+        task!("compose:ps", "compose", "list containers"),
+        task!("compose:logs", "compose", "follow logs of all services"),
+    ];
+    for service in &compose_file.services {
+        let mut description = format!("start {}", service.name);
+        if let Some(image) = &service.image {
+            description.push_str(&format!("({})", image));
+        }
+        if !service.profiles.is_empty() {
+            description.push_str(&format!(" [profiles: {}]", service.profiles.join(", ")));
+        }
+        tasks.push(task!(format!("up:{}", service.name), "compose", description));
+        tasks.push(task!(
+            format!("logs:{}", service.name),
+            "compose",
+            format!("follow logs of {}", service.name)
+        ));
+    }
+    for profile in compose_file.profiles() {
+        tasks.push(task!(
+            format!("profile:{}", profile),
+            "compose",
+            format!("start services with profile {}", profile)
+        ));
+    }
+    for compose_task in &compose_file.tasks {
+        tasks.push(task!(compose_task.name, "compose", compose_task.description));
+    }
+    Ok(tasks)
+}
+
+/// arguments of compose for the task, and the command of one-off task is split as shell words
+fn get_task_args(task: &str, compose_file: &ComposeFile) -> Option<Vec<String>> {
+    let strings = |items: &[&str]| items.iter().map(|item| item.to_string()).collect::<Vec<String>>();
+    if let Some(compose_task) = compose_file.tasks.iter().find(|item| item.name == task) {
+        let mut args = strings(&["run", "--rm", &compose_task.service]);
+        args.extend(shlex::split(&compose_task.command).unwrap_or_default());
+        return Some(args);
+    }
+    match task.split_once(':') {
+        Some(("compose", "up")) => Some(strings(&["up", "-d"])),
+        Some(("compose", command @ ("down" | "ps"))) => Some(strings(&[command])),
+        Some(("compose", "logs")) => Some(strings(&["logs", "-f"])),
+        Some(("up", service)) => Some(strings(&["up", "-d", service])),
+        Some(("logs", service)) => Some(strings(&["logs", "-f", service])),
+        Some(("profile", profile)) => Some(strings(&["--profile", profile, "up", "-d"])),
+        _ => None,
+    }
+}
+
+pub fn run_task(
+    task: &str,
+    task_args: &[&str],
+    global_args: &[&str],
+    verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    let compose_file = read_compose_file()?;
+    let compose_args =
+        get_task_args(task, &compose_file).ok_or_else(|| KeeperError::TaskNotFound(task.to_string()))?;
+    let command = get_compose_command()
+        .ok_or_else(|| KeeperError::CommandNotFound("docker compose".to_string()))?;
+    let mut args: Vec<&str> = command[1..].to_vec();
+    args.extend(global_args);
+    args.extend(compose_args.iter().map(|arg| arg.as_str()));
+    args.extend(task_args);
+    run_command(command[0], &args, verbose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPOSE_YAML: &str = r#"
+services:
+  db:
+    image: postgres:17
+  app:
+    build:
+      context: ./app
+  debug:
+    image: busybox
+    profiles: ["debug", "tools"]
+
+x-tk-tasks:
+  migrate:
+    service: app
+    command: ./manage.py migrate --noinput
+    description: Run database migrations
+  psql:
+    service: db
+    command: ["psql", "-U", "postgres"]
+"#;
+
+    #[test]
+    fn test_parse_compose_yaml() {
+        let compose_file = parse_compose_yaml(COMPOSE_YAML).unwrap();
+        let names: Vec<&str> = compose_file.services.iter().map(|service| service.name.as_str()).collect();
+        assert_eq!(vec!["db", "app", "debug"], names);
+        assert_eq!(Some("build ./app".to_string()), compose_file.services[1].image);
+        assert_eq!(vec!["debug", "tools"], compose_file.profiles());
+        assert_eq!(2, compose_file.tasks.len());
+        assert_eq!("Run database migrations", compose_file.tasks[0].description);
+        assert_eq!("run --rm db psql -U postgres", compose_file.tasks[1].description);
+    }
+
+    #[test]
+    fn test_get_task_args() {
+        let compose_file = parse_compose_yaml(COMPOSE_YAML).unwrap();
+        assert_eq!(
+            Some(["run", "--rm", "app", "./manage.py", "migrate", "--noinput"]
+                .iter()
+                .map(|item| item.to_string())
+                .collect()),
+            get_task_args("migrate", &compose_file)
+        );
+        assert_eq!(Some(vec!["up".to_string(), "-d".to_string(), "db".to_string()]), get_task_args("up:db", &compose_file));
+        assert_eq!(
+            Some(vec!["--profile".to_string(), "debug".to_string(), "up".to_string(), "-d".to_string()]),
+            get_task_args("profile:debug", &compose_file)
+        );
+        assert_eq!(Some(vec!["ps".to_string()]), get_task_args("compose:ps", &compose_file));
+        assert_eq!(None, get_task_args("up", &compose_file));
+        assert_eq!(None, get_task_args("restart", &compose_file));
+    }
+}
@@ -0,0 +1,370 @@
+//! Earthly: targets of Earthfile with doc comments and `ARG` declarations, executed by `earthly +target --ARG=value`.
+//! Dagger: functions of the module in dagger.json from `dagger functions`, named as `dagger:<function>` and executed by `dagger call`.
+use crate::command_utils::{capture_command_output, run_command, CommandOutput};
+use crate::common::devshell::hashed_cache_file;
+use crate::errors::KeeperError;
+use crate::models::Task;
+use crate::task;
+use error_stack::{IntoReport, Report, ResultExt};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use which::which;
+
+/// generated code and dependencies skipped to hash the source of the Dagger module
+const DAGGER_GENERATED_FILES: [&str; 4] = ["internal", "sdk", "node_modules", "dagger.gen.go"];
+/// empty functions, such as the failure of `dagger functions` when the engine is not running, are retried after it
+const EMPTY_FUNCTIONS_CACHE_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EarthlyArg {
+    pub name: String,
+    pub default_value: Option<String>,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EarthlyTarget {
+    pub name: String,
+    pub doc: String,
+    pub args: Vec<EarthlyArg>,
+}
+
+impl EarthlyTarget {
+    /// doc comment and ARGs as parameters, such as `build the app (VERSION!, GOOS=linux)`
+    pub fn description(&self) -> String {
+        if self.args.is_empty() {
+            return self.doc.clone();
+        }
+        let params: Vec<String> = self
+            .args
+            .iter()
+            .map(|arg| match (&arg.default_value, arg.required) {
+                (_, true) => format!("{}!", arg.name),
+                (Some(value), _) => format!("{}={}", arg.name, value),
+                (None, _) => arg.name.clone(),
+            })
+            .collect();
+        format!("{} ({})", self.doc, params.join(", ")).trim().to_string()
+    }
+}
+
+pub fn is_available() -> bool {
+    has_earthfile() || has_dagger_module()
+}
+
+pub fn has_earthfile() -> bool {
+    std::env::current_dir()
+        .map(|dir| dir.join("Earthfile").exists())
+        .unwrap_or(false)
+}
+
+pub fn has_dagger_module() -> bool {
+    std::env::current_dir()
+        .map(|dir| dir.join("dagger.json").exists())
+        .unwrap_or(false)
+}
+
+pub fn is_earthly_available() -> bool {
+    which("earthly").is_ok()
+}
+
+pub fn is_dagger_available() -> bool {
+    which("dagger").is_ok()
+}
+
+/// `ARG [--required] [--global] NAME[=default]`
+fn parse_arg(line: &str) -> Option<EarthlyArg> {
+    let mut required = false;
+    let mut declaration = None;
+    for word in shlex::split(line.strip_prefix("ARG")?)? {
+        if word == "--required" {
+            required = true;
+        } else if !word.starts_with("--") {
+            declaration = Some(word);
+            break;
+        }
+    }
+    let declaration = declaration?;
+    let (name, default_value) = match declaration.split_once('=') {
+        Some((name, value)) => (name.to_string(), Some(value.to_string())),
+        None => (declaration, None),
+    };
+    Some(EarthlyArg {
+        name,
+        default_value,
+        required,
+    })
+}
+
+/// a target is a lowercase `name:` line at the top level, and `FUNCTION`(or `COMMAND`) blocks are skipped
+fn parse_earthfile(text: &str) -> Vec<EarthlyTarget> {
+    let mut targets: Vec<EarthlyTarget> = vec![];
+    let mut comments: Vec<String> = vec![];
+    let mut current: Option<EarthlyTarget> = None;
+    let mut is_function = false;
+    let mut first_line = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            // the doc comment is right above the target
+            comments.clear();
+        } else if !line.starts_with([' ', '\t']) {
+            if let Some(comment) = trimmed.strip_prefix('#') {
+                comments.push(comment.trim().to_string());
+                continue;
+            }
+            if let Some(target) = current.take()
+                && !is_function
+            {
+                targets.push(target);
+            }
+            if let Some(name) = trimmed.strip_suffix(':')
+                && name.starts_with(|c: char| c.is_ascii_lowercase())
+                && !name.contains(' ')
+            {
+                // the doc comment starts with the target name by the convention of earthly docs
+                let doc = comments.join(" ");
+                current = Some(EarthlyTarget {
+                    name: name.to_string(),
+                    doc: doc.strip_prefix(name).map(|doc| doc.trim().to_string()).unwrap_or(doc),
+                    args: vec![],
+                });
+                is_function = false;
+                first_line = true;
+            }
+            comments.clear();
+        } else if let Some(target) = current.as_mut() {
+            if trimmed.starts_with('#') {
+                continue;
+            }
+            if first_line && (trimmed == "FUNCTION" || trimmed == "COMMAND") {
+                is_function = true;
+            }
+            first_line = false;
+            if let Some(arg) = parse_arg(trimmed) {
+                target.args.push(arg);
+            }
+        }
+    }
+    if let Some(target) = current
+        && !is_function
+    {
+        targets.push(target);
+    }
+    targets
+}
+
+pub fn list_targets() -> Result<Vec<EarthlyTarget>, Report<KeeperError>> {
+    let text = std::fs::read_to_string("Earthfile").change_context(KeeperError::InvalidEarthfile)?;
+    Ok(parse_earthfile(&text))
+}
+
+/// `Name  Description` table of `dagger functions`
+fn parse_dagger_functions(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .skip_while(|line| !line.starts_with("Name"))
+        .skip(1)
+        .map(|line| match line.split_once(char::is_whitespace) {
+            Some((name, description)) => (name.to_string(), description.trim().to_string()),
+            None => (line.to_string(), String::new()),
+        })
+        .collect()
+}
+
+/// dagger.json and files of the module source, and generated code is skipped
+fn dagger_source_files() -> Vec<String> {
+    let source = std::fs::read_to_string("dagger.json")
+        .ok()
+        .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+        .and_then(|json| json["source"].as_str().map(|source| source.to_string()))
+        .unwrap_or(".".to_string());
+    let mut files = vec!["dagger.json".to_string()];
+    let mut pending = vec![PathBuf::from(&source)];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if name.starts_with('.') || DAGGER_GENERATED_FILES.contains(&name.as_str()) {
+                continue;
+            }
+            if path.is_dir() {
+                // the module at the project root shares the directory with the project, and only its files are hashed
+                if source != "." {
+                    pending.push(path);
+                }
+            } else {
+                files.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// functions of the Dagger module, cached by the hash of dagger.json and the module source,
+/// and empty functions or failures are cached for a short time only
+pub fn list_dagger_functions() -> Vec<(String, String)> {
+    let source_files = dagger_source_files();
+    let file_names: Vec<&str> = source_files.iter().map(|name| name.as_str()).collect();
+    let cache_file = hashed_cache_file("dagger", "functions", &file_names);
+    if let Some(cache_file) = &cache_file
+        && let Ok(json) = std::fs::read_to_string(cache_file)
+        && let Ok(functions) = serde_json::from_str::<Vec<(String, String)>>(&json)
+        && (!functions.is_empty() || is_fresh(cache_file, EMPTY_FUNCTIONS_CACHE_TTL))
+    {
+        return functions;
+    }
+    let functions = match capture_command_output("dagger", &["functions"]) {
+        Ok(output) if output.status.success() => parse_dagger_functions(&String::from_utf8_lossy(&output.stdout)),
+        _ => vec![],
+    };
+    if let Some(cache_file) = &cache_file
+        && let Some(parent) = cache_file.parent()
+        && std::fs::create_dir_all(parent).is_ok()
+        && let Ok(json) = serde_json::to_string(&functions)
+    {
+        std::fs::write(cache_file, json).ok();
+    }
+    functions
+}
+
+fn is_fresh(file: &Path, ttl: Duration) -> bool {
+    std::fs::metadata(file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|elapsed| elapsed < ttl)
+}
+
+pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
+    let mut tasks = vec![];
+    if has_earthfile() {
+        for target in list_targets()? {
+            tasks.push(task!(target.name, "earthly", target.description()));
+        }
+    }
+    if has_dagger_module() && is_dagger_available() {
+        for (name, description) in list_dagger_functions() {
+            tasks.push(task!(format!("dagger:{}", name), "earthly", description));
+        }
+    }
+    Ok(tasks)
+}
+
+/// task options as build args: `--VERSION 1.0` to `--VERSION=1.0`
+fn to_build_args(task_args: &[&str]) -> Vec<String> {
+    let mut build_args = vec![];
+    let mut index = 0;
+    while index < task_args.len() {
+        let arg = task_args[index];
+        if arg.starts_with("--")
+            && !arg.contains('=')
+            && let Some(value) = task_args.get(index + 1)
+            && !value.starts_with("--")
+        {
+            build_args.push(format!("{}={}", arg, value));
+            index += 2;
+        } else {
+            build_args.push(arg.to_string());
+            index += 1;
+        }
+    }
+    build_args
+}
+
+pub fn run_task(
+    task: &str,
+    task_args: &[&str],
+    global_args: &[&str],
+    verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    if let Some(function) = task.strip_prefix("dagger:") {
+        let mut args = vec![];
+        args.extend(global_args);
+        args.push("call");
+        args.push(function);
+        args.extend(task_args);
+        return run_command("dagger", &args, verbose);
+    }
+    if !list_targets()?.iter().any(|target| target.name == task) {
+        return Err(KeeperError::TaskNotFound(task.to_string()).into_report());
+    }
+    let target = format!("+{}", task);
+    let build_args = to_build_args(task_args);
+    let mut args = vec![];
+    args.extend(global_args);
+    args.push(target.as_str());
+    args.extend(build_args.iter().map(|arg| arg.as_str()));
+    run_command("earthly", &args, verbose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_earthfile() {
+        let text = r#"VERSION 0.8
+FROM golang:1.23
+ARG --global REGISTRY=ghcr.io
+WORKDIR /app
+
+# deps downloads the go modules
+deps:
+    COPY go.mod go.sum ./
+    RUN go mod download
+
+# build compiles the binary
+# for the target platform
+build:
+    FROM +deps
+    ARG --required VERSION
+    ARG GOOS=linux
+    ARG GOARCH
+    RUN go build -ldflags "-X main.version=$VERSION" -o out/app
+
+# INSTALL_DEPS installs the system packages
+INSTALL_DEPS:
+    FUNCTION
+    RUN apk add git
+
+docker:
+    FROM +build
+    SAVE IMAGE --push $REGISTRY/app:latest
+"#;
+        let targets = parse_earthfile(text);
+        let names: Vec<&str> = targets.iter().map(|target| target.name.as_str()).collect();
+        assert_eq!(vec!["deps", "build", "docker"], names);
+        assert_eq!("downloads the go modules", targets[0].description());
+        assert_eq!(
+            "compiles the binary for the target platform (VERSION!, GOOS=linux, GOARCH)",
+            targets[1].description()
+        );
+        assert_eq!("", targets[2].description());
+    }
+
+    #[test]
+    fn test_parse_dagger_functions() {
+        let text = "Name          Description\nbuild         Build the application container\nlint          -\ntest\n";
+        assert_eq!(
+            vec![
+                ("build".to_string(), "Build the application container".to_string()),
+                ("lint".to_string(), "-".to_string()),
+                ("test".to_string(), "".to_string())
+            ],
+            parse_dagger_functions(text)
+        );
+    }
+
+    #[test]
+    fn test_to_build_args() {
+        assert_eq!(
+            vec!["--VERSION=1.0", "--GOOS=darwin", "--push"],
+            to_build_args(&["--VERSION", "1.0", "--GOOS=darwin", "--push"])
+        );
+    }
+}
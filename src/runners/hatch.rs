@@ -0,0 +1,127 @@
+//! Hatch: scripts of `[tool.hatch.envs.<env>.scripts]` in pyproject.toml or `[envs.<env>.scripts]` in hatch.toml,
+//! executed by `hatch run`. Scripts of the `default` env are named as `script`, and others as `env:script`.
+use crate::command_utils::{run_command, CommandOutput};
+use crate::common::pyproject::{get_uv_tool_path, HatchEnv, PyProjectToml};
+use crate::common::pyproject_toml_has_tool;
+use crate::errors::KeeperError;
+use crate::models::Task;
+use crate::runners::uv_scripts::Script;
+use crate::task;
+use error_stack::Report;
+use std::collections::HashMap;
+use which::which;
+
+pub fn is_available() -> bool {
+    pyproject_toml_has_tool("hatch.envs")
+        || std::env::current_dir()
+            .map(|dir| dir.join("hatch.toml").exists())
+            .unwrap_or(false)
+}
+
+pub fn is_command_available() -> bool {
+    get_uv_tool_path("hatch").is_some() || which("hatch").is_ok()
+}
+
+pub fn install() -> Result<CommandOutput, Report<KeeperError>> {
+    run_command("uv", &["tool", "install", "hatch"], true)
+}
+
+/// a script is a command, or a list of commands executed in sequence, with `env-vars` of the env
+fn hatch_script(value: &toml::Value, env_vars: &HashMap<String, String>) -> Option<Script> {
+    match value {
+        toml::Value::String(command) => Some(Script::Cmd(shlex::split(command)?, env_vars.clone(), None)),
+        toml::Value::Array(commands) => Some(Script::Chain(
+            commands
+                .iter()
+                .filter_map(|command| command.as_str())
+                .map(|command| vec![command.to_string()])
+                .collect(),
+        )),
+        _ => None,
+    }
+}
+
+fn parse_hatch_scripts(envs: &HashMap<String, HatchEnv>) -> Vec<(String, Script)> {
+    let mut scripts: Vec<(String, Script)> = vec![];
+    for (env_name, env) in envs {
+        let env_vars = env.env_vars.clone().unwrap_or_default();
+        for (name, value) in env.scripts.iter().flatten() {
+            if let Some(script) = hatch_script(value, &env_vars) {
+                let task_name = if env_name == "default" {
+                    name.clone()
+                } else {
+                    format!("{}:{}", env_name, name)
+                };
+                scripts.push((task_name, script));
+            }
+        }
+    }
+    scripts.sort_by(|a, b| a.0.cmp(&b.0));
+    scripts
+}
+
+pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
+    let envs = PyProjectToml::get_default_project()
+        .map(|pyproject| pyproject.get_hatch_envs())
+        .unwrap_or_default();
+    Ok(parse_hatch_scripts(&envs)
+        .iter()
+        .map(|(name, script)| task!(name, "hatch", script.description()))
+        .collect())
+}
+
+pub fn run_task(
+    task: &str,
+    task_args: &[&str],
+    global_args: &[&str],
+    verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    let mut args = vec![];
+    args.extend(global_args);
+    args.push("run");
+    args.push(task);
+    args.extend(task_args);
+    if let Some(hatch) = get_uv_tool_path("hatch") {
+        run_command(&hatch, &args, verbose)
+    } else {
+        run_command("hatch", &args, verbose)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hatch_scripts() {
+        let text = r#"
+[tool.hatch.envs.default.scripts]
+test = "pytest {args:tests}"
+cov = ["test-cov", "cov-report"]
+
+[tool.hatch.envs.docs]
+env-vars = { MKDOCS_STRICT = "true" }
+
+[tool.hatch.envs.docs.scripts]
+build = "mkdocs build --clean"
+
+[tool.hatch.envs.lint]
+dependencies = ["ruff"]
+"#;
+        let pyproject: PyProjectToml = toml::from_str(text).unwrap();
+        let scripts = parse_hatch_scripts(&pyproject.get_hatch_envs());
+        let descriptions: Vec<(&str, String)> = scripts
+            .iter()
+            .map(|(name, script)| (name.as_str(), script.description()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("cov", "test-cov && cov-report".to_string()),
+                ("docs:build", "mkdocs build --clean".to_string()),
+                ("test", "pytest {args:tests}".to_string())
+            ],
+            descriptions
+        );
+        assert!(matches!(&scripts[1].1, Script::Cmd(_, env_vars, _) if env_vars["MKDOCS_STRICT"] == "true"));
+    }
+}
@@ -0,0 +1,778 @@
+//! JetBrains run configurations: shared `.run/*.run.xml` files and `.idea/runConfigurations/*.xml` files.
+//! Shell script, npm, Gradle, Maven, Application, Cargo and Python configurations are converted to command lines.
+use crate::command_utils::{is_command_available, run_command_with_env_vars, CommandOutput};
+use crate::errors::KeeperError;
+use crate::models::Task;
+use crate::task;
+use colored::Colorize;
+use error_stack::{IntoReport, Report};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// root element of the file: `<component name="ProjectRunConfigurationManager">`
+#[derive(Deserialize, Debug, Default)]
+struct RunConfigurationComponent {
+    #[serde(default)]
+    configuration: Vec<RunConfiguration>,
+}
+
+/// children of the configuration are interleaved, such as `<option>` items around `<module>` and `<envs>`
+#[derive(Deserialize, Debug, Default)]
+struct RunConfiguration {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@type")]
+    type_value: String,
+    #[serde(rename = "#content", default)]
+    children: Vec<ConfigurationChild>,
+}
+
+#[derive(Deserialize, Debug)]
+enum ConfigurationChild {
+    #[serde(rename = "option")]
+    Option(XmlOption),
+    #[serde(rename = "module")]
+    Module(XmlNamed),
+    #[serde(rename = "envs")]
+    Envs(XmlEnvs),
+    // Gradle
+    #[serde(rename = "ExternalSystemSettings")]
+    ExternalSystemSettings(XmlOptions),
+    // Maven
+    #[serde(rename = "MavenSettings")]
+    MavenSettings(XmlOptions),
+    // npm
+    #[serde(rename = "package-json")]
+    PackageJson(XmlValue),
+    #[serde(rename = "package-manager")]
+    PackageManager(XmlValue),
+    #[serde(rename = "command")]
+    Command(XmlValue),
+    #[serde(rename = "scripts")]
+    Scripts(XmlScripts),
+    #[serde(rename = "arguments")]
+    Arguments(XmlValue),
+    #[serde(other)]
+    Other,
+}
+
+/// `<option name="..." value="..."/>`, and the value may be a nested list, set, map or settings
+#[derive(Deserialize, Debug, Default)]
+struct XmlOption {
+    #[serde(rename = "@name")]
+    name: Option<String>,
+    #[serde(rename = "@value")]
+    value: Option<String>,
+    list: Option<XmlOptions>,
+    set: Option<XmlOptions>,
+    map: Option<XmlMap>,
+    #[serde(rename = "MavenRunnerParameters")]
+    maven_runner_parameters: Option<XmlOptions>,
+    #[serde(rename = "MavenRunnerSettings")]
+    maven_runner_settings: Option<XmlOptions>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct XmlOptions {
+    #[serde(default)]
+    option: Vec<XmlOption>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct XmlMap {
+    #[serde(default)]
+    entry: Vec<XmlEntry>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct XmlEntry {
+    #[serde(rename = "@key")]
+    key: String,
+    #[serde(rename = "@value")]
+    value: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct XmlEnvs {
+    #[serde(default)]
+    env: Vec<XmlEnv>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct XmlEnv {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@value")]
+    value: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct XmlScripts {
+    #[serde(default)]
+    script: Vec<XmlValue>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct XmlNamed {
+    #[serde(rename = "@name")]
+    name: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct XmlValue {
+    #[serde(rename = "@value")]
+    value: Option<String>,
+}
+
+/// command line converted from a run configuration
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    pub working_dir: Option<String>,
+    pub env_vars: HashMap<String, String>,
+}
+
+impl RunCommand {
+    pub fn command_line(&self) -> String {
+        let mut items = vec![self.program.as_str()];
+        items.extend(self.args.iter().map(|arg| arg.as_str()));
+        shlex::try_join(items.clone()).unwrap_or(items.join(" "))
+    }
+}
+
+fn find_option<'a>(options: &'a [XmlOption], name: &str) -> Option<&'a XmlOption> {
+    options.iter().find(|option| option.name.as_deref() == Some(name))
+}
+
+/// value of the option, and empty values are ignored
+fn option_value(options: &[XmlOption], name: &str) -> Option<String> {
+    find_option(options, name)
+        .and_then(|option| option.value.clone())
+        .filter(|value| !value.is_empty())
+}
+
+/// values of `<list>` or `<set>` items of the option
+fn option_values(options: &[XmlOption], name: &str) -> Vec<String> {
+    find_option(options, name)
+        .and_then(|option| option.list.as_ref().or(option.set.as_ref()))
+        .map(|items| items.option.iter().filter_map(|item| item.value.clone()).collect())
+        .unwrap_or_default()
+}
+
+fn option_map(options: &[XmlOption], name: &str) -> HashMap<String, String> {
+    find_option(options, name)
+        .and_then(|option| option.map.as_ref())
+        .map(|map| {
+            map.entry
+                .iter()
+                .map(|entry| (entry.key.clone(), entry.value.clone().unwrap_or_default()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn split_words(text: Option<String>) -> Vec<String> {
+    text.and_then(|text| shlex::split(&text)).unwrap_or_default()
+}
+
+/// `$PROJECT_DIR$`, `$USER_HOME$` and module macros(`$MODULE_WORKING_DIR$`, `$MODULE_DIR$` and `$ContentRoot$`),
+/// and the `file://` prefix of paths
+fn expand_macros(text: &str, project_dir: &Path, module_dir: &Path) -> String {
+    let module_dir = module_dir.to_string_lossy();
+    let mut text = text
+        .trim_start_matches("file://")
+        .replace("$PROJECT_DIR$", &project_dir.to_string_lossy())
+        .replace("$MODULE_WORKING_DIR$", &module_dir)
+        .replace("$MODULE_DIR$", &module_dir)
+        .replace("$ContentRoot$", &module_dir);
+    if let Some(home_dir) = dirs::home_dir() {
+        text = text.replace("$USER_HOME$", &home_dir.to_string_lossy());
+    }
+    text
+}
+
+/// text with unresolved macros, such as `$FileDir$` of the file in the editor
+fn has_macro(text: &str) -> bool {
+    regex::Regex::new(r"\$\w+\$").unwrap().is_match(text)
+}
+
+impl RunConfiguration {
+    fn formatted_name(&self) -> String {
+        str::replace(&self.name, " ", "-")
+    }
+
+    fn child<T>(&self, select: impl Fn(&ConfigurationChild) -> Option<&T>) -> Option<&T> {
+        self.children.iter().find_map(select)
+    }
+
+    /// value of a child element with the `value` attribute, such as `<command value="run"/>` of npm
+    fn child_value(&self, select: impl Fn(&ConfigurationChild) -> Option<&XmlValue>) -> Option<String> {
+        self.child(select).and_then(|element| element.value.clone())
+    }
+
+    fn value(&self, name: &str) -> Option<String> {
+        self.child(|child| match child {
+            ConfigurationChild::Option(option) if option.name.as_deref() == Some(name) => Some(option),
+            _ => None,
+        })
+        .and_then(|option| option.value.clone())
+        .filter(|value| !value.is_empty())
+    }
+
+    /// directory of the `<module>`, and the project directory without the module
+    fn module_dir(&self, project_dir: &Path) -> PathBuf {
+        self.child(|child| match child {
+            ConfigurationChild::Module(module) => Some(module),
+            _ => None,
+        })
+        .map(|module| module_dir(&module.name, project_dir))
+        .unwrap_or(project_dir.to_path_buf())
+    }
+
+    fn envs(&self) -> HashMap<String, String> {
+        self.child(|child| match child {
+            ConfigurationChild::Envs(envs) => Some(envs),
+            _ => None,
+        })
+        .map(|envs| {
+            envs.env
+                .iter()
+                .map(|env| (env.name.clone(), env.value.clone().unwrap_or_default()))
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+
+    fn to_run_command(&self, project_dir: &Path) -> Option<RunCommand> {
+        let mut run_command = match self.type_value.as_str() {
+            "ShConfigurationType" => self.sh_command(),
+            "js.build_tools.npm" => self.npm_command(project_dir),
+            "GradleRunConfiguration" => self.gradle_command(),
+            "MavenRunConfiguration" => self.maven_command(),
+            "Application" => self.application_command(project_dir),
+            "CargoCommandRunConfiguration" => self.cargo_command(),
+            "PythonConfigurationType" => self.python_command(project_dir),
+            _ => None,
+        }?;
+        let module_dir = self.module_dir(project_dir);
+        run_command.args = run_command
+            .args
+            .iter()
+            .map(|arg| expand_macros(arg, project_dir, &module_dir))
+            .collect();
+        // the current directory is used for the working directory with unresolved macros
+        run_command.working_dir = run_command
+            .working_dir
+            .map(|dir| expand_macros(&dir, project_dir, &module_dir))
+            .filter(|dir| !dir.is_empty() && !has_macro(dir));
+        Some(run_command)
+    }
+
+    /// script text by `sh -c`, or the script file with the interpreter
+    fn sh_command(&self) -> Option<RunCommand> {
+        let interpreter = self.value("INTERPRETER_PATH").unwrap_or("sh".to_owned());
+        let mut args = split_words(self.value("INTERPRETER_OPTIONS"));
+        let script_text = self.value("SCRIPT_TEXT");
+        if script_text.is_some() && self.value("EXECUTE_SCRIPT_FILE").as_deref() != Some("true") {
+            args.push("-c".to_owned());
+            args.push(script_text?);
+        } else {
+            args.push(self.value("SCRIPT_PATH")?);
+            args.extend(split_words(self.value("SCRIPT_OPTIONS")));
+        }
+        Some(RunCommand {
+            program: interpreter,
+            args,
+            working_dir: self.value("SCRIPT_WORKING_DIRECTORY"),
+            env_vars: self.envs(),
+        })
+    }
+
+    fn npm_command(&self, project_dir: &Path) -> Option<RunCommand> {
+        let working_dir = self
+            .child_value(|child| match child {
+                ConfigurationChild::PackageJson(package_json) => Some(package_json),
+                _ => None,
+            })
+            .map(|path| expand_macros(&path, project_dir, project_dir))
+            .and_then(|path| Path::new(&path).parent().map(|dir| dir.to_string_lossy().to_string()));
+        let program = self
+            .child_value(|child| match child {
+                ConfigurationChild::PackageManager(package_manager) => Some(package_manager),
+                _ => None,
+            })
+            .and_then(|path| Path::new(&path).file_stem().map(|name| name.to_string_lossy().to_string()))
+            .filter(|name| ["npm", "pnpm", "yarn", "bun"].contains(&name.as_str()))
+            .unwrap_or_else(|| {
+                let package_json = crate::common::parse_package_json().unwrap_or_default();
+                crate::common::get_npm_command(&package_json).to_owned()
+            });
+        let command = self.child_value(|child| match child {
+            ConfigurationChild::Command(command) => Some(command),
+            _ => None,
+        });
+        let mut args = vec![command.unwrap_or("run".to_owned())];
+        if let Some(scripts) = self.child(|child| match child {
+            ConfigurationChild::Scripts(scripts) => Some(scripts),
+            _ => None,
+        }) {
+            args.extend(scripts.script.iter().filter_map(|script| script.value.clone()));
+        }
+        args.extend(split_words(self.child_value(|child| match child {
+            ConfigurationChild::Arguments(arguments) => Some(arguments),
+            _ => None,
+        })));
+        Some(RunCommand {
+            program,
+            args,
+            working_dir,
+            env_vars: self.envs(),
+        })
+    }
+
+    fn gradle_command(&self) -> Option<RunCommand> {
+        let options = &self
+            .child(|child| match child {
+                ConfigurationChild::ExternalSystemSettings(settings) => Some(settings),
+                _ => None,
+            })?
+            .option;
+        let mut args = option_values(options, "taskNames");
+        args.extend(split_words(option_value(options, "scriptParameters")));
+        Some(RunCommand {
+            program: crate::managers::gradle::get_gradle_command().to_owned(),
+            args,
+            working_dir: option_value(options, "externalProjectPath"),
+            env_vars: option_map(options, "env"),
+        })
+    }
+
+    fn maven_command(&self) -> Option<RunCommand> {
+        let settings = &self
+            .child(|child| match child {
+                ConfigurationChild::MavenSettings(settings) => Some(settings),
+                _ => None,
+            })?
+            .option;
+        let parameters = &find_option(settings, "myRunnerParameters")?
+            .maven_runner_parameters
+            .as_ref()?
+            .option;
+        let mut args = option_values(parameters, "goals");
+        let profiles = option_values(parameters, "profiles");
+        if !profiles.is_empty() {
+            args.push(format!("-P{}", profiles.join(",")));
+        }
+        let env_vars = find_option(settings, "myRunnerSettings")
+            .and_then(|option| option.maven_runner_settings.as_ref())
+            .map(|runner_settings| option_map(&runner_settings.option, "environmentProperties"))
+            .unwrap_or_default();
+        Some(RunCommand {
+            program: crate::managers::maven::get_mvn_command().to_owned(),
+            args,
+            working_dir: option_value(parameters, "workingDirPath"),
+            env_vars,
+        })
+    }
+
+    /// main class by `mvn exec:java` with VM parameters of `MAVEN_OPTS` for Maven projects,
+    /// or by the `run` task of the Gradle application plugin with `-PmainClass`, and the build script should
+    /// read the property, such as `mainClass = providers.gradleProperty("mainClass").orElse("...")`.
+    /// Configurations of other projects are left out, because the classpath of dependencies can't be resolved.
+    fn application_command(&self, project_dir: &Path) -> Option<RunCommand> {
+        let main_class = self.value("MAIN_CLASS_NAME")?;
+        let module_dir = self.module_dir(project_dir);
+        let module_path = module_dir
+            .strip_prefix(project_dir)
+            .unwrap_or(&module_dir)
+            .to_string_lossy()
+            .to_string();
+        let vm_parameters = self.value("VM_PARAMETERS");
+        let program_parameters = self.value("PROGRAM_PARAMETERS");
+        let mut env_vars = self.envs();
+        let (program, args) = if project_dir.join("pom.xml").exists() {
+            let mut args = vec![];
+            if !module_path.is_empty() {
+                args.push("-pl".to_owned());
+                args.push(module_path);
+            }
+            args.push("compile".to_owned());
+            args.push("exec:java".to_owned());
+            args.push(format!("-Dexec.mainClass={}", main_class));
+            if let Some(program_parameters) = program_parameters {
+                args.push(format!("-Dexec.args={}", program_parameters));
+            }
+            // exec:java runs the main class in the JVM of Maven
+            if let Some(vm_parameters) = vm_parameters {
+                let maven_opts = env_vars
+                    .get("MAVEN_OPTS")
+                    .cloned()
+                    .or_else(|| std::env::var("MAVEN_OPTS").ok())
+                    .filter(|opts| !opts.is_empty());
+                let maven_opts = match maven_opts {
+                    Some(opts) => format!("{} {}", opts, vm_parameters),
+                    None => vm_parameters,
+                };
+                env_vars.insert("MAVEN_OPTS".to_owned(), maven_opts);
+            }
+            (crate::managers::maven::get_mvn_command().to_owned(), args)
+        } else if ["build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts"]
+            .iter()
+            .any(|name| project_dir.join(name).exists())
+        {
+            // JVM arguments of the run task are declared by the build script only
+            if vm_parameters.is_some() {
+                return None;
+            }
+            let run_task = if module_path.is_empty() {
+                "run".to_owned()
+            } else {
+                format!(":{}:run", module_path.replace(['/', '\\'], ":"))
+            };
+            let mut args = vec![run_task, format!("-PmainClass={}", main_class)];
+            if let Some(program_parameters) = program_parameters {
+                args.push(format!("--args={}", program_parameters));
+            }
+            (crate::managers::gradle::get_gradle_command().to_owned(), args)
+        } else {
+            return None;
+        };
+        Some(RunCommand {
+            program,
+            args,
+            working_dir: self.value("WORKING_DIRECTORY"),
+            env_vars,
+        })
+    }
+
+    fn cargo_command(&self) -> Option<RunCommand> {
+        Some(RunCommand {
+            program: "cargo".to_owned(),
+            args: split_words(self.value("command")),
+            working_dir: self.value("workingDirectory"),
+            env_vars: self.envs(),
+        })
+    }
+
+    /// the script or module(`MODULE_MODE`) by python of `.venv` if available
+    fn python_command(&self, project_dir: &Path) -> Option<RunCommand> {
+        let venv_python = project_dir.join(".venv").join("bin").join("python");
+        let program = if venv_python.exists() {
+            venv_python.to_string_lossy().to_string()
+        } else {
+            "python3".to_owned()
+        };
+        let mut args = split_words(self.value("INTERPRETER_OPTIONS"));
+        if self.value("MODULE_MODE").as_deref() == Some("true") {
+            args.push("-m".to_owned());
+        }
+        args.push(self.value("SCRIPT_NAME")?);
+        args.extend(split_words(self.value("PARAMETERS")));
+        Some(RunCommand {
+            program,
+            args,
+            working_dir: self.value("WORKING_DIRECTORY"),
+            env_vars: self.envs(),
+        })
+    }
+}
+
+/// directory of the module, such as `demo.app.main` of the Gradle project `demo` for `app` directory
+fn module_dir(module_name: &str, project_dir: &Path) -> PathBuf {
+    let project_name = project_dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let name = module_name.strip_suffix(".main").unwrap_or(module_name);
+    let name = name
+        .strip_prefix(&format!("{}.", project_name))
+        .unwrap_or(if name == project_name { "" } else { name });
+    let dir = project_dir.join(name.replace('.', "/"));
+    if dir.is_dir() {
+        dir
+    } else {
+        project_dir.to_path_buf()
+    }
+}
+
+fn xml_files(dir: &Path, suffix: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.to_string_lossy().ends_with(suffix))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+fn run_configuration_files() -> Vec<PathBuf> {
+    let Ok(current_dir) = std::env::current_dir() else {
+        return vec![];
+    };
+    let mut files = xml_files(&current_dir.join(".run"), ".run.xml");
+    files.extend(xml_files(&current_dir.join(".idea").join("runConfigurations"), ".xml"));
+    files
+}
+
+pub fn is_available() -> bool {
+    !run_configuration_files().is_empty()
+}
+
+fn parse_run_configurations(text: &str) -> Vec<RunConfiguration> {
+    serde_xml_rs::from_str::<RunConfigurationComponent>(text)
+        .map(|component| component.configuration)
+        .unwrap_or_default()
+}
+
+/// configurations with supported types, and the first one wins for duplicated names
+fn list_run_commands() -> Vec<(String, RunCommand)> {
+    let Ok(project_dir) = std::env::current_dir() else {
+        return vec![];
+    };
+    let mut run_commands: Vec<(String, RunCommand)> = vec![];
+    for file in run_configuration_files() {
+        let text = std::fs::read_to_string(&file).unwrap_or_default();
+        for configuration in parse_run_configurations(&text) {
+            let name = configuration.formatted_name();
+            if run_commands.iter().any(|(existing, _)| *existing == name) {
+                continue;
+            }
+            if let Some(run_command) = configuration.to_run_command(&project_dir) {
+                run_commands.push((name, run_command));
+            }
+        }
+    }
+    run_commands
+}
+
+pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
+    Ok(list_run_commands()
+        .iter()
+        .map(|(name, run_command)| task!(name, "jetbrains", run_command.command_line()))
+        .collect())
+}
+
+pub fn run_task(
+    task_name: &str,
+    task_args: &[&str],
+    _global_args: &[&str],
+    verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    let (_, run_command) = list_run_commands()
+        .into_iter()
+        .find(|(name, _)| name == task_name)
+        .ok_or_else(|| KeeperError::TaskNotFound(task_name.to_owned()).into_report())?;
+    if !is_command_available(&run_command.program) {
+        println!(
+            "{}",
+            format!("{} is not available", run_command.program).bold().red()
+        );
+        return Err(KeeperError::CommandNotFound(run_command.program).into_report());
+    }
+    let mut args: Vec<&str> = run_command.args.iter().map(|arg| arg.as_str()).collect();
+    args.extend(task_args);
+    let env_vars = Some(run_command.env_vars.clone()).filter(|vars| !vars.is_empty());
+    run_command_with_env_vars(&run_command.program, &args, &run_command.working_dir, &env_vars, verbose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_command_of(xml: &str) -> RunCommand {
+        let configurations = parse_run_configurations(xml);
+        configurations[0].to_run_command(Path::new("/work/demo")).unwrap()
+    }
+
+    #[test]
+    fn test_sh_configuration() {
+        let xml = r#"<component name="ProjectRunConfigurationManager">
+  <configuration default="false" name="Say hello" type="ShConfigurationType">
+    <option name="SCRIPT_TEXT" value="echo hello $NAME" />
+    <option name="INDEPENDENT_SCRIPT_PATH" value="true" />
+    <option name="SCRIPT_PATH" value="" />
+    <option name="SCRIPT_WORKING_DIRECTORY" value="$PROJECT_DIR$/scripts" />
+    <option name="INTERPRETER_PATH" value="/bin/bash" />
+    <option name="EXECUTE_SCRIPT_FILE" value="false" />
+    <envs>
+      <env name="NAME" value="world" />
+    </envs>
+    <method v="2" />
+  </configuration>
+</component>"#;
+        let configurations = parse_run_configurations(xml);
+        assert_eq!("Say-hello", configurations[0].formatted_name());
+        let run_command = run_command_of(xml);
+        assert_eq!("/bin/bash -c 'echo hello $NAME'", run_command.command_line());
+        assert_eq!(Some("/work/demo/scripts".to_owned()), run_command.working_dir);
+        assert_eq!(Some(&"world".to_owned()), run_command.env_vars.get("NAME"));
+    }
+
+    #[test]
+    fn test_npm_configuration() {
+        let xml = r#"<component name="ProjectRunConfigurationManager">
+  <configuration default="false" name="dev" type="js.build_tools.npm">
+    <package-json value="$PROJECT_DIR$/web/package.json" />
+    <command value="run" />
+    <scripts>
+      <script value="dev" />
+    </scripts>
+    <arguments value="--port 3000" />
+    <package-manager value="/usr/local/bin/pnpm" />
+    <method v="2" />
+  </configuration>
+</component>"#;
+        let run_command = run_command_of(xml);
+        assert_eq!("pnpm run dev --port 3000", run_command.command_line());
+        assert_eq!(Some("/work/demo/web".to_owned()), run_command.working_dir);
+    }
+
+    #[test]
+    fn test_gradle_configuration() {
+        let xml = r#"<component name="ProjectRunConfigurationManager">
+  <configuration default="false" name="build" type="GradleRunConfiguration" factoryName="Gradle">
+    <ExternalSystemSettings>
+      <option name="executionName" />
+      <option name="externalProjectPath" value="$PROJECT_DIR$" />
+      <option name="externalSystemIdString" value="GRADLE" />
+      <option name="scriptParameters" value="--info" />
+      <option name="taskDescriptions">
+        <list />
+      </option>
+      <option name="taskNames">
+        <list>
+          <option value="clean" />
+          <option value="build" />
+        </list>
+      </option>
+      <option name="env">
+        <map>
+          <entry key="CI" value="true" />
+        </map>
+      </option>
+    </ExternalSystemSettings>
+    <method v="2" />
+  </configuration>
+</component>"#;
+        let run_command = run_command_of(xml);
+        assert_eq!(vec!["clean", "build", "--info"], run_command.args);
+        assert_eq!(Some("/work/demo".to_owned()), run_command.working_dir);
+        assert_eq!(Some(&"true".to_owned()), run_command.env_vars.get("CI"));
+    }
+
+    #[test]
+    fn test_maven_configuration() {
+        let xml = r#"<component name="ProjectRunConfigurationManager">
+  <configuration default="false" name="package" type="MavenRunConfiguration" factoryName="Maven">
+    <MavenSettings>
+      <option name="myGeneralSettings" />
+      <option name="myRunnerSettings" />
+      <option name="myRunnerParameters">
+        <MavenRunnerParameters>
+          <option name="profiles">
+            <set>
+              <option value="prod" />
+            </set>
+          </option>
+          <option name="goals">
+            <list>
+              <option value="clean" />
+              <option value="package" />
+            </list>
+          </option>
+          <option name="pomFileName" />
+          <option name="workingDirPath" value="$PROJECT_DIR$" />
+        </MavenRunnerParameters>
+      </option>
+    </MavenSettings>
+    <method v="2" />
+  </configuration>
+</component>"#;
+        let run_command = run_command_of(xml);
+        assert_eq!(vec!["clean", "package", "-Pprod"], run_command.args);
+        assert_eq!(Some("/work/demo".to_owned()), run_command.working_dir);
+    }
+
+    #[test]
+    fn test_application_cargo_python_configurations() {
+        let xml = r#"<component name="ProjectRunConfigurationManager">
+  <configuration default="false" name="Main" type="Application" factoryName="Application">
+    <option name="MAIN_CLASS_NAME" value="com.example.Main" />
+    <module name="demo.main" />
+    <option name="PROGRAM_PARAMETERS" value="--port 8080" />
+    <option name="VM_PARAMETERS" value="-Xmx512m" />
+    <method v="2" />
+  </configuration>
+  <configuration default="false" name="Run app" type="CargoCommandRunConfiguration" factoryName="Cargo Command">
+    <option name="command" value="run --package app --bin app" />
+    <option name="workingDirectory" value="file://$PROJECT_DIR$" />
+    <envs />
+  </configuration>
+  <configuration default="false" name="server" type="PythonConfigurationType" factoryName="Python">
+    <option name="PARENT_ENVS" value="true" />
+    <option name="SCRIPT_NAME" value="uvicorn" />
+    <option name="PARAMETERS" value="main:app --reload" />
+    <option name="MODULE_MODE" value="true" />
+  </configuration>
+  <configuration default="false" name="Compound" type="CompoundRunConfigurationType" />
+</component>"#;
+        let project_dir = Path::new("/work/demo");
+        let configurations = parse_run_configurations(xml);
+        assert_eq!(4, configurations.len());
+        // neither Maven nor Gradle project
+        assert_eq!(None, configurations[0].to_run_command(project_dir));
+        let cargo = configurations[1].to_run_command(project_dir).unwrap();
+        assert_eq!("cargo run --package app --bin app", cargo.command_line());
+        assert_eq!(Some("/work/demo".to_owned()), cargo.working_dir);
+        let python = configurations[2].to_run_command(project_dir).unwrap();
+        assert_eq!("python3 -m uvicorn main:app --reload", python.command_line());
+        assert_eq!(None, configurations[3].to_run_command(project_dir));
+    }
+
+    #[test]
+    fn test_application_configuration() {
+        let xml = r#"<component name="ProjectRunConfigurationManager">
+  <configuration default="false" name="Main" type="Application" factoryName="Application">
+    <option name="MAIN_CLASS_NAME" value="com.example.Main" />
+    <module name="demo.app.main" />
+    <option name="PROGRAM_PARAMETERS" value="--port 8080" />
+    <option name="WORKING_DIRECTORY" value="$MODULE_WORKING_DIR$" />
+    <method v="2" />
+  </configuration>
+  <configuration default="false" name="Server" type="Application" factoryName="Application">
+    <option name="MAIN_CLASS_NAME" value="com.example.Server" />
+    <option name="VM_PARAMETERS" value="-Xmx512m" />
+    <option name="WORKING_DIRECTORY" value="$FileDir$" />
+    <method v="2" />
+  </configuration>
+</component>"#;
+        let configurations = parse_run_configurations(xml);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let project_dir = temp_dir.path().join("demo");
+        std::fs::create_dir_all(project_dir.join("app")).unwrap();
+        // Gradle project, and the run configuration with VM parameters is left out
+        std::fs::write(project_dir.join("settings.gradle.kts"), "include(\"app\")").unwrap();
+        let application = configurations[0].to_run_command(&project_dir).unwrap();
+        assert_eq!(
+            vec![":app:run", "-PmainClass=com.example.Main", "--args=--port 8080"],
+            application.args
+        );
+        assert_eq!(
+            Some(project_dir.join("app").to_string_lossy().to_string()),
+            application.working_dir
+        );
+        assert_eq!(None, configurations[1].to_run_command(&project_dir));
+        // Maven project, and VM parameters are passed by MAVEN_OPTS
+        std::fs::write(project_dir.join("pom.xml"), "<project/>").unwrap();
+        let server = configurations[1].to_run_command(&project_dir).unwrap();
+        assert_eq!(
+            vec!["compile", "exec:java", "-Dexec.mainClass=com.example.Server"],
+            server.args
+        );
+        assert!(server.env_vars["MAVEN_OPTS"].ends_with("-Xmx512m"));
+        assert_eq!(None, server.working_dir);
+    }
+}
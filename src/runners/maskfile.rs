@@ -0,0 +1,457 @@
+//! mask(maskfile.md): tasks are headings with a description quote, positional args in the heading,
+//! `**OPTIONS**` list and a fenced code block. Sub commands are named as `parent:child`.
+use crate::command_utils::{run_command, run_command_line_from_stdin_with_env_vars, CommandOutput};
+use crate::errors::KeeperError;
+use crate::models::Task;
+use crate::task;
+use error_stack::{Report, ResultExt};
+use std::collections::HashMap;
+use std::env;
+use which::which;
+
+/// positional argument in heading: `(name)`, `(name?)` or `(name...)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaskArg {
+    pub name: String,
+    pub required: bool,
+    pub variadic: bool,
+}
+
+/// item of `**OPTIONS**` list
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MaskOption {
+    pub name: String,
+    pub flags: Vec<String>,
+    pub description: String,
+    /// `type: string` or `type: number`, otherwise a boolean flag
+    pub takes_value: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MaskCommand {
+    /// words from the top command, such as `["services", "start"]`
+    pub path: Vec<String>,
+    pub level: usize,
+    pub description: String,
+    pub args: Vec<MaskArg>,
+    pub options: Vec<MaskOption>,
+    pub language: Option<String>,
+    pub script: Option<String>,
+}
+
+impl MaskCommand {
+    pub fn name(&self) -> String {
+        self.path.join(":")
+    }
+
+    /// `<service> [version] [--verbose]`
+    pub fn usage(&self) -> String {
+        let mut items: Vec<String> = self
+            .args
+            .iter()
+            .map(|arg| {
+                let name = if arg.variadic {
+                    format!("{}...", arg.name)
+                } else {
+                    arg.name.clone()
+                };
+                if arg.required {
+                    format!("<{}>", name)
+                } else {
+                    format!("[{}]", name)
+                }
+            })
+            .collect();
+        for option in &self.options {
+            let flag = option
+                .flags
+                .iter()
+                .find(|flag| flag.starts_with("--"))
+                .or(option.flags.first())
+                .cloned()
+                .unwrap_or(format!("--{}", option.name));
+            if option.takes_value {
+                items.push(format!("[{} {}]", flag, option.name));
+            } else {
+                items.push(format!("[{}]", flag));
+            }
+        }
+        items.join(" ")
+    }
+}
+
+pub fn is_available() -> bool {
+    env::current_dir()
+        .map(|dir| dir.join("maskfile.md").exists())
+        .unwrap_or(false)
+}
+
+pub fn is_command_available() -> bool {
+    which("mask").is_ok()
+}
+
+pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
+    let commands = list_commands()?;
+    Ok(commands
+        .iter()
+        .map(|command| {
+            let usage = command.usage();
+            let description = if usage.is_empty() {
+                command.description.clone()
+            } else if command.description.is_empty() {
+                usage
+            } else {
+                format!("{} {}", command.description, usage)
+            };
+            task!(
+                command.name(),
+                "mask",
+                command.language.clone().unwrap_or("sh".to_owned()),
+                description,
+                command.script.clone()
+            )
+        })
+        .collect())
+}
+
+/// commands with a code block, and the commands only to group sub commands are skipped
+fn list_commands() -> Result<Vec<MaskCommand>, Report<KeeperError>> {
+    let maskfile_md = env::current_dir()
+        .map(|dir| dir.join("maskfile.md"))
+        .and_then(std::fs::read_to_string)
+        .change_context(KeeperError::InvalidMaskfile)?;
+    Ok(parse_maskfile(&maskfile_md)
+        .into_iter()
+        .filter(|command| command.script.is_some())
+        .collect())
+}
+
+fn parse_maskfile(text: &str) -> Vec<MaskCommand> {
+    let mut commands: Vec<MaskCommand> = vec![];
+    // opening fence and language of the current code block
+    let mut fence: Option<(String, String)> = None;
+    let mut code_lines: Vec<&str> = vec![];
+    let mut in_options = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if let Some((marker, language)) = &fence {
+            if trimmed.starts_with(marker.as_str()) && trimmed.trim_start_matches(marker.as_str()).trim().is_empty() {
+                if let Some(command) = commands.last_mut()
+                    && command.script.is_none()
+                {
+                    command.language = Some(language.clone()).filter(|language| !language.is_empty());
+                    command.script = Some(code_lines.join("\n"));
+                }
+                fence = None;
+                code_lines.clear();
+            } else {
+                code_lines.push(line);
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let marker_len = trimmed.len() - trimmed.trim_start_matches(['`', '~']).len();
+            let marker = trimmed[..marker_len].to_string();
+            let language = trimmed[marker_len..]
+                .split(|c: char| c.is_whitespace() || c == '{')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            fence = Some((marker, language));
+            in_options = false;
+            continue;
+        }
+        if line.starts_with('#') {
+            in_options = false;
+            let level = line.len() - line.trim_start_matches('#').len();
+            // the title of maskfile.md
+            if level < 2 {
+                continue;
+            }
+            let (words, args) = parse_heading(line[level..].trim());
+            let parent = commands
+                .iter()
+                .rev()
+                .find(|command| command.level < level)
+                .map(|command| command.path.clone())
+                .unwrap_or_default();
+            // `### services start` repeats the parent command, and `### start` doesn't
+            let words = if words.len() > parent.len() && words.starts_with(&parent) {
+                words[parent.len()..].to_vec()
+            } else {
+                words
+            };
+            if words.is_empty() {
+                continue;
+            }
+            let mut path = parent;
+            path.extend(words);
+            commands.push(MaskCommand {
+                path,
+                level,
+                args,
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(command) = commands.last_mut() else {
+            continue;
+        };
+        if command.script.is_some() {
+            continue;
+        }
+        if trimmed == "**OPTIONS**" {
+            in_options = true;
+        } else if in_options {
+            parse_option_line(line, &mut command.options);
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            let quote = quote.trim();
+            if !quote.is_empty() {
+                if !command.description.is_empty() {
+                    command.description.push(' ');
+                }
+                command.description.push_str(quote);
+            }
+        }
+    }
+    commands
+}
+
+/// `services start (service_name) (version?)` to words and positional args
+fn parse_heading(heading: &str) -> (Vec<String>, Vec<MaskArg>) {
+    let mut words = vec![];
+    let mut args = vec![];
+    for item in heading.split_whitespace() {
+        if let Some(name) = item.strip_prefix('(').and_then(|item| item.strip_suffix(')')) {
+            let (name, variadic) = match name.strip_suffix("...") {
+                Some(name) => (name, true),
+                None => (name, false),
+            };
+            let (name, required) = match name.strip_suffix('?') {
+                Some(name) => (name, false),
+                None => (name, true),
+            };
+            args.push(MaskArg {
+                name: name.to_string(),
+                required,
+                variadic,
+            });
+        } else if args.is_empty() {
+            words.push(item.to_string());
+        }
+    }
+    (words, args)
+}
+
+/// `* name` starts an option, and the nested `* flags: -v --verbose`, `* desc: ...`, `* type: string` describe it
+fn parse_option_line(line: &str, options: &mut Vec<MaskOption>) {
+    let indent = line.len() - line.trim_start().len();
+    let Some(item) = line.trim().strip_prefix("* ").or(line.trim().strip_prefix("- ")) else {
+        return;
+    };
+    let item = item.trim();
+    if indent == 0 {
+        options.push(MaskOption {
+            name: item.to_string(),
+            ..Default::default()
+        });
+        return;
+    }
+    let Some(option) = options.last_mut() else {
+        return;
+    };
+    if let Some((key, value)) = item.split_once(':') {
+        let value = value.trim();
+        match key.trim() {
+            "flags" => {
+                option.flags = value
+                    .split([' ', ','])
+                    .filter(|flag| !flag.is_empty())
+                    .map(|flag| flag.to_string())
+                    .collect()
+            }
+            "desc" => option.description = value.to_string(),
+            "type" => option.takes_value = value == "string" || value == "number",
+            _ => {}
+        }
+    }
+}
+
+/// variables for the script like mask: positional args and options by name, boolean flags as `true`
+fn collect_variables(command: &MaskCommand, task_args: &[&str]) -> Vec<(String, String)> {
+    let mut variables = vec![];
+    let mut positional: Vec<&str> = vec![];
+    let mut index = 0;
+    while index < task_args.len() {
+        let arg = task_args[index];
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if arg.starts_with('-') => (flag, Some(value)),
+            _ => (arg, None),
+        };
+        if let Some(option) = command
+            .options
+            .iter()
+            .find(|option| option.flags.iter().any(|item| item == flag))
+        {
+            let value = if !option.takes_value {
+                "true".to_string()
+            } else if let Some(value) = inline_value {
+                value.to_string()
+            } else {
+                index += 1;
+                task_args.get(index).map(|value| value.to_string()).unwrap_or_default()
+            };
+            variables.push((option.name.clone(), value));
+        } else {
+            positional.push(arg);
+        }
+        index += 1;
+    }
+    for (index, arg) in command.args.iter().enumerate() {
+        let value = if arg.variadic {
+            positional.get(index..).map(|items| items.join(" "))
+        } else {
+            positional.get(index).map(|item| item.to_string())
+        };
+        if let Some(value) = value {
+            variables.push((arg.name.clone(), value));
+        }
+    }
+    variables
+}
+
+/// interpreter to read the script from stdin
+fn get_interpreter(language: &str) -> Option<&'static str> {
+    match language {
+        "" | "sh" | "shell" => Some("sh -s"),
+        "bash" => Some("bash -s"),
+        "zsh" => Some("zsh -s"),
+        "fish" => Some("fish"),
+        "js" | "javascript" | "node" => Some("node -"),
+        "ts" | "typescript" => Some("deno run -"),
+        "py" | "python" => Some("python3 -"),
+        "rb" | "ruby" => Some("ruby -"),
+        "php" => Some("php"),
+        "pwsh" | "powershell" => Some("pwsh -Command -"),
+        _ => None,
+    }
+}
+
+pub fn run_task(
+    task: &str,
+    task_args: &[&str],
+    global_args: &[&str],
+    verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    if is_command_available() {
+        let mut args = vec![];
+        args.extend(global_args);
+        args.extend(task.split(':'));
+        args.extend(task_args);
+        return run_command("mask", &args, verbose);
+    }
+    let commands = list_commands()?;
+    let command = commands
+        .iter()
+        .find(|command| command.name() == task)
+        .ok_or_else(|| KeeperError::TaskNotFound(task.to_string()))?;
+    let language = command.language.clone().unwrap_or_default();
+    let interpreter = get_interpreter(&language).ok_or_else(|| {
+        KeeperError::FailedToRunTasks(format!("{} code block not supported without mask", language))
+    })?;
+    // variables of the script, and `MASK` is not set because mask is not available
+    let current_dir = env::current_dir().unwrap_or_default();
+    let mut env_vars: HashMap<String, String> = collect_variables(command, task_args).into_iter().collect();
+    env_vars.insert("MASKFILE_DIR".to_owned(), current_dir.to_string_lossy().to_string());
+    let script = command.script.clone().unwrap_or_default();
+    run_command_line_from_stdin_with_env_vars(interpreter, &script, &env_vars, verbose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MASKFILE: &str = r#"# Tasks for demo
+
+## build (target?)
+
+> Build the project
+
+**OPTIONS**
+* release
+    * flags: -r --release
+    * desc: Build with optimizations
+* jobs
+    * flags: -j --jobs
+    * type: number
+
+~~~bash
+cargo build
+~~~
+
+## services
+
+> Commands for services
+
+### services start (service_name)
+
+> Start a service
+
+```sh
+echo "start $service_name"
+```
+
+#### services start all
+
+```python
+print("all")
+```
+
+### stop (names...)
+
+```js
+console.log(process.env.names)
+```
+"#;
+
+    #[test]
+    fn test_parse_maskfile() {
+        let commands: Vec<MaskCommand> = parse_maskfile(MASKFILE);
+        let names: Vec<String> = commands.iter().map(|command| command.name()).collect();
+        assert_eq!(
+            vec!["build", "services", "services:start", "services:start:all", "services:stop"],
+            names
+        );
+        let build = &commands[0];
+        assert_eq!("Build the project", build.description);
+        assert_eq!(Some("bash".to_string()), build.language);
+        assert_eq!(Some("cargo build".to_string()), build.script);
+        assert_eq!("[target] [--release] [--jobs jobs]", build.usage());
+        assert!(commands[1].script.is_none());
+        assert_eq!("<service_name>", commands[2].usage());
+        assert_eq!("<names...>", commands[4].usage());
+    }
+
+    #[test]
+    fn test_collect_variables() {
+        let commands = parse_maskfile(MASKFILE);
+        let variables = collect_variables(&commands[0], &["-r", "--jobs", "4", "x86"]);
+        assert_eq!(
+            vec![
+                ("release".to_string(), "true".to_string()),
+                ("jobs".to_string(), "4".to_string()),
+                ("target".to_string(), "x86".to_string())
+            ],
+            variables
+        );
+        let variables = collect_variables(&commands[4], &["api", "web"]);
+        assert_eq!(vec![("names".to_string(), "api web".to_string())], variables);
+    }
+
+    #[test]
+    fn test_parse() {
+        if let Ok(tasks) = list_tasks() {
+            println!("{:?}", tasks);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! mise tasks: `[tasks.*]` of mise.toml and executable file tasks in `.mise/tasks/`, `mise-tasks/` etc.,
+//! and file tasks are configured by `#MISE description="..."` header comments.
+use crate::command_utils::{run_command, CommandOutput};
+use crate::errors::KeeperError;
+use crate::models::Task;
+use crate::task;
+use colored::Colorize;
+use error_stack::{IntoReport, Report};
+use std::env;
+use std::io::Write;
+use std::path::Path;
+use which::which;
+
+const MISE_CONFIG_FILES: [&str; 4] = ["mise.toml", ".mise.toml", "mise.local.toml", ".mise.local.toml"];
+
+const MISE_TASK_DIRS: [&str; 5] = [
+    "mise-tasks",
+    ".mise-tasks",
+    "mise/tasks",
+    ".mise/tasks",
+    ".config/mise/tasks",
+];
+
+/// task of mise.toml or a file task
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MiseTask {
+    pub name: String,
+    pub description: String,
+    pub depends: Vec<String>,
+    pub aliases: Vec<String>,
+    pub hide: bool,
+}
+
+impl MiseTask {
+    /// description with dependencies and aliases, such as `Build the project [depends: lint] [alias: b]`
+    pub fn summary(&self) -> String {
+        let mut items = vec![];
+        if !self.description.is_empty() {
+            items.push(self.description.clone());
+        }
+        if !self.depends.is_empty() {
+            items.push(format!("[depends: {}]", self.depends.join(", ")));
+        }
+        if !self.aliases.is_empty() {
+            items.push(format!("[alias: {}]", self.aliases.join(", ")));
+        }
+        items.join(" ")
+    }
+}
+
+pub fn is_available() -> bool {
+    env::current_dir()
+        .map(|dir| {
+            MISE_CONFIG_FILES.iter().any(|name| dir.join(name).exists())
+                || MISE_TASK_DIRS.iter().any(|name| dir.join(name).is_dir())
+        })
+        .unwrap_or(false)
+}
+
+pub fn is_command_available() -> bool {
+    which("mise").is_ok()
+}
+
+pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
+    let current_dir = env::current_dir().unwrap();
+    let mut mise_tasks: Vec<MiseTask> = vec![];
+    for name in MISE_CONFIG_FILES {
+        if let Ok(text) = std::fs::read_to_string(current_dir.join(name)) {
+            let config_tasks = parse_mise_toml(&text)
+                .map_err(|error| KeeperError::InvalidMiseToml(format!("{}: {}", name, error)).into_report())?;
+            merge_tasks(&mut mise_tasks, config_tasks);
+        }
+    }
+    for name in MISE_TASK_DIRS {
+        let task_dir = current_dir.join(name);
+        if task_dir.is_dir() {
+            let mut file_tasks = vec![];
+            collect_file_tasks(&task_dir, &task_dir, &mut file_tasks);
+            merge_tasks(&mut mise_tasks, file_tasks);
+        }
+    }
+    Ok(mise_tasks
+        .iter()
+        .filter(|mise_task| !mise_task.hide)
+        .map(|mise_task| task!(mise_task.name, "mise", mise_task.summary()))
+        .collect())
+}
+
+/// tasks of the later config file override the earlier ones with the same name
+fn merge_tasks(mise_tasks: &mut Vec<MiseTask>, new_tasks: Vec<MiseTask>) {
+    for new_task in new_tasks {
+        if let Some(existing) = mise_tasks.iter_mut().find(|mise_task| mise_task.name == new_task.name) {
+            *existing = new_task;
+        } else {
+            mise_tasks.push(new_task);
+        }
+    }
+}
+
+/// string or array of strings
+fn string_items(value: Option<&toml::Value>) -> Vec<String> {
+    match value {
+        Some(toml::Value::String(item)) => vec![item.clone()],
+        Some(toml::Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str())
+            .map(|item| item.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// `[tasks.build]` tables, and `build = "cargo build"` or `build = ["cargo fmt", "cargo build"]` in `[tasks]`
+fn parse_mise_toml(text: &str) -> Result<Vec<MiseTask>, toml::de::Error> {
+    let table = text.parse::<toml::Table>()?;
+    let mut mise_tasks = vec![];
+    if let Some(tasks) = table.get("tasks").and_then(|tasks| tasks.as_table()) {
+        for (name, value) in tasks {
+            let mise_task = match value {
+                toml::Value::Table(options) => parse_task_options(name, options),
+                _ => MiseTask {
+                    name: name.clone(),
+                    description: string_items(Some(value)).join(" && "),
+                    ..Default::default()
+                },
+            };
+            mise_tasks.push(mise_task);
+        }
+    }
+    Ok(mise_tasks)
+}
+
+fn parse_task_options(name: &str, options: &toml::Table) -> MiseTask {
+    MiseTask {
+        name: name.to_string(),
+        description: options
+            .get("description")
+            .and_then(|description| description.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        depends: string_items(options.get("depends")),
+        aliases: string_items(options.get("alias")),
+        hide: options
+            .get("hide")
+            .and_then(|hide| hide.as_bool())
+            .unwrap_or(false),
+    }
+}
+
+/// header comments of file tasks: `#MISE description="Build"`, `# [MISE] depends=["lint"]` or `//MISE alias="b"`
+fn parse_file_task(name: &str, text: &str) -> MiseTask {
+    let mut options = toml::Table::new();
+    for line in text.lines() {
+        let line = line.trim();
+        let comment = line
+            .strip_prefix("//")
+            .or(line.strip_prefix("#"))
+            .map(|comment| comment.trim());
+        // an invalid line is skipped, and the other options are kept
+        if let Some(option) = comment
+            .and_then(|comment| comment.strip_prefix("MISE").or(comment.strip_prefix("[MISE]")))
+            && let Ok(option) = option.trim().parse::<toml::Table>()
+        {
+            options.extend(option);
+        }
+    }
+    parse_task_options(name, &options)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|metadata| metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+/// executable files in the task directory, and `db/migrate` is named as `db:migrate`
+fn collect_file_tasks(task_dir: &Path, dir: &Path, mise_tasks: &mut Vec<MiseTask>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<_> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect();
+    paths.sort();
+    for path in paths {
+        let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if file_name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            collect_file_tasks(task_dir, &path, mise_tasks);
+        } else if is_executable(&path) {
+            let Ok(relative_path) = path.strip_prefix(task_dir) else {
+                continue;
+            };
+            let name = relative_path
+                .with_extension("")
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<String>>()
+                .join(":");
+            let text = std::fs::read_to_string(&path).unwrap_or_default();
+            mise_tasks.push(parse_file_task(&name, &text));
+        }
+    }
+}
+
+pub fn run_task(
+    task: &str,
+    task_args: &[&str],
+    global_args: &[&str],
+    verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    let mut args = vec!["run"];
+    args.extend(global_args);
+    args.push(task);
+    args.extend(task_args);
+    run_command("mise", &args, verbose)
+}
+
+pub fn init_mise_toml() {
+    if Path::new("mise.toml").exists() {
+        println!("{}", "[tk] mise.toml already exists".bold().red());
+        return;
+    }
+    let mut mise_file = std::fs::File::create("mise.toml").unwrap();
+    mise_file
+        .write_all(include_bytes!("../templates/mise.toml"))
+        .unwrap();
+    println!("{}", "mise.toml created".bold().green());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mise_toml() {
+        let text = r#"
+[tools]
+node = "22"
+
+[tasks]
+fmt = "cargo fmt"
+
+[tasks.build]
+description = "Build the project"
+depends = ["fmt", "lint"]
+alias = "b"
+run = "cargo build"
+
+[tasks.internal]
+hide = true
+run = "echo internal"
+"#;
+        let mise_tasks = parse_mise_toml(text).unwrap();
+        assert_eq!(3, mise_tasks.len());
+        let build = mise_tasks.iter().find(|mise_task| mise_task.name == "build").unwrap();
+        assert_eq!("Build the project [depends: fmt, lint] [alias: b]", build.summary());
+        let fmt = mise_tasks.iter().find(|mise_task| mise_task.name == "fmt").unwrap();
+        assert_eq!("cargo fmt", fmt.summary());
+        assert!(mise_tasks.iter().any(|mise_task| mise_task.hide));
+    }
+
+    #[test]
+    fn test_parse_file_task() {
+        let text = r#"#!/usr/bin/env bash
+#MISE description="Migrate database"
+#MISE depends=["db:start"]
+# [MISE] alias="m"
+set -e
+echo migrate
+"#;
+        let mise_task = parse_file_task("db:migrate", text);
+        assert_eq!("Migrate database", mise_task.description);
+        assert_eq!(vec!["db:start"], mise_task.depends);
+        assert_eq!(vec!["m"], mise_task.aliases);
+    }
+
+    #[test]
+    fn test_parse() {
+        if let Ok(tasks) = list_tasks() {
+            println!("{:?}", tasks);
+        }
+    }
+}
@@ -10,10 +10,22 @@ pub mod justfile;
 pub mod makefile;
 pub mod makefiletoml;
 pub mod markdown;
+pub mod maskfile;
+pub mod mise;
+pub mod turbo;
+pub mod nx;
+pub mod compose;
+pub mod nix;
+pub mod jetbrains;
+pub mod cargo_scripts;
+pub mod earthly;
 pub mod nurfile;
 pub mod packagejson;
 pub mod poe;
 pub mod poetry;
+pub mod pdm;
+pub mod hatch;
+pub mod pixi;
 pub mod procfile;
 pub mod rakefile;
 pub mod taskfileyml;
@@ -56,8 +68,20 @@ pub const RUNNERS: &'static [&'static str] = &[
     "vscode",
     "zed",
     "markdown",
+    "mask",
+    "mise",
+    "turbo",
+    "nx",
+    "compose",
+    "nix",
+    "jetbrains",
+    "cargo-scripts",
+    "earthly",
     "poe",
     "poetry",
+    "pdm",
+    "hatch",
+    "pixi-tasks",
     "bun-shell",
     "argc",
     "amber",
@@ -101,10 +125,22 @@ pub fn run_task(
         "usql" => usql::run_task(task_name, task_args, global_args, verbose),
         "composer" => composer::run_task(task_name, task_args, global_args, verbose),
         "markdown" => markdown::run_task(task_name, task_args, global_args, verbose),
+        "mask" => maskfile::run_task(task_name, task_args, global_args, verbose),
+        "mise" => mise::run_task(task_name, task_args, global_args, verbose),
+        "turbo" => turbo::run_task(task_name, task_args, global_args, verbose),
+        "nx" => nx::run_task(task_name, task_args, global_args, verbose),
+        "compose" => compose::run_task(task_name, task_args, global_args, verbose),
+        "nix" => nix::run_task(task_name, task_args, global_args, verbose),
+        "jetbrains" => jetbrains::run_task(task_name, task_args, global_args, verbose),
+        "cargo-scripts" => cargo_scripts::run_task(task_name, task_args, global_args, verbose),
+        "earthly" => earthly::run_task(task_name, task_args, global_args, verbose),
         "shell" => taskshell::run_task(task_name, task_args, global_args, verbose),
         "jbang" => jbang::run_task(task_name, task_args, global_args, verbose),
         "poe" => poe::run_task(task_name, task_args, global_args, verbose),
         "poetry" => poetry::run_task(task_name, task_args, global_args, verbose),
+        "pdm" => pdm::run_task(task_name, task_args, global_args, verbose),
+        "hatch" => hatch::run_task(task_name, task_args, global_args, verbose),
+        "pixi-tasks" => pixi::run_task(task_name, task_args, global_args, verbose),
         "argc" => argcfile::run_task(task_name, task_args, global_args, verbose),
         "amber" => amberfile::run_task(task_name, task_args, global_args, verbose),
         "nur" => nurfile::run_task(task_name, task_args, global_args, verbose),
@@ -141,9 +177,21 @@ pub fn get_runner_file_name(runner: &str) -> &'static str {
         "zed" => ".zed/tasks.json",
         "shell" => "task.sh",
         "markdown" => "README.md",
+        "mask" => "maskfile.md",
+        "mise" => "mise.toml",
+        "turbo" => "turbo.json",
+        "nx" => "nx.json",
+        "compose" => "compose.yaml",
+        "nix" => "devenv.nix, flake.nix",
+        "jetbrains" => ".run/*.run.xml",
+        "cargo-scripts" => ".cargo/config.toml, Cargo.toml",
+        "earthly" => "Earthfile, dagger.json",
         "jbang" => "jbang-catalog.json",
         "poe" => "pyproject.toml",
         "poetry" => "pyproject.toml",
+        "pdm" => "pyproject.toml",
+        "hatch" => "pyproject.toml, hatch.toml",
+        "pixi-tasks" => "pixi.toml",
         "bun-shell" => "Taskfile.ts",
         "argc" => "Argcfile.sh",
         "amber" => "Amberfile",
@@ -177,9 +225,21 @@ pub fn get_runner_web_url(runner: &str) -> &'static str {
         "zed" => "https://zed.dev/docs/tasks",
         "shell" => "https://www.gnu.org/software/bash/",
         "markdown" => "https://github.com/linux-china/task-keeper#tasks-from-readmemd",
+        "mask" => "https://github.com/jacobdeichert/mask",
+        "mise" => "https://mise.jdx.dev/tasks/",
+        "turbo" => "https://turborepo.com",
+        "nx" => "https://nx.dev",
+        "compose" => "https://docs.docker.com/compose/",
+        "nix" => "https://devenv.sh/scripts/",
+        "jetbrains" => "https://www.jetbrains.com/help/idea/run-debug-configuration.html",
+        "cargo-scripts" => "https://doc.rust-lang.org/cargo/reference/config.html#alias",
+        "earthly" => "https://docs.earthly.dev/docs/earthfile",
         "jbang" => "https://www.jbang.dev/",
         "poe" => "https://github.com/nat-n/poethepoet",
         "poetry" => "https://python-poetry.org",
+        "pdm" => "https://pdm-project.org/latest/usage/scripts/",
+        "hatch" => "https://hatch.pypa.io/latest/config/environment/overview/#scripts",
+        "pixi-tasks" => "https://pixi.sh/latest/workspace/advanced_tasks/",
         "bun-shell" => "https://bun.sh/docs/runtime/shell",
         "argc" => "https://github.com/sigoden/argc",
         "amber" => "https://amber-lang.com",
@@ -0,0 +1,612 @@
+//! Nix: scripts, processes and `enterTest` of devenv.nix, and apps of flake.nix.
+//! `devenv info` and `nix flake show --json` are used when available, otherwise nix files are parsed statically.
+use crate::command_utils::{capture_command_output, is_command_available, run_command, CommandOutput};
+use crate::common::devshell::hashed_cache_file;
+use crate::errors::KeeperError;
+use crate::models::Task;
+use crate::task;
+use error_stack::Report;
+use std::collections::HashMap;
+use std::env;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NixTaskKind {
+    /// `scripts.<name>.exec` by `devenv shell <name>`
+    Script,
+    /// `processes.<name>.exec` by `devenv up <name>`
+    Process,
+    /// all processes by `devenv up`
+    Processes,
+    /// `enterTest` by `devenv test`
+    Test,
+    /// `apps.<system>.<name>` by `nix run .#<name>`
+    App,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NixTask {
+    pub name: String,
+    pub kind: NixTaskKind,
+    /// name of the script, process or app
+    pub target: String,
+    pub description: String,
+}
+
+pub fn is_available() -> bool {
+    env::current_dir()
+        .map(|dir| dir.join("devenv.nix").exists() || dir.join("flake.nix").exists())
+        .unwrap_or(false)
+}
+
+/// `x86_64-linux` or `aarch64-darwin`
+fn current_system() -> String {
+    let os = match env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    format!("{}-{}", env::consts::ARCH, os)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '\''
+}
+
+/// position after the string or comment starting at `i`, or `None` if nothing to skip
+fn skip_literal(chars: &[char], i: usize) -> Option<usize> {
+    let n = chars.len();
+    match chars[i] {
+        '#' => Some((i..n).find(|&j| chars[j] == '\n').unwrap_or(n)),
+        '/' if chars.get(i + 1) == Some(&'*') => Some(
+            (i + 2..n.saturating_sub(1))
+                .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                .map(|j| j + 2)
+                .unwrap_or(n),
+        ),
+        '"' => {
+            let mut j = i + 1;
+            while j < n && chars[j] != '"' {
+                j = if chars[j] == '\\' {
+                    j + 2
+                } else if chars[j] == '$' && chars.get(j + 1) == Some(&'{') {
+                    skip_interpolation(chars, j + 2)
+                } else {
+                    j + 1
+                };
+            }
+            Some((j + 1).min(n))
+        }
+        // `''$`, `'''` and `''\` are escapes in indented strings
+        '\'' if chars.get(i + 1) == Some(&'\'') => {
+            let mut j = i + 2;
+            while j + 1 < n {
+                if chars[j] == '\'' && chars[j + 1] == '\'' {
+                    if matches!(chars.get(j + 2), Some('$') | Some('\'') | Some('\\')) {
+                        j += 3;
+                        continue;
+                    }
+                    return Some(j + 2);
+                }
+                j = if chars[j] == '$' && chars[j + 1] == '{' {
+                    skip_interpolation(chars, j + 2)
+                } else {
+                    j + 1
+                };
+            }
+            Some(n)
+        }
+        _ => None,
+    }
+}
+
+/// position after `}` of the interpolation `${...}`, and `i` is after `${`
+fn skip_interpolation(chars: &[char], mut i: usize) -> usize {
+    let mut depth = 0;
+    while i < chars.len() {
+        if let Some(next) = skip_literal(chars, i) {
+            i = next;
+            continue;
+        }
+        match chars[i] {
+            '{' => depth += 1,
+            '}' if depth == 0 => return i + 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+/// end of the value starting at `i`: `;` or the closing bracket of the enclosing attribute set
+fn find_value_end(chars: &[char], mut i: usize) -> usize {
+    let mut depth = 0;
+    while i < chars.len() {
+        if let Some(next) = skip_literal(chars, i) {
+            i = next;
+            continue;
+        }
+        match chars[i] {
+            '{' | '[' | '(' => depth += 1,
+            '}' | ']' | ')' if depth == 0 => return i,
+            '}' | ']' | ')' => depth -= 1,
+            ';' if depth == 0 => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+/// attribute path such as `scripts.hello.exec` or `apps.${system}.default` starting at `i`
+fn read_attr_path(chars: &[char], mut i: usize) -> (Vec<String>, usize) {
+    let mut path = vec![];
+    loop {
+        let start = i;
+        if chars.get(i) == Some(&'$') && chars.get(i + 1) == Some(&'{') {
+            i = (i..chars.len()).find(|&j| chars[j] == '}').map(|j| j + 1).unwrap_or(chars.len());
+        } else {
+            while i < chars.len() && is_ident_char(chars[i]) {
+                i += 1;
+            }
+        }
+        if i == start {
+            break;
+        }
+        path.push(chars[start..i].iter().collect());
+        if chars.get(i) == Some(&'.') && chars.get(i + 1).is_some_and(|c| c.is_alphabetic() || *c == '_' || *c == '$') {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    (path, i)
+}
+
+/// best-effort parse of nix bindings as attribute paths and value texts, and nested attribute sets are flattened:
+/// `scripts = { hello.exec = "echo hello"; };` to `(["scripts", "hello", "exec"], "\"echo hello\"")`
+fn parse_nix_bindings(text: &str) -> Vec<(Vec<String>, String)> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut bindings = vec![];
+    let mut prefixes: Vec<Vec<String>> = vec![vec![]];
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(next) = skip_literal(&chars, i) {
+            i = next;
+            continue;
+        }
+        let c = chars[i];
+        if c == '{' {
+            prefixes.push(prefixes.last().cloned().unwrap_or_default());
+            i += 1;
+        } else if c == '}' {
+            if prefixes.len() > 1 {
+                prefixes.pop();
+            }
+            i += 1;
+        } else if c.is_alphabetic() || c == '_' {
+            let (path, end) = read_attr_path(&chars, i);
+            let mut j = end;
+            while j < chars.len() && chars[j].is_whitespace() {
+                j += 1;
+            }
+            if chars.get(j) == Some(&'=') && chars.get(j + 1) != Some(&'=') {
+                let mut full_path = prefixes.last().cloned().unwrap_or_default();
+                full_path.extend(path);
+                j += 1;
+                while j < chars.len() && chars[j].is_whitespace() {
+                    j += 1;
+                }
+                if chars.get(j) == Some(&'{') {
+                    prefixes.push(full_path);
+                    i = j + 1;
+                } else {
+                    let value_end = find_value_end(&chars, j);
+                    bindings.push((full_path, chars[j..value_end].iter().collect::<String>().trim().to_string()));
+                    i = value_end + 1;
+                    if chars.get(value_end) == Some(&'}') {
+                        i = value_end;
+                    }
+                }
+            } else {
+                i = end.max(i + 1);
+            }
+        } else {
+            i += 1;
+        }
+    }
+    bindings
+}
+
+/// text of a nix string literal, and indented strings are dedented
+fn nix_string(value: &str) -> Option<String> {
+    let value = value.trim();
+    if let Some(text) = value.strip_prefix("''").and_then(|text| text.strip_suffix("''")) {
+        let lines: Vec<&str> = text.trim_start_matches([' ', '\t']).trim_start_matches('\n').lines().collect();
+        let indent = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let text = lines
+            .iter()
+            .map(|line| line.get(indent..).unwrap_or("").trim_end())
+            .collect::<Vec<&str>>()
+            .join("\n");
+        return Some(text.trim_end().replace("''$", "$").replace("'''", "''"));
+    }
+    value
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .map(|text| text.replace("\\\"", "\"").replace("\\n", "\n").replace("\\$", "$").replace("\\\\", "\\"))
+}
+
+/// the first line of the command as description
+fn first_line(command: &str) -> String {
+    command.lines().find(|line| !line.trim().is_empty()).unwrap_or_default().trim().to_string()
+}
+
+/// scripts and processes with descriptions, and `enterTest`
+#[derive(Debug, Default, PartialEq)]
+struct DevenvConfig {
+    scripts: Vec<(String, String)>,
+    processes: Vec<(String, String)>,
+    enter_test: bool,
+}
+
+fn parse_devenv_nix(text: &str) -> DevenvConfig {
+    let mut config = DevenvConfig::default();
+    let mut descriptions: HashMap<String, String> = HashMap::new();
+    for (path, value) in parse_nix_bindings(text) {
+        let path: Vec<&str> = path.iter().map(|item| item.as_str()).collect();
+        match path.as_slice() {
+            ["scripts", name, "exec"] => {
+                let command = nix_string(&value).unwrap_or(value.clone());
+                config.scripts.push((name.to_string(), first_line(&command)));
+            }
+            ["scripts", name, "description"] => {
+                if let Some(description) = nix_string(&value) {
+                    descriptions.insert(name.to_string(), description);
+                }
+            }
+            ["processes", name, "exec"] => {
+                let command = nix_string(&value).unwrap_or(value.clone());
+                config.processes.push((name.to_string(), first_line(&command)));
+            }
+            ["enterTest"] => config.enter_test = true,
+            _ => {}
+        }
+    }
+    for (name, description) in config.scripts.iter_mut() {
+        if let Some(text) = descriptions.remove(name.as_str()) {
+            *description = text;
+        }
+    }
+    config
+}
+
+/// `# scripts` and `# processes` sections of `devenv info`, and items are `- name: ...`
+fn parse_devenv_info(text: &str) -> (Vec<String>, Vec<String>) {
+    let mut scripts = vec![];
+    let mut processes = vec![];
+    let mut section = "";
+    for line in text.lines() {
+        if let Some(title) = line.strip_prefix("# ") {
+            section = title.trim();
+        } else if let Some(item) = line.trim().strip_prefix("- ") {
+            let name = item.split(':').next().unwrap_or_default().trim().to_string();
+            match section {
+                "scripts" => scripts.push(name),
+                "processes" => processes.push(name),
+                _ => {}
+            }
+        }
+    }
+    (scripts, processes)
+}
+
+/// apps with descriptions in flake.nix: `apps.${system}.<name> = { type = "app"; program = ...; meta.description = ...; }`
+fn parse_flake_apps(text: &str) -> Vec<(String, String)> {
+    let mut apps: Vec<(String, String)> = vec![];
+    for (path, value) in parse_nix_bindings(text) {
+        let Some(index) = path.iter().position(|item| item == "apps") else {
+            continue;
+        };
+        let rest: Vec<&str> = path[index + 1..].iter().map(|item| item.as_str()).collect();
+        let (name, description) = match rest.as_slice() {
+            [.., name, "type"] | [.., name, "program"] => (name.to_string(), None),
+            [.., name, "meta", "description"] | [.., name, "description"] => (name.to_string(), nix_string(&value)),
+            _ => continue,
+        };
+        match apps.iter_mut().find(|(existing, _)| *existing == name) {
+            Some((_, existing)) => {
+                if let Some(description) = description {
+                    *existing = description;
+                }
+            }
+            None => apps.push((name, description.unwrap_or_default())),
+        }
+    }
+    apps
+}
+
+/// apps of the current system in the output of `nix flake show --json`, and a flake without apps has none
+fn parse_flake_show(json: &str) -> Option<Vec<(String, String)>> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    Some(
+        value["apps"][current_system()]
+            .as_object()
+            .map(|apps| {
+                apps.iter()
+                    .map(|(name, app)| (name.clone(), app["description"].as_str().unwrap_or_default().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    )
+}
+
+/// `nix flake show --json` is slow to evaluate, and cached by the hash of flake.nix and flake.lock
+fn flake_show_apps() -> Option<Vec<(String, String)>> {
+    let cache_file = hashed_cache_file("nix", "flake-show", &["flake.nix", "flake.lock"]);
+    if let Some(cache_file) = &cache_file
+        && let Ok(json) = std::fs::read_to_string(cache_file)
+    {
+        return parse_flake_show(&json);
+    }
+    let output = capture_command_output("nix", &["flake", "show", "--json"]).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let json = String::from_utf8_lossy(&output.stdout).to_string();
+    let apps = parse_flake_show(&json);
+    if apps.is_some()
+        && let Some(cache_file) = &cache_file
+        && let Some(parent) = cache_file.parent()
+        && std::fs::create_dir_all(parent).is_ok()
+    {
+        std::fs::write(cache_file, &json).ok();
+    }
+    apps
+}
+
+/// `devenv info` evaluates the whole environment, and is cached by the hash of devenv.nix, devenv.yaml and devenv.lock
+fn devenv_info() -> Option<String> {
+    let cache_file = hashed_cache_file("nix", "devenv-info", &["devenv.nix", "devenv.yaml", "devenv.lock"]);
+    if let Some(cache_file) = &cache_file
+        && let Ok(text) = std::fs::read_to_string(cache_file)
+    {
+        return Some(text);
+    }
+    let output = capture_command_output("devenv", &["info"]).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout).to_string();
+    if let Some(cache_file) = &cache_file
+        && let Some(parent) = cache_file.parent()
+        && std::fs::create_dir_all(parent).is_ok()
+    {
+        std::fs::write(cache_file, &text).ok();
+    }
+    Some(text)
+}
+
+fn devenv_tasks() -> Vec<NixTask> {
+    let Ok(text) = std::fs::read_to_string("devenv.nix") else {
+        return vec![];
+    };
+    let mut config = parse_devenv_nix(&text);
+    // scripts and processes from imported modules are known by devenv only
+    if is_command_available("devenv")
+        && let Some(info) = devenv_info()
+    {
+        let (scripts, processes) = parse_devenv_info(&info);
+        for (names, items) in [(scripts, &mut config.scripts), (processes, &mut config.processes)] {
+            for name in names {
+                if !items.iter().any(|(existing, _)| *existing == name) {
+                    items.push((name, String::new()));
+                }
+            }
+        }
+    }
+    let mut tasks = vec![];
+    for (name, description) in &config.scripts {
+        tasks.push(NixTask {
+            name: name.clone(),
+            kind: NixTaskKind::Script,
+            target: name.clone(),
+            description: description.clone(),
+        });
+    }
+    if !config.processes.is_empty() {
+        tasks.push(NixTask {
+            name: "devenv:up".to_string(),
+            kind: NixTaskKind::Processes,
+            target: String::new(),
+            description: "start all processes by devenv up".to_string(),
+        });
+    }
+    for (name, description) in &config.processes {
+        tasks.push(NixTask {
+            name: format!("devenv:up:{}", name),
+            kind: NixTaskKind::Process,
+            target: name.clone(),
+            description: description.clone(),
+        });
+    }
+    if config.enter_test {
+        tasks.push(NixTask {
+            name: "devenv:test".to_string(),
+            kind: NixTaskKind::Test,
+            target: String::new(),
+            description: "run enterTest by devenv test".to_string(),
+        });
+    }
+    tasks
+}
+
+/// devenv tasks, then flake apps, and an app with the same name of a devenv task is named as `app:<name>`
+pub fn list_nix_tasks() -> Vec<NixTask> {
+    let mut tasks = devenv_tasks();
+    if let Ok(text) = std::fs::read_to_string("flake.nix") {
+        let apps = if is_command_available("nix") {
+            flake_show_apps().unwrap_or_else(|| parse_flake_apps(&text))
+        } else {
+            parse_flake_apps(&text)
+        };
+        for (name, description) in apps {
+            let task_name = if tasks.iter().any(|task| task.name == name) {
+                format!("app:{}", name)
+            } else {
+                name.clone()
+            };
+            tasks.push(NixTask {
+                name: task_name,
+                kind: NixTaskKind::App,
+                description: if description.is_empty() {
+                    format!("nix run .#{}", name)
+                } else {
+                    description
+                },
+                target: name,
+            });
+        }
+    }
+    tasks
+}
+
+pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
+    Ok(list_nix_tasks()
+        .iter()
+        .map(|nix_task| task!(nix_task.name, "nix", nix_task.description))
+        .collect())
+}
+
+pub fn run_task(
+    task: &str,
+    task_args: &[&str],
+    global_args: &[&str],
+    verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    let nix_task = list_nix_tasks()
+        .into_iter()
+        .find(|nix_task| nix_task.name == task)
+        .ok_or_else(|| KeeperError::TaskNotFound(task.to_string()))?;
+    let mut args: Vec<&str> = global_args.to_vec();
+    let app_ref = format!(".#{}", nix_task.target);
+    let command_name = match nix_task.kind {
+        NixTaskKind::Script => {
+            args.extend(["shell", nix_task.target.as_str()]);
+            args.extend(task_args);
+            "devenv"
+        }
+        NixTaskKind::Process => {
+            args.extend(["up", nix_task.target.as_str()]);
+            args.extend(task_args);
+            "devenv"
+        }
+        NixTaskKind::Processes => {
+            args.push("up");
+            args.extend(task_args);
+            "devenv"
+        }
+        NixTaskKind::Test => {
+            args.push("test");
+            args.extend(task_args);
+            "devenv"
+        }
+        NixTaskKind::App => {
+            args.extend(["run", app_ref.as_str()]);
+            if !task_args.is_empty() {
+                args.push("--");
+                args.extend(task_args);
+            }
+            "nix"
+        }
+    };
+    run_command(command_name, &args, verbose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_devenv_nix() {
+        let text = r#"{ pkgs, lib, ... }: {
+  # scripts.ignored.exec = "echo ignored";
+  scripts.hello.exec = "echo hello from $GREET";
+  scripts.hello.description = "Say hello";
+  scripts = {
+    build = {
+      exec = ''
+        cargo build --release
+        echo "done; ok"
+      '';
+    };
+  };
+  processes.web.exec = "python -m http.server";
+  enterTest = ''
+    wait_for_port 8000
+  '';
+  env.GREET = if pkgs.stdenv.isLinux then "linux" else "{other}";
+}
+"#;
+        let config = parse_devenv_nix(text);
+        assert_eq!(
+            vec![
+                ("hello".to_string(), "Say hello".to_string()),
+                ("build".to_string(), "cargo build --release".to_string())
+            ],
+            config.scripts
+        );
+        assert_eq!(vec![("web".to_string(), "python -m http.server".to_string())], config.processes);
+        assert!(config.enter_test);
+    }
+
+    #[test]
+    fn test_parse_flake_apps() {
+        let text = r#"{
+  description = "demo";
+  outputs = { self, nixpkgs, ... }: let
+    system = "x86_64-linux";
+  in {
+    apps.${system}.default = {
+      type = "app";
+      program = "${self.packages.${system}.default}/bin/tk";
+    };
+    apps.${system}.migrate = {
+      type = "app";
+      program = "${pkgs.writeShellScript "migrate" "echo migrate"}";
+      meta.description = "Migrate database";
+    };
+  };
+}"#;
+        assert_eq!(
+            vec![
+                ("default".to_string(), "".to_string()),
+                ("migrate".to_string(), "Migrate database".to_string())
+            ],
+            parse_flake_apps(text)
+        );
+    }
+
+    #[test]
+    fn test_parse_devenv_info() {
+        let text = "# env\n- DEVENV_ROOT: /work\n# scripts\n- hello: /nix/store/abc-hello\n# processes\n- web: exec python\n";
+        assert_eq!((vec!["hello".to_string()], vec!["web".to_string()]), parse_devenv_info(text));
+    }
+
+    #[test]
+    fn test_parse_flake_show() {
+        let json = format!(
+            r#"{{"apps": {{"{}": {{"migrate": {{"type": "app", "description": "Migrate database"}}}}}}}}"#,
+            current_system()
+        );
+        assert_eq!(
+            Some(vec![("migrate".to_string(), "Migrate database".to_string())]),
+            parse_flake_show(&json)
+        );
+        assert_eq!(Some(vec![]), parse_flake_show(r#"{"packages": {}}"#));
+    }
+}
@@ -0,0 +1,288 @@
+//! Nx: targets of `project.json` files, and inferred targets by `nx graph --file=stdout` when nx is installed.
+//! Targets are named as `project:target` for `nx run`, and `nx:target` for `nx run-many` of all projects,
+//! not to clash with scripts of the root package.json.
+use crate::command_utils::{capture_command_output, run_command, CommandOutput};
+use crate::common::devshell::hashed_cache_file;
+use crate::common::workspace::sub_dirs;
+use crate::common::{get_node_bin_command, is_node_bin_available};
+use crate::errors::KeeperError;
+use crate::models::Task;
+use crate::task;
+use error_stack::Report;
+use jsonc_parser::parse_to_serde_value;
+use std::env;
+use std::path::Path;
+
+/// depth of directories to search project.json
+const MAX_PROJECT_DEPTH: usize = 4;
+
+/// project with its targets and the description of each target
+#[derive(Debug, Clone, Default, PartialEq)]
+struct NxProject {
+    name: String,
+    targets: Vec<(String, String)>,
+}
+
+impl NxProject {
+    fn merge_targets(&mut self, targets: Vec<(String, String)>) {
+        for (name, description) in targets {
+            if !self.targets.iter().any(|(existing, _)| *existing == name) {
+                self.targets.push((name, description));
+            }
+        }
+    }
+}
+
+pub fn is_available() -> bool {
+    env::current_dir()
+        .map(|dir| dir.join("nx.json").exists())
+        .unwrap_or(false)
+}
+
+pub fn is_command_available() -> bool {
+    is_node_bin_available("nx")
+}
+
+/// `executor`, or `command`(`options.command`) of run-commands
+fn target_description(target: &serde_json::Value) -> String {
+    target["command"]
+        .as_str()
+        .or(target["options"]["command"].as_str())
+        .or(target["executor"].as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn parse_targets(json: &serde_json::Value) -> Vec<(String, String)> {
+    let mut targets: Vec<(String, String)> = json["targets"]
+        .as_object()
+        .map(|targets| {
+            targets
+                .iter()
+                .map(|(name, target)| (name.clone(), target_description(target)))
+                .collect()
+        })
+        .unwrap_or_default();
+    targets.sort();
+    targets
+}
+
+/// project.json with `name`, the name of its directory by default
+fn parse_project_json(dir: &Path, text: &str) -> Option<NxProject> {
+    let json: serde_json::Value = parse_to_serde_value(text, &Default::default()).ok()?;
+    let name = json["name"]
+        .as_str()
+        .map(|name| name.to_string())
+        .or_else(|| dir.file_name().map(|name| name.to_string_lossy().to_string()))?;
+    Some(NxProject {
+        name,
+        targets: parse_targets(&json),
+    })
+}
+
+/// projects of project.json files, and project.json and package.json files of projects for the hash of the graph cache
+fn find_project_json_files(
+    root: &Path,
+    dir: &Path,
+    depth: usize,
+    projects: &mut Vec<NxProject>,
+    config_files: &mut Vec<String>,
+) {
+    if let Ok(text) = std::fs::read_to_string(dir.join("project.json"))
+        && let Some(project) = parse_project_json(dir, &text)
+    {
+        projects.push(project);
+    }
+    for file_name in ["project.json", "package.json"] {
+        let path = dir.join(file_name);
+        if path.is_file()
+            && let Ok(relative_path) = path.strip_prefix(root)
+        {
+            config_files.push(relative_path.to_string_lossy().to_string());
+        }
+    }
+    if depth < MAX_PROJECT_DEPTH {
+        for sub_dir in sub_dirs(dir) {
+            find_project_json_files(root, &sub_dir, depth + 1, projects, config_files);
+        }
+    }
+}
+
+/// projects of `{"graph": {"nodes": {"web": {"name": "web", "data": {"targets": {}}}}}}`
+fn parse_project_graph(json: &serde_json::Value) -> Vec<NxProject> {
+    json["graph"]["nodes"]
+        .as_object()
+        .map(|nodes| {
+            nodes
+                .iter()
+                .map(|(name, node)| NxProject {
+                    name: node["name"].as_str().unwrap_or(name).to_string(),
+                    targets: parse_targets(&node["data"]),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// projects and targets inferred by nx plugins, such as targets from package.json scripts or vite.config.ts.
+/// The project graph is computed by one `nx graph` call, and cached by the hash of nx.json and config files of projects.
+fn list_inferred_projects(config_files: &[String]) -> Vec<NxProject> {
+    let mut file_names: Vec<&str> = vec!["nx.json"];
+    file_names.extend(config_files.iter().map(|name| name.as_str()));
+    let cache_file = hashed_cache_file("nx", "graph", &file_names);
+    if let Some(cache_file) = &cache_file
+        && let Ok(text) = std::fs::read_to_string(cache_file)
+        && let Ok(json) = serde_json::from_str::<serde_json::Value>(&text)
+    {
+        return parse_project_graph(&json);
+    }
+    let command = get_node_bin_command("nx");
+    let mut args: Vec<&str> = command[1..].iter().map(|item| item.as_str()).collect();
+    args.extend(["graph", "--file=stdout"]);
+    let Ok(output) = capture_command_output(&command[0], &args) else {
+        return vec![];
+    };
+    if !output.status.success() {
+        return vec![];
+    }
+    let Ok(json) = serde_json::from_slice::<serde_json::Value>(&output.stdout) else {
+        return vec![];
+    };
+    if let Some(cache_file) = &cache_file
+        && let Some(parent) = cache_file.parent()
+        && std::fs::create_dir_all(parent).is_ok()
+    {
+        std::fs::write(cache_file, &output.stdout).ok();
+    }
+    parse_project_graph(&json)
+}
+
+fn list_projects() -> Vec<NxProject> {
+    let mut projects = vec![];
+    let mut config_files = vec![];
+    if let Ok(current_dir) = env::current_dir() {
+        find_project_json_files(&current_dir, &current_dir, 0, &mut projects, &mut config_files);
+    }
+    if is_command_available() {
+        for inferred in list_inferred_projects(&config_files) {
+            if let Some(project) = projects.iter_mut().find(|project| project.name == inferred.name) {
+                project.merge_targets(inferred.targets);
+            } else {
+                projects.push(inferred);
+            }
+        }
+    }
+    projects.sort_by(|a, b| a.name.cmp(&b.name));
+    projects
+}
+
+/// `nx:target` for all projects with the target, then `project:target`
+pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
+    let projects = list_projects();
+    let mut target_names: Vec<&String> = projects
+        .iter()
+        .flat_map(|project| project.targets.iter().map(|(name, _)| name))
+        .collect();
+    target_names.sort();
+    target_names.dedup();
+    let mut tasks: Vec<Task> = target_names
+        .iter()
+        .map(|name| {
+            let count = projects
+                .iter()
+                .filter(|project| project.targets.iter().any(|(target, _)| target == *name))
+                .count();
+            task!(format!("nx:{}", name), "nx", format!("run-many for {} project(s)", count))
+        })
+        .collect();
+    for project in &projects {
+        for (target, description) in &project.targets {
+            tasks.push(task!(format!("{}:{}", project.name, target), "nx", description));
+        }
+    }
+    Ok(tasks)
+}
+
+/// `project:target` is a target of one project, and `test:unit` is a target of all projects if `test` is not a project
+fn is_project_target(task: &str, project_names: &[String]) -> bool {
+    project_names
+        .iter()
+        .any(|name| task.starts_with(&format!("{}:", name)))
+}
+
+pub fn run_task(
+    task: &str,
+    task_args: &[&str],
+    global_args: &[&str],
+    verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    let project_names: Vec<String> = list_projects().into_iter().map(|project| project.name).collect();
+    let command = get_node_bin_command("nx");
+    let mut args: Vec<&str> = command[1..].iter().map(|item| item.as_str()).collect();
+    if let Some(target) = task.strip_prefix("nx:") {
+        args.push("run-many");
+        args.push("-t");
+        args.push(target);
+    } else if is_project_target(task, &project_names) {
+        args.push("run");
+        args.push(task);
+    } else {
+        args.push("run-many");
+        args.push("-t");
+        args.push(task);
+    }
+    args.extend(global_args);
+    args.extend(task_args);
+    run_command(&command[0], &args, verbose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_project_json() {
+        let text = r#"{
+  "name": "web",
+  "targets": {
+    "build": { "executor": "@nx/vite:build", "options": { "outputPath": "dist/apps/web" } },
+    "serve": { "command": "vite" },
+    "deploy": { "executor": "nx:run-commands", "options": { "command": "./deploy.sh" } }
+  }
+}"#;
+        let project = parse_project_json(Path::new("apps/web-app"), text).unwrap();
+        assert_eq!("web", project.name);
+        assert_eq!(
+            vec![
+                ("build".to_string(), "@nx/vite:build".to_string()),
+                ("deploy".to_string(), "./deploy.sh".to_string()),
+                ("serve".to_string(), "vite".to_string())
+            ],
+            project.targets
+        );
+        let project = parse_project_json(Path::new("libs/ui"), "{}").unwrap();
+        assert_eq!("ui", project.name);
+    }
+
+    #[test]
+    fn test_parse_project_graph() {
+        let text = r#"{"graph": {"nodes": {
+  "web": { "name": "web", "type": "app", "data": { "root": "apps/web", "targets": { "dev": { "executor": "nx:run-script" } } } },
+  "ui": { "name": "ui", "type": "lib", "data": { "root": "libs/ui", "targets": {} } }
+}, "dependencies": {}}}"#;
+        let mut projects = parse_project_graph(&serde_json::from_str(text).unwrap());
+        projects.sort_by(|a, b| a.name.cmp(&b.name));
+        let names: Vec<&str> = projects.iter().map(|project| project.name.as_str()).collect();
+        assert_eq!(vec!["ui", "web"], names);
+        assert_eq!(vec![("dev".to_string(), "nx:run-script".to_string())], projects[1].targets);
+    }
+
+    #[test]
+    fn test_is_project_target() {
+        let project_names = vec!["web".to_string(), "@acme/ui".to_string()];
+        assert!(is_project_target("web:build", &project_names));
+        assert!(is_project_target("@acme/ui:test:unit", &project_names));
+        assert!(!is_project_target("test:unit", &project_names));
+        assert!(!is_project_target("build", &project_names));
+    }
+}
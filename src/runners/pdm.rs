@@ -0,0 +1,105 @@
+//! PDM: `[tool.pdm.scripts]` of pyproject.toml with cmd, shell, call and composite scripts, executed by `pdm run`.
+use crate::command_utils::{run_command, CommandOutput};
+use crate::common::pyproject::{get_uv_tool_path, PyProjectToml};
+use crate::common::pyproject_toml_has_tool;
+use crate::errors::KeeperError;
+use crate::models::Task;
+use crate::runners::uv_scripts::{get_script_cmd, Script};
+use crate::task;
+use error_stack::Report;
+use std::collections::HashMap;
+use which::which;
+
+pub fn is_available() -> bool {
+    pyproject_toml_has_tool("pdm.scripts")
+}
+
+pub fn is_command_available() -> bool {
+    get_uv_tool_path("pdm").is_some() || which("pdm").is_ok()
+}
+
+pub fn install() -> Result<CommandOutput, Report<KeeperError>> {
+    run_command("uv", &["tool", "install", "pdm"], true)
+}
+
+/// scripts with `help` or the description of the script, and `_` is the shared options of all scripts
+fn parse_pdm_scripts(scripts: &HashMap<String, toml::Value>) -> Vec<(String, Script, String)> {
+    let mut pdm_scripts: Vec<(String, Script, String)> = scripts
+        .iter()
+        .filter(|(name, _)| *name != "_")
+        .filter_map(|(name, value)| {
+            let script = get_script_cmd(value)?;
+            let description = value
+                .get("help")
+                .and_then(|help| help.as_str())
+                .map(|help| help.to_string())
+                .unwrap_or_else(|| script.description());
+            Some((name.clone(), script, description))
+        })
+        .collect();
+    pdm_scripts.sort_by(|a, b| a.0.cmp(&b.0));
+    pdm_scripts
+}
+
+pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
+    let scripts = PyProjectToml::get_default_project()
+        .ok()
+        .and_then(|pyproject| pyproject.get_pdm_scripts())
+        .unwrap_or_default();
+    Ok(parse_pdm_scripts(&scripts)
+        .iter()
+        .map(|(name, _, description)| task!(name, "pdm", description))
+        .collect())
+}
+
+pub fn run_task(
+    task: &str,
+    task_args: &[&str],
+    global_args: &[&str],
+    verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    let mut args = vec![];
+    args.push("run");
+    args.extend(global_args);
+    args.push(task);
+    args.extend(task_args);
+    if let Some(pdm) = get_uv_tool_path("pdm") {
+        run_command(&pdm, &args, verbose)
+    } else {
+        run_command("pdm", &args, verbose)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pdm_scripts() {
+        let text = r#"
+[tool.pdm.scripts]
+_.env_file = ".env"
+start = "flask run -p 54321"
+lint = { shell = "ruff check . && ruff format --check .", help = "Lint the code" }
+migrate = { call = "app.db:migrate", env = { FLASK_ENV = "development" } }
+all = { composite = ["lint", "test --cov"] }
+broken = "echo 'unclosed"
+"#;
+        let pyproject: PyProjectToml = toml::from_str(text).unwrap();
+        let scripts = parse_pdm_scripts(&pyproject.get_pdm_scripts().unwrap());
+        let descriptions: Vec<(&str, &str)> = scripts
+            .iter()
+            .map(|(name, _, description)| (name.as_str(), description.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("all", "lint && test --cov"),
+                ("lint", "Lint the code"),
+                ("migrate", "call app.db:migrate"),
+                ("start", "flask run -p 54321")
+            ],
+            descriptions
+        );
+        assert!(matches!(&scripts[2].1, Script::Call(_, env_vars, _) if env_vars["FLASK_ENV"] == "development"));
+    }
+}
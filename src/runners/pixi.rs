@@ -0,0 +1,247 @@
+//! pixi: `[tasks]` and `[feature.<name>.tasks]` of pixi.toml, or `[tool.pixi.*]` of pyproject.toml, executed by `pixi run`.
+//! Tasks of features are named as `env:task` for every environment with the feature.
+use crate::command_utils::{run_command, CommandOutput};
+use crate::errors::KeeperError;
+use crate::models::Task;
+use crate::task;
+use error_stack::{IntoReport, Report, ResultExt};
+use std::collections::HashMap;
+use which::which;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PixiTask {
+    pub name: String,
+    /// environment to run the task, and None for tasks of the default feature
+    pub environment: Option<String>,
+    pub cmd: Option<String>,
+    pub depends_on: Vec<String>,
+    pub cwd: Option<String>,
+    pub env: HashMap<String, String>,
+    pub description: Option<String>,
+}
+
+impl PixiTask {
+    pub fn task_name(&self) -> String {
+        match &self.environment {
+            Some(environment) => format!("{}:{}", environment, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// `description`, or the command with dependencies and cwd
+    pub fn summary(&self) -> String {
+        if let Some(description) = &self.description {
+            return description.clone();
+        }
+        let mut summary = self.cmd.clone().unwrap_or_default();
+        if !self.depends_on.is_empty() {
+            summary = format!("{} [depends-on: {}]", summary, self.depends_on.join(", "));
+        }
+        if let Some(cwd) = &self.cwd {
+            summary = format!("{} [cwd: {}]", summary, cwd);
+        }
+        summary.trim().to_string()
+    }
+}
+
+fn pyproject_has_pixi() -> bool {
+    std::fs::read_to_string("pyproject.toml")
+        .map(|text| text.contains("[tool.pixi"))
+        .unwrap_or(false)
+}
+
+pub fn is_available() -> bool {
+    std::env::current_dir()
+        .map(|dir| dir.join("pixi.toml").exists())
+        .unwrap_or(false)
+        || pyproject_has_pixi()
+}
+
+pub fn is_command_available() -> bool {
+    which("pixi").is_ok()
+}
+
+fn string_items(value: Option<&toml::Value>) -> Vec<String> {
+    match value {
+        Some(toml::Value::String(item)) => vec![item.clone()],
+        Some(toml::Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| {
+                // `depends-on = [{ task = "build", args = ["--release"] }]`
+                item.as_str().or(item.get("task").and_then(|task| task.as_str()))
+            })
+            .map(|item| item.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// `build = "cargo build"`, `check = ["ruff check", "mypy"]` or a table with cmd, depends-on, cwd, env and description
+fn parse_task(name: &str, value: &toml::Value, environment: Option<String>) -> PixiTask {
+    let mut pixi_task = PixiTask {
+        name: name.to_string(),
+        environment,
+        ..Default::default()
+    };
+    match value {
+        toml::Value::Table(options) => {
+            let cmd = string_items(options.get("cmd"));
+            if !cmd.is_empty() {
+                pixi_task.cmd = Some(cmd.join(" "));
+            }
+            pixi_task.depends_on = string_items(options.get("depends-on").or(options.get("depends_on")));
+            pixi_task.cwd = options.get("cwd").and_then(|cwd| cwd.as_str()).map(|cwd| cwd.to_string());
+            pixi_task.description = options
+                .get("description")
+                .and_then(|description| description.as_str())
+                .map(|description| description.to_string());
+            if let Some(env) = options.get("env").and_then(|env| env.as_table()) {
+                pixi_task.env = env
+                    .iter()
+                    .filter_map(|(key, value)| value.as_str().map(|value| (key.clone(), value.to_string())))
+                    .collect();
+            }
+        }
+        _ => pixi_task.cmd = Some(string_items(Some(value)).join(" ")),
+    }
+    pixi_task
+}
+
+fn parse_tasks(tasks: Option<&toml::Value>, environment: Option<String>) -> Vec<PixiTask> {
+    tasks
+        .and_then(|tasks| tasks.as_table())
+        .map(|tasks| {
+            tasks
+                .iter()
+                .map(|(name, value)| parse_task(name, value, environment.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// features of environments: `test = ["test"]` or `test = { features = ["test"] }`
+fn parse_environments(manifest: &toml::Table) -> Vec<(String, Vec<String>)> {
+    manifest
+        .get("environments")
+        .and_then(|environments| environments.as_table())
+        .map(|environments| {
+            environments
+                .iter()
+                .map(|(name, value)| {
+                    let features = match value {
+                        toml::Value::Table(options) => string_items(options.get("features")),
+                        _ => string_items(Some(value)),
+                    };
+                    (name.clone(), features)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// tasks of the manifest, which is pixi.toml or `[tool.pixi]` of pyproject.toml
+fn parse_pixi_manifest(manifest: &toml::Table) -> Vec<PixiTask> {
+    let mut pixi_tasks = parse_tasks(manifest.get("tasks"), None);
+    let environments = parse_environments(manifest);
+    if let Some(features) = manifest.get("feature").and_then(|features| features.as_table()) {
+        for (feature_name, feature) in features {
+            for (environment, _) in environments
+                .iter()
+                .filter(|(_, features)| features.contains(feature_name))
+            {
+                pixi_tasks.extend(parse_tasks(feature.get("tasks"), Some(environment.clone())));
+            }
+        }
+    }
+    pixi_tasks.sort_by_key(|pixi_task| pixi_task.task_name());
+    pixi_tasks
+}
+
+fn read_pixi_manifest() -> Result<toml::Table, Report<KeeperError>> {
+    if let Ok(text) = std::fs::read_to_string("pixi.toml") {
+        return text
+            .parse::<toml::Table>()
+            .map_err(|e| KeeperError::InvalidPixiToml(e.to_string()).into_report());
+    }
+    let text = std::fs::read_to_string("pyproject.toml")
+        .change_context(KeeperError::InvalidPixiToml("pyproject.toml not found".to_string()))?;
+    let pyproject = text
+        .parse::<toml::Table>()
+        .map_err(|e| KeeperError::InvalidPixiToml(e.to_string()).into_report())?;
+    Ok(pyproject
+        .get("tool")
+        .and_then(|tool| tool.get("pixi"))
+        .and_then(|pixi| pixi.as_table())
+        .cloned()
+        .unwrap_or_default())
+}
+
+pub fn list_pixi_tasks() -> Result<Vec<PixiTask>, Report<KeeperError>> {
+    Ok(parse_pixi_manifest(&read_pixi_manifest()?))
+}
+
+pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
+    Ok(list_pixi_tasks()?
+        .iter()
+        .map(|pixi_task| task!(pixi_task.task_name(), "pixi-tasks", pixi_task.summary()))
+        .collect())
+}
+
+pub fn run_task(
+    task: &str,
+    task_args: &[&str],
+    global_args: &[&str],
+    verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    let pixi_task = list_pixi_tasks()?
+        .into_iter()
+        .find(|pixi_task| pixi_task.task_name() == task)
+        .ok_or_else(|| KeeperError::TaskNotFound(task.to_string()).into_report())?;
+    let mut args = vec!["run"];
+    args.extend(global_args);
+    if let Some(environment) = &pixi_task.environment {
+        args.push("-e");
+        args.push(environment);
+    }
+    args.push(&pixi_task.name);
+    args.extend(task_args);
+    run_command("pixi", &args, verbose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pixi_manifest() {
+        let text = r#"
+[workspace]
+name = "analysis"
+channels = ["conda-forge"]
+platforms = ["linux-64", "osx-arm64"]
+
+[tasks]
+fetch = { cmd = "python fetch.py", cwd = "scripts", env = { DATA_DIR = "data" } }
+train = { cmd = ["python", "train.py"], depends-on = ["fetch"], description = "Train the model" }
+all = { depends-on = ["fetch", "train"] }
+
+[feature.test.tasks]
+test = "pytest"
+
+[feature.lint.tasks]
+lint = "ruff check ."
+
+[environments]
+test = ["test"]
+ci = { features = ["test", "lint"], solve-group = "default" }
+"#;
+        let pixi_tasks = parse_pixi_manifest(&text.parse::<toml::Table>().unwrap());
+        let names: Vec<String> = pixi_tasks.iter().map(|pixi_task| pixi_task.task_name()).collect();
+        assert_eq!(vec!["all", "ci:lint", "ci:test", "fetch", "test:test", "train"], names);
+        assert_eq!("[depends-on: fetch, train]", pixi_tasks[0].summary());
+        assert_eq!("python fetch.py [cwd: scripts]", pixi_tasks[3].summary());
+        assert_eq!(Some(&"data".to_string()), pixi_tasks[3].env.get("DATA_DIR"));
+        assert_eq!("Train the model", pixi_tasks[5].summary());
+        assert_eq!(Some("python train.py".to_string()), pixi_tasks[5].cmd);
+    }
+}
@@ -0,0 +1,211 @@
+//! Turborepo: tasks of `tasks`(or `pipeline` of turbo 1.x) in turbo.json are named as `turbo:task`, not to clash with
+//! scripts of the root package.json, and tasks of workspace packages are named as `package:task`.
+//! Tasks are executed by `turbo run` with the package manager's launcher.
+use crate::command_utils::{run_command, CommandOutput};
+use crate::common::workspace::find_workspace_packages;
+use crate::common::{get_node_bin_command, is_node_bin_available, parse_package_json};
+use crate::errors::KeeperError;
+use crate::models::Task;
+use crate::task;
+use error_stack::{Report, ResultExt};
+use jsonc_parser::parse_to_serde_value;
+use std::env;
+
+const TURBO_FILE_NAMES: [&str; 2] = ["turbo.json", "turbo.jsonc"];
+
+/// task in turbo.json, `package` for `web#build`
+#[derive(Debug, Clone, Default, PartialEq)]
+struct TurboTask {
+    name: String,
+    package: Option<String>,
+    depends_on: Vec<String>,
+    persistent: bool,
+    cache: bool,
+}
+
+impl TurboTask {
+    fn summary(&self) -> String {
+        let mut items = vec![];
+        if !self.depends_on.is_empty() {
+            items.push(format!("depends on {}", self.depends_on.join(", ")));
+        }
+        if self.persistent {
+            items.push("persistent".to_owned());
+        }
+        if !self.cache {
+            items.push("no cache".to_owned());
+        }
+        items.join("; ")
+    }
+}
+
+pub fn is_available() -> bool {
+    env::current_dir()
+        .map(|dir| TURBO_FILE_NAMES.iter().any(|name| dir.join(name).exists()))
+        .unwrap_or(false)
+}
+
+pub fn is_command_available() -> bool {
+    is_node_bin_available("turbo")
+}
+
+fn parse_turbo_json(text: &str) -> Option<Vec<TurboTask>> {
+    let json: serde_json::Value = parse_to_serde_value(text, &Default::default()).ok()?;
+    let tasks = json.get("tasks").or(json.get("pipeline"))?.as_object()?;
+    Some(
+        tasks
+            .iter()
+            .map(|(name, options)| {
+                // `web#build` for the package, `//#format` for the root package
+                let (package, name) = match name.split_once('#') {
+                    Some(("//", name)) => (None, name),
+                    Some((package, name)) => (Some(package.to_string()), name),
+                    None => (None, name.as_str()),
+                };
+                TurboTask {
+                    name: name.to_string(),
+                    package,
+                    depends_on: options["dependsOn"]
+                        .as_array()
+                        .map(|items| {
+                            items
+                                .iter()
+                                .filter_map(|item| item.as_str())
+                                .map(|item| item.to_string())
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    persistent: options["persistent"].as_bool().unwrap_or(false),
+                    cache: options["cache"].as_bool().unwrap_or(true),
+                }
+            })
+            .collect(),
+    )
+}
+
+fn read_turbo_tasks() -> Result<Vec<TurboTask>, Report<KeeperError>> {
+    let current_dir = env::current_dir().unwrap();
+    let turbo_file = TURBO_FILE_NAMES
+        .iter()
+        .map(|name| current_dir.join(name))
+        .find(|path| path.exists())
+        .ok_or(KeeperError::InvalidTurboJson)?;
+    let text = std::fs::read_to_string(turbo_file).change_context(KeeperError::InvalidTurboJson)?;
+    parse_turbo_json(&text).ok_or(KeeperError::InvalidTurboJson.into())
+}
+
+/// `turbo:task` for all packages, and `package:task` for packages with the script of the task
+pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
+    let turbo_tasks = read_turbo_tasks()?;
+    let package_json = parse_package_json().unwrap_or_default();
+    let packages = find_workspace_packages(&package_json);
+    let mut tasks: Vec<Task> = vec![];
+    for turbo_task in turbo_tasks.iter().filter(|turbo_task| turbo_task.package.is_none()) {
+        tasks.push(task!(format!("turbo:{}", turbo_task.name), "turbo", turbo_task.summary()));
+    }
+    for package in &packages {
+        let scripts = package.package_json.scripts.clone().unwrap_or_default();
+        for turbo_task in &turbo_tasks {
+            let matched = match &turbo_task.package {
+                Some(name) => name == &package.name,
+                None => !turbo_tasks.iter().any(|item| {
+                    item.name == turbo_task.name && item.package.as_ref() == Some(&package.name)
+                }),
+            };
+            if matched && let Some(script) = scripts.get(&turbo_task.name) {
+                tasks.push(task!(
+                    format!("{}:{}", package.name, turbo_task.name),
+                    "turbo",
+                    script
+                ));
+            }
+        }
+    }
+    Ok(tasks)
+}
+
+/// `package:task` to the package and the task, `turbo:task` to the task of all packages,
+/// and `test:unit` is a task if `test` is not a package
+fn split_task_name(task: &str, package_names: &[String]) -> (Option<String>, String) {
+    if let Some(task_name) = task.strip_prefix("turbo:") {
+        return (None, task_name.to_string());
+    }
+    package_names
+        .iter()
+        .filter(|name| task.starts_with(&format!("{}:", name)))
+        .max_by_key(|name| name.len())
+        .map(|name| (Some(name.clone()), task[name.len() + 1..].to_string()))
+        .unwrap_or((None, task.to_string()))
+}
+
+pub fn run_task(
+    task: &str,
+    task_args: &[&str],
+    global_args: &[&str],
+    verbose: bool,
+) -> Result<CommandOutput, Report<KeeperError>> {
+    let package_json = parse_package_json()?;
+    let package_names: Vec<String> = find_workspace_packages(&package_json)
+        .into_iter()
+        .map(|package| package.name)
+        .collect();
+    let (package, task_name) = split_task_name(task, &package_names);
+    let command = get_node_bin_command("turbo");
+    let mut args: Vec<&str> = command[1..].iter().map(|item| item.as_str()).collect();
+    args.push("run");
+    args.push(&task_name);
+    let filter = package.map(|package| format!("--filter={}", package));
+    if let Some(filter) = &filter {
+        args.push(filter);
+    }
+    args.extend(global_args);
+    if !task_args.is_empty() {
+        args.push("--");
+        args.extend(task_args);
+    }
+    run_command(&command[0], &args, verbose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_turbo_json() {
+        let text = r#"{
+  // turbo 2.x
+  "$schema": "https://turbo.build/schema.json",
+  "tasks": {
+    "build": { "dependsOn": ["^build"], "outputs": ["dist/**"] },
+    "dev": { "cache": false, "persistent": true },
+    "web#lint": {},
+    "//#format": {}
+  }
+}"#;
+        let turbo_tasks = parse_turbo_json(text).unwrap();
+        assert_eq!(4, turbo_tasks.len());
+        let build = turbo_tasks.iter().find(|item| item.name == "build").unwrap();
+        assert_eq!("depends on ^build", build.summary());
+        let dev = turbo_tasks.iter().find(|item| item.name == "dev").unwrap();
+        assert_eq!("persistent; no cache", dev.summary());
+        let lint = turbo_tasks.iter().find(|item| item.name == "lint").unwrap();
+        assert_eq!(Some("web".to_string()), lint.package);
+        let format = turbo_tasks.iter().find(|item| item.name == "format").unwrap();
+        assert_eq!(None, format.package);
+        let pipeline = parse_turbo_json(r#"{"pipeline": {"test": {}}}"#).unwrap();
+        assert_eq!("test", pipeline[0].name);
+    }
+
+    #[test]
+    fn test_split_task_name() {
+        let package_names = vec!["web".to_string(), "@acme/ui".to_string()];
+        assert_eq!((Some("web".to_string()), "build".to_string()), split_task_name("web:build", &package_names));
+        assert_eq!(
+            (Some("@acme/ui".to_string()), "test:unit".to_string()),
+            split_task_name("@acme/ui:test:unit", &package_names)
+        );
+        assert_eq!((None, "test:unit".to_string()), split_task_name("test:unit", &package_names));
+        assert_eq!((None, "build".to_string()), split_task_name("turbo:build", &package_names));
+        assert_eq!((None, "test:unit".to_string()), split_task_name("turbo:test:unit", &package_names));
+    }
+}
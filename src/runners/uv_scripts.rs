@@ -1,4 +1,4 @@
-use crate::command_utils::{run_command, CommandOutput};
+use crate::command_utils::{run_command, run_command_by_shell, CommandOutput};
 use crate::common::pyproject::PyProjectToml;
 use crate::common::pyproject_toml_has_tool;
 use crate::errors::KeeperError;
@@ -10,9 +10,20 @@ use std::path::{Path, PathBuf};
 use toml::Value;
 use which::which;
 
-/// implement feature from https://rye.astral.sh/guide/pyproject/#toolryescripts
+/// implement feature from https://rye.astral.sh/guide/pyproject/#toolryescripts,
+/// and console entry points of `[project.scripts]` are executed by `uv run`
 pub fn is_available() -> bool {
-    pyproject_toml_has_tool("rye")
+    pyproject_toml_has_tool("rye") || has_project_scripts()
+}
+
+fn has_project_scripts() -> bool {
+    std::env::current_dir()
+        .map(|dir| {
+            std::fs::read_to_string(dir.join("pyproject.toml"))
+                .map(|text| text.contains("[project.scripts]"))
+                .unwrap_or(false)
+        })
+        .unwrap_or(false)
 }
 
 pub fn is_command_available() -> bool {
@@ -27,20 +38,39 @@ pub fn list_tasks() -> Result<Vec<Task>, Report<KeeperError>> {
                 tasks.push(task!(name, "uvs", description));
             });
         }
+        if let Some(scripts) = pyproject.get_project_scripts() {
+            let mut entry_points: Vec<(&String, &String)> = scripts
+                .iter()
+                .filter(|(name, _)| !tasks.iter().any(|task| &task.name == *name))
+                .collect();
+            entry_points.sort();
+            for (name, entry_point) in entry_points {
+                tasks.push(task!(name, "uvs", entry_point));
+            }
+        }
     }
     Ok(tasks)
 }
 
 pub fn run_task(
     task: &str,
-    _task_args: &[&str],
-    _global_args: &[&str],
+    task_args: &[&str],
+    global_args: &[&str],
     verbose: bool,
 ) -> Result<CommandOutput, Report<KeeperError>> {
     let project = PyProjectToml::get_default_project().unwrap();
     if let Some(script_value) = project.get_uv_script(task) {
         let script = get_script_cmd(&script_value);
         invoke_script(&project, &script.unwrap(), verbose)
+    } else if project
+        .get_project_scripts()
+        .is_some_and(|scripts| scripts.contains_key(task))
+    {
+        let mut args = vec!["run"];
+        args.extend(global_args);
+        args.push(task);
+        args.extend(task_args);
+        run_command("uv", &args, verbose)
     } else {
         Err(KeeperError::TaskNotFound(task.to_owned()).into_report())
     }
@@ -56,14 +86,31 @@ pub enum Script {
     Call(String, EnvVars, EnvFile),
     /// A command alias
     Cmd(Vec<String>, EnvVars, EnvFile),
+    /// A command line executed by the shell
+    Shell(String, EnvVars, EnvFile),
     /// A multi-script execution
     Chain(Vec<Vec<String>>),
 }
 
+impl Script {
+    pub fn description(&self) -> String {
+        match self {
+            Script::Call(entry, _, _) => format!("call {}", entry),
+            Script::Cmd(command_and_args, _, _) => command_and_args.join(" "),
+            Script::Shell(command_line, _, _) => command_line.clone(),
+            Script::Chain(commands) => commands
+                .iter()
+                .map(|command| command.join(" "))
+                .collect::<Vec<String>>()
+                .join(" && "),
+        }
+    }
+}
+
 pub fn get_script_cmd(tom_value: &Value) -> Option<Script> {
     match &tom_value {
         Value::String(cmd_text) => {
-            let command_and_args = shlex::split(cmd_text).unwrap();
+            let command_and_args = shlex::split(cmd_text)?;
             Some(Script::Cmd(command_and_args, HashMap::new(), None))
         }
         Value::Array(arr) => {
@@ -88,28 +135,36 @@ pub fn get_script_cmd(tom_value: &Value) -> Option<Script> {
             } else {
                 HashMap::new()
             };
+            // `env-file` of rye, and `env_file` of PDM
+            let env_file: EnvFile = table
+                .get("env-file")
+                .or(table.get("env_file"))
+                .and_then(|env_file| env_file.as_str())
+                .map(PathBuf::from);
             if let Some(cmd) = table.get("cmd") {
                 match cmd {
                     Value::String(cmd_text) => {
-                        let command_and_args = shlex::split(cmd_text).unwrap();
-                        Some(Script::Cmd(command_and_args, env_hash_map, None))
+                        let command_and_args = shlex::split(cmd_text)?;
+                        Some(Script::Cmd(command_and_args, env_hash_map, env_file))
                     }
                     Value::Array(arr) => {
                         let command_and_args: Vec<String> =
                             arr.iter().map(|item| item.to_string().trim_matches( &['"', '\'']).to_string()).collect();
-                        Some(Script::Cmd(command_and_args, env_hash_map, None))
+                        Some(Script::Cmd(command_and_args, env_hash_map, env_file))
                     }
                     _ => None,
                 }
+            } else if let Some(Value::String(shell_text)) = table.get("shell") {
+                Some(Script::Shell(shell_text.clone(), env_hash_map, env_file))
             } else if let Some(call) = table.get("call") {
                 match call {
                     Value::String(call_text) => {
                         let callable = call_text.to_string();
-                        return Some(Script::Call(callable, env_hash_map, None));
+                        return Some(Script::Call(callable, env_hash_map, env_file));
                     }
                     _ => None,
                 }
-            } else if let Some(chain) = table.get("chain") {
+            } else if let Some(chain) = table.get("chain").or(table.get("composite")) {
                 match chain {
                     Value::Array(chain_arr) => {
                         let commands: Vec<Vec<String>> = chain_arr
@@ -134,6 +189,17 @@ pub fn get_script_cmd(tom_value: &Value) -> Option<Script> {
     }
 }
 
+/// load the env file of the script, and a missing file is skipped because it's usually ignored by git
+fn load_env_file(env_file: &EnvFile) -> Result<(), Report<KeeperError>> {
+    if let Some(env_file_path) = env_file
+        && env_file_path.is_file()
+    {
+        dotenvx_rs::from_path(env_file_path)
+            .map_err(|e| KeeperError::InvalidEnvFile(format!("{}: {}", env_file_path.display(), e)).into_report())?;
+    }
+    Ok(())
+}
+
 fn invoke_script(
     pyproject: &PyProjectToml,
     script: &Script,
@@ -168,9 +234,7 @@ fn invoke_script(
                     }
                 }
             }
-            if let Some(env_file_path) = env_file {
-                dotenvx_rs::from_path(env_file_path).unwrap()
-            }
+            load_env_file(env_file)?;
             let real_args: Vec<&str> = args.iter().map(String::as_str).collect();
             let py = pyproject.venv_bin_path().join("python3");
             run_command(py.to_str().unwrap(), &real_args, verbose)
@@ -188,9 +252,7 @@ fn invoke_script(
                     }
                 }
             }
-            if let Some(env_file_path) = env_file {
-                dotenvx_rs::from_path(env_file_path).unwrap()
-            }
+            load_env_file(env_file)?;
             let script_target = std::env::current_dir().unwrap().join(&script_args[0]);
             if script_target.exists() && script_target.is_file() {
                 let args: Vec<&str> = script_args.into_iter().map(String::as_str).collect();
@@ -201,6 +263,15 @@ fn invoke_script(
                 run_command(command_name, &args, verbose)
             }
         }
+        Script::Shell(command_line, env_vars, env_file) => {
+            for (key, value) in env_vars {
+                unsafe {
+                    std::env::set_var(key, value);
+                }
+            }
+            load_env_file(env_file)?;
+            run_command_by_shell(command_line, verbose)
+        }
         Script::Chain(commands) => {
             if commands.is_empty() {
                 eprintln!("Please supply at least one command to chain");
@@ -1,6 +1,8 @@
 use crate::command_utils::{capture_command_output, run_command, CommandOutput};
 use crate::errors::KeeperError;
 use crate::models::Task;
+use crate::polyglot;
+use colored::Colorize;
 use crate::task;
 use std::io::{BufRead, BufReader};
 use error_stack::Report;
@@ -41,6 +43,21 @@ pub fn run_task(
     global_args: &[&str],
     verbose: bool,
 ) -> Result<CommandOutput, Report<KeeperError>> {
+    if polyglot::rust::is_available()
+        && which::which("rustup").is_ok()
+        && let Some(channel) = polyglot::rust::get_default_version()
+        && !polyglot::rust::is_toolchain_installed(&channel)
+    {
+        println!(
+            "{}",
+            format!(
+                "[tk] Rust {} from rust-toolchain is not installed, please run `tk install` or `rustup toolchain install {}`",
+                channel, channel
+            )
+            .bold()
+            .yellow()
+        );
+    }
     let mut args = vec![];
     args.push("-q");
     args.push("xtask");